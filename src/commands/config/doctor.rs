@@ -0,0 +1,1343 @@
+//! `config validate` / `config doctor`: check the local config and its
+//! `include` target without loading them, reporting every problem at once
+//! with its file, line and column.
+
+use super::marked::{self, Mark, Node, NodeKind, Seg};
+use crate::config::{self, Config, ProjectCommand, Step};
+use crate::utils::browser;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub file: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// `file:line:column`, or just `file` when the position is unknown.
+    pub fn location(&self) -> String {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => format!("{}:{}:{}", self.file, line, column),
+            _ => self.file.clone(),
+        }
+    }
+}
+
+/// Expected shape of a config value.
+#[derive(Clone, Copy)]
+enum Kind {
+    Str,
+    /// A string or a list of strings.
+    StrOrList,
+    Bool,
+    /// Unsigned integer with an inclusive maximum.
+    Uint(u64),
+    StrList,
+    /// Environment variable names to string values.
+    Env,
+    /// Command keys or inline [`STEP`] mappings.
+    Steps,
+    List(&'static Schema),
+    Map(&'static Schema),
+}
+
+/// Fields of one config mapping. Mirrors the serde structs in `config.rs`;
+/// anything missed here still surfaces through the serde fallback in
+/// [`check_typed`].
+struct Schema {
+    what: &'static str,
+    /// Field naming a list item (`name` / `key`).
+    id_field: &'static str,
+    /// Severity of a repeated id among siblings.
+    duplicate: Severity,
+    fields: &'static [(&'static str, Kind)],
+}
+
+static COMMAND: Schema = Schema {
+    what: "command",
+    id_field: "key",
+    // Lookups take the first match, so later duplicates are unreachable.
+    duplicate: Severity::Warning,
+    fields: &[
+        ("key", Kind::Str),
+        ("aliases", Kind::StrList),
+        ("url", Kind::Str),
+        ("command", Kind::Str),
+        ("browser", Kind::Str),
+        ("args", Kind::Str),
+        ("cwd", Kind::Str),
+        ("steps", Kind::Steps),
+        ("parallel", Kind::Bool),
+        ("webview", Kind::Bool),
+        ("pinned", Kind::Bool),
+    ],
+};
+
+static STEP: Schema = Schema {
+    what: "step",
+    id_field: "",
+    duplicate: Severity::Error,
+    fields: &[
+        ("url", Kind::Str),
+        ("command", Kind::Str),
+        ("browser", Kind::Str),
+        ("args", Kind::Str),
+        ("cwd", Kind::Str),
+        ("webview", Kind::Bool),
+    ],
+};
+
+static PROJECT: Schema = Schema {
+    what: "project",
+    id_field: "name",
+    duplicate: Severity::Error,
+    fields: &[
+        ("name", Kind::Str),
+        ("path", Kind::Str),
+        ("description", Kind::Str),
+        ("browser", Kind::Str),
+        ("env", Kind::Env),
+        ("onEnter", Kind::StrList),
+        ("onLeave", Kind::StrList),
+        ("commands", Kind::List(&COMMAND)),
+    ],
+};
+
+static CLIENT: Schema = Schema {
+    what: "client",
+    id_field: "name",
+    duplicate: Severity::Warning,
+    fields: &[
+        ("name", Kind::Str),
+        ("path", Kind::Str),
+        ("description", Kind::Str),
+        ("browser", Kind::Str),
+        ("env", Kind::Env),
+        ("onEnter", Kind::StrList),
+        ("onLeave", Kind::StrList),
+        ("commands", Kind::List(&COMMAND)),
+        ("projects", Kind::List(&PROJECT)),
+    ],
+};
+
+static SHORTCUTS: Schema = Schema {
+    what: "shortcuts",
+    id_field: "",
+    duplicate: Severity::Error,
+    fields: &[
+        ("enabled", Kind::Bool),
+        ("extraPaths", Kind::StrList),
+        ("exclude", Kind::StrList),
+    ],
+};
+
+static REPOS: Schema = Schema {
+    what: "repos",
+    id_field: "",
+    duplicate: Severity::Error,
+    fields: &[
+        ("roots", Kind::StrList),
+        ("depth", Kind::Uint(u32::MAX as u64)),
+        ("editor", Kind::Str),
+        ("terminal", Kind::Str),
+    ],
+};
+
+static PROVIDER: Schema = Schema {
+    what: "provider",
+    id_field: "name",
+    // Items point back to their provider by name.
+    duplicate: Severity::Error,
+    fields: &[
+        ("name", Kind::Str),
+        ("command", Kind::Str),
+        ("timeout", Kind::Uint(u64::MAX)),
+        ("cacheTtl", Kind::Uint(u64::MAX)),
+        ("sync", Kind::Bool),
+    ],
+};
+
+static WEBSERVER: Schema = Schema {
+    what: "webserver",
+    id_field: "",
+    duplicate: Severity::Error,
+    fields: &[
+        ("enabled", Kind::Bool),
+        ("command", Kind::Str),
+        ("distro", Kind::Str),
+        ("port", Kind::Uint(u16::MAX as u64)),
+    ],
+};
+
+static ROOT: Schema = Schema {
+    what: "config",
+    id_field: "",
+    duplicate: Severity::Error,
+    fields: &[
+        ("include", Kind::StrOrList),
+        ("currentClient", Kind::Str),
+        ("currentProject", Kind::Str),
+        ("previousClient", Kind::Str),
+        ("previousProject", Kind::Str),
+        ("defaultBrowser", Kind::Str),
+        ("global", Kind::List(&COMMAND)),
+        ("env", Kind::Env),
+        ("shortcuts", Kind::Map(&SHORTCUTS)),
+        ("repos", Kind::Map(&REPOS)),
+        ("providers", Kind::List(&PROVIDER)),
+        ("webserver", Kind::Map(&WEBSERVER)),
+        ("monitor", Kind::Uint(u32::MAX as u64)),
+        ("hookTimeout", Kind::Uint(u64::MAX)),
+        ("historySize", Kind::Uint(u32::MAX as u64)),
+        ("clients", Kind::List(&CLIENT)),
+    ],
+};
+
+/// One config file as the doctor sees it.
+struct ConfigFile {
+    path: PathBuf,
+    /// Position-aware tree; `None` when the YAML is malformed.
+    root: Option<Node>,
+    /// Typed view; `None` when the file doesn't match the schema.
+    config: Option<Config>,
+}
+
+#[derive(Default)]
+struct Report {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Report {
+    fn push(&mut self, severity: Severity, file: &Path, mark: Option<Mark>, message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            file: file.display().to_string(),
+            line: mark.map(|m| m.line),
+            column: mark.map(|m| m.column),
+            message,
+        });
+    }
+
+    fn errors_in(&self, file: &Path) -> bool {
+        let file = file.display().to_string();
+        self.diagnostics
+            .iter()
+            .any(|d| d.severity == Severity::Error && d.file == file)
+    }
+}
+
+/// " in <context>", or nothing at the top level.
+fn within(context: &str) -> String {
+    if context.is_empty() {
+        String::new()
+    } else {
+        format!(" in {}", context)
+    }
+}
+
+/// Check the local config file and its include target.
+pub fn run() -> anyhow::Result<Vec<Diagnostic>> {
+    let local_path = config::local_config_path()?;
+    let mut report = Report::default();
+    if !local_path.exists() {
+        report.push(
+            Severity::Warning,
+            &local_path,
+            None,
+            "config file does not exist yet; it is created on first run".to_string(),
+        );
+        return Ok(report.diagnostics);
+    }
+    check_files(&local_path, &mut report);
+    Ok(report.diagnostics)
+}
+
+fn check_files(local_path: &Path, report: &mut Report) {
+    let mut files = Vec::new();
+    collect_files(local_path, &mut Vec::new(), &mut files, report);
+
+    // Files are lowest priority first, the local file last.
+    let configs: Option<Vec<Config>> = files.iter().map(|f| f.config.clone()).collect();
+    match configs {
+        Some(configs) => check_merged(&config::merge_all(configs), &files, report),
+        None => report.push(
+            Severity::Warning,
+            local_path,
+            None,
+            "selection, command, path and browser checks skipped until the errors are fixed"
+                .to_string(),
+        ),
+    }
+}
+
+/// `include` entries of a file with their positions.
+fn include_entries(root: &Node) -> Vec<(String, Mark)> {
+    let Some(include) = root.get("include") else {
+        return Vec::new();
+    };
+    match &include.kind {
+        NodeKind::Seq(items) => items
+            .iter()
+            .filter_map(|item| item.as_str().map(|s| (s.to_string(), item.mark)))
+            .collect(),
+        _ => include
+            .as_str()
+            .map(|s| vec![(s.to_string(), include.mark)])
+            .unwrap_or_default(),
+    }
+}
+
+/// Load `path` and everything it includes into `files` in merge order (each
+/// file after the files it includes), as the loader does.
+fn collect_files(
+    path: &Path,
+    chain: &mut Vec<PathBuf>,
+    files: &mut Vec<ConfigFile>,
+    report: &mut Report,
+) {
+    let file = load_file(path, report);
+    let entries = file.root.as_ref().map(include_entries).unwrap_or_default();
+    chain.push(config::canonical_path(path));
+    for (entry, mark) in entries {
+        let resolved = config::resolve_include(&entry, path);
+        if !resolved.exists() {
+            report.push(
+                Severity::Warning,
+                path,
+                Some(mark),
+                format!("included config not found: {}", resolved.display()),
+            );
+            continue;
+        }
+        let key = config::canonical_path(&resolved);
+        if let Some(cycle) = config::include_cycle(chain, &key) {
+            report.push(
+                Severity::Error,
+                path,
+                Some(mark),
+                format!("include cycle: {}", cycle),
+            );
+            continue;
+        }
+        if files.iter().any(|f| config::canonical_path(&f.path) == key) {
+            continue;
+        }
+        collect_files(&resolved, chain, files, report);
+    }
+    chain.pop();
+    files.push(file);
+}
+
+fn load_file(path: &Path, report: &mut Report) -> ConfigFile {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) => {
+            report.push(Severity::Error, path, None, format!("cannot read: {}", e));
+            return ConfigFile {
+                path: path.to_path_buf(),
+                root: None,
+                config: None,
+            };
+        }
+    };
+
+    let root = match marked::parse(&source) {
+        Ok(mut root) => {
+            migrate_keys(&mut root, path, report);
+            check_map(&root, &ROOT, "", path, report);
+            Some(root)
+        }
+        Err((mark, message)) => {
+            report.push(Severity::Error, path, Some(mark), message);
+            None
+        }
+    };
+
+    let config = root
+        .as_ref()
+        .and_then(|_| check_typed(&source, path, report));
+    ConfigFile {
+        path: path.to_path_buf(),
+        root,
+        config,
+    }
+}
+
+/// Old-schema files (`projects:` / `currentProject:` at the top level) are
+/// migrated on load; check them as the loader will see them.
+fn migrate_keys(root: &mut Node, path: &Path, report: &mut Report) {
+    let NodeKind::Map(entries) = &mut root.kind else {
+        return;
+    };
+    if !entries.iter().any(|(k, _)| k.as_str() == Some("projects")) {
+        return;
+    }
+    for (key, _) in entries.iter_mut() {
+        let renamed = match key.as_str() {
+            Some("projects") => "clients",
+            Some("currentProject") => "currentClient",
+            _ => continue,
+        };
+        report.push(
+            Severity::Warning,
+            path,
+            Some(key.mark),
+            format!(
+                "old schema: '{}' is renamed to '{}' on next load",
+                key.as_str().unwrap_or_default(),
+                renamed
+            ),
+        );
+        key.kind = NodeKind::Scalar(renamed.to_string(), false);
+    }
+}
+
+/// Parse with serde as the loader does. Anything the schema walk missed is
+/// reported here so the doctor never passes a file the loader rejects.
+fn check_typed(source: &str, path: &Path, report: &mut Report) -> Option<Config> {
+    let migrated = config::migrate_schema(source)
+        .map(|(s, _)| s)
+        .unwrap_or_else(|_| source.to_string());
+    let walked_clean = !report.errors_in(path);
+    match serde_yaml::from_str::<Config>(&migrated) {
+        Ok(config) => {
+            if walked_clean {
+                if let Err(e) = config::validate_commands(&config) {
+                    report.push(Severity::Error, path, None, format!("{e:#}"));
+                }
+            }
+            Some(config)
+        }
+        Err(e) => {
+            if walked_clean {
+                let mark = e.location().map(|l| Mark {
+                    line: l.line(),
+                    column: l.column(),
+                });
+                report.push(Severity::Error, path, mark, e.to_string());
+            }
+            None
+        }
+    }
+}
+
+fn check_map(node: &Node, schema: &Schema, context: &str, path: &Path, report: &mut Report) {
+    let NodeKind::Map(entries) = &node.kind else {
+        report.push(
+            Severity::Error,
+            path,
+            Some(node.mark),
+            format!("{} must be a mapping", describe(schema, context)),
+        );
+        return;
+    };
+
+    let mut seen: Vec<&str> = Vec::new();
+    for (key, value) in entries {
+        let Some(name) = key.as_str() else {
+            report.push(
+                Severity::Error,
+                path,
+                Some(key.mark),
+                format!("keys must be strings{}", within(context)),
+            );
+            continue;
+        };
+        if seen.contains(&name) {
+            report.push(
+                Severity::Error,
+                path,
+                Some(key.mark),
+                format!("duplicate key '{}'{}", name, within(context)),
+            );
+            continue;
+        }
+        seen.push(name);
+
+        match schema.fields.iter().find(|(field, _)| *field == name) {
+            Some((field, kind)) => check_value(value, *kind, field, context, path, report),
+            None => {
+                let hint = suggest(name, schema)
+                    .map(|s| format!(" (did you mean '{}'?)", s))
+                    .unwrap_or_default();
+                report.push(
+                    Severity::Error,
+                    path,
+                    Some(key.mark),
+                    format!("unknown field '{}'{}{}", name, within(context), hint),
+                );
+            }
+        }
+    }
+}
+
+fn describe(schema: &Schema, context: &str) -> String {
+    if context.is_empty() {
+        format!("the {}", schema.what)
+    } else {
+        context.to_string()
+    }
+}
+
+fn check_value(
+    node: &Node,
+    kind: Kind,
+    field: &str,
+    context: &str,
+    path: &Path,
+    report: &mut Report,
+) {
+    let mut wrong = |expected: &str| {
+        report.push(
+            Severity::Error,
+            path,
+            Some(node.mark),
+            format!("'{}'{} must be {}", field, within(context), expected),
+        );
+    };
+    match kind {
+        Kind::Str => {
+            if !matches!(node.kind, NodeKind::Scalar(..)) {
+                wrong("a string");
+            }
+        }
+        Kind::Bool => {
+            let ok = matches!(&node.kind, NodeKind::Scalar(s, false)
+                if matches!(s.as_str(), "true" | "True" | "TRUE" | "false" | "False" | "FALSE"));
+            if !ok {
+                wrong("true or false");
+            }
+        }
+        Kind::Uint(max) => {
+            let ok = node.is_null()
+                || matches!(&node.kind, NodeKind::Scalar(s, false)
+                    if s.parse::<u64>().is_ok_and(|n| n <= max));
+            if !ok {
+                wrong(&format!("a whole number from 0 to {}", max));
+            }
+        }
+        Kind::StrOrList if matches!(node.kind, NodeKind::Scalar(..)) => {}
+        Kind::StrList | Kind::StrOrList => {
+            if node.is_null() {
+                return;
+            }
+            match &node.kind {
+                NodeKind::Seq(items) => {
+                    for item in items {
+                        if !matches!(item.kind, NodeKind::Scalar(..)) || item.is_null() {
+                            report.push(
+                                Severity::Error,
+                                path,
+                                Some(item.mark),
+                                format!("'{}'{} entries must be strings", field, within(context)),
+                            );
+                        }
+                    }
+                }
+                _ if matches!(kind, Kind::StrOrList) => wrong("a path or a list of paths"),
+                _ => wrong("a list of strings"),
+            }
+        }
+        Kind::Env => {
+            if node.is_null() {
+                return;
+            }
+            let NodeKind::Map(entries) = &node.kind else {
+                wrong("a map of variable names to values");
+                return;
+            };
+            for (key, value) in entries {
+                let name = key.as_str().unwrap_or_default();
+                if !config::valid_env_name(name) {
+                    report.push(
+                        Severity::Error,
+                        path,
+                        Some(key.mark),
+                        format!(
+                            "'{}'{} has an invalid variable name '{}'",
+                            field,
+                            within(context),
+                            name
+                        ),
+                    );
+                } else if !matches!(value.kind, NodeKind::Scalar(..)) || value.is_null() {
+                    report.push(
+                        Severity::Error,
+                        path,
+                        Some(value.mark),
+                        format!("'{}.{}'{} must be a string", field, name, within(context)),
+                    );
+                }
+            }
+        }
+        Kind::Steps => {
+            if node.is_null() {
+                return;
+            }
+            let NodeKind::Seq(items) = &node.kind else {
+                wrong("a list of command keys or actions");
+                return;
+            };
+            for (index, item) in items.iter().enumerate() {
+                let context = format!("step {} of {}", index + 1, context);
+                match item.kind {
+                    NodeKind::Scalar(..) if !item.is_null() => {}
+                    NodeKind::Map(_) => {
+                        check_map(item, &STEP, &context, path, report);
+                        check_command_conflict(item, false, &context, path, report);
+                    }
+                    _ => report.push(
+                        Severity::Error,
+                        path,
+                        Some(item.mark),
+                        format!("{} must be a command key or a mapping", context),
+                    ),
+                }
+            }
+        }
+        Kind::List(schema) => {
+            if node.is_null() {
+                return;
+            }
+            if !matches!(node.kind, NodeKind::Seq(_)) {
+                wrong("a list");
+                return;
+            }
+            let parent = if context.is_empty() && field == "global" {
+                "global commands"
+            } else {
+                context
+            };
+            check_list(node, schema, parent, path, report);
+        }
+        Kind::Map(schema) => {
+            if !node.is_null() {
+                check_map(node, schema, &format!("'{}'", field), path, report);
+            }
+        }
+    }
+}
+
+fn check_list(node: &Node, schema: &Schema, parent: &str, path: &Path, report: &mut Report) {
+    let mut seen: Vec<(&str, Mark)> = Vec::new();
+    for (index, item) in node.items().iter().enumerate() {
+        let id = item.get(schema.id_field).and_then(Node::as_str);
+        let context = match id {
+            Some(id) => format!("{} '{}'{}", schema.what, id, within(parent)),
+            None => format!("{} #{}{}", schema.what, index + 1, within(parent)),
+        };
+        if !matches!(item.kind, NodeKind::Map(_)) {
+            report.push(
+                Severity::Error,
+                path,
+                Some(item.mark),
+                format!("{} must be a mapping", context),
+            );
+            continue;
+        }
+        check_map(item, schema, &context, path, report);
+
+        let Some(id) = id else {
+            report.push(
+                Severity::Error,
+                path,
+                Some(item.mark),
+                format!("{} is missing '{}'", context, schema.id_field),
+            );
+            continue;
+        };
+        if let Some((_, first)) = seen.iter().find(|(seen_id, _)| *seen_id == id) {
+            let consequence = match schema.duplicate {
+                Severity::Error => "",
+                Severity::Warning => " and is never used",
+            };
+            report.push(
+                schema.duplicate,
+                path,
+                Some(item.mark),
+                format!(
+                    "duplicate {} (first defined on line {}){}",
+                    context, first.line, consequence
+                ),
+            );
+        } else {
+            seen.push((id, item.mark));
+        }
+
+        if std::ptr::eq(schema, &COMMAND) {
+            let has_steps = item.get("steps").is_some_and(|v| !v.is_null());
+            check_command_conflict(item, has_steps, &context, path, report);
+        }
+    }
+}
+
+/// Report a command or inline step whose action fields contradict each other.
+fn check_command_conflict(
+    item: &Node,
+    has_steps: bool,
+    context: &str,
+    path: &Path,
+    report: &mut Report,
+) {
+    let has = |field: &str| item.get(field).is_some_and(|v| !v.is_null());
+    let webview = item.get("webview").and_then(Node::as_str) == Some("true");
+    if let Some(problem) = config::command_conflict(
+        has("url"),
+        has("command"),
+        has("browser"),
+        webview,
+        has_steps,
+    ) {
+        report.push(
+            Severity::Error,
+            path,
+            Some(item.mark),
+            format!("{} {}", context, problem),
+        );
+    }
+}
+
+/// A known field close to a misspelled `name`.
+fn suggest(name: &str, schema: &Schema) -> Option<&'static str> {
+    schema
+        .fields
+        .iter()
+        .map(|(field, _)| *field)
+        .find(|field| field.eq_ignore_ascii_case(name) || edit_distance(field, name) <= 2)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (diagonal + usize::from(ca != *cb))
+                .min(row[j] + 1)
+                .min(above + 1);
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// Where `path` is written, preferring the highest-priority file (the one
+/// whose value wins the merge). Falls back to the local file.
+fn locate<'f>(files: &'f [ConfigFile], path: &[Seg]) -> (&'f Path, Option<Mark>) {
+    let local = files
+        .last()
+        .map(|f| f.path.as_path())
+        .unwrap_or(Path::new(""));
+    files
+        .iter()
+        .rev()
+        .find_map(|f| {
+            let node = f.root.as_ref()?.at(path)?;
+            Some((f.path.as_path(), Some(node.mark)))
+        })
+        .unwrap_or((local, None))
+}
+
+/// A command list in the merged config and how to find it in a file.
+struct CommandList<'a> {
+    path: Vec<Seg<'a>>,
+    context: String,
+    commands: &'a [ProjectCommand],
+}
+
+/// Every command list in the merged config: global, each client, each project.
+fn command_lists(config: &Config) -> Vec<CommandList<'_>> {
+    let mut lists = vec![CommandList {
+        path: vec![Seg::Key("global")],
+        context: "global commands".to_string(),
+        commands: config.global.as_deref().unwrap_or_default(),
+    }];
+    for client in &config.clients {
+        lists.push(CommandList {
+            path: vec![
+                Seg::Key("clients"),
+                Seg::Named("name", &client.name),
+                Seg::Key("commands"),
+            ],
+            context: format!("client '{}'", client.name),
+            commands: client.commands.as_deref().unwrap_or_default(),
+        });
+        for project in client.projects.iter().flatten() {
+            lists.push(CommandList {
+                path: vec![
+                    Seg::Key("clients"),
+                    Seg::Named("name", &client.name),
+                    Seg::Key("projects"),
+                    Seg::Named("name", &project.name),
+                    Seg::Key("commands"),
+                ],
+                context: format!("project '{}' in client '{}'", project.name, client.name),
+                commands: project.commands.as_deref().unwrap_or_default(),
+            });
+        }
+    }
+    lists
+}
+
+/// Checks that only make sense once the local file and its include are
+/// merged: stale selection, shadowed commands, unknown step keys, missing
+/// directories and browsers.
+fn check_merged(config: &Config, files: &[ConfigFile], report: &mut Report) {
+    check_selection(config, files, report);
+    check_shadowed(config, files, report);
+
+    let lists = command_lists(config);
+    for list in &lists {
+        for cmd in list.commands {
+            let mut path = list.path.clone();
+            path.push(Seg::Named("key", &cmd.key));
+            if cmd.url.is_none() && cmd.command.is_none() && cmd.steps.is_none() {
+                let (file, mark) = locate(files, &path);
+                report.push(
+                    Severity::Warning,
+                    file,
+                    mark,
+                    format!(
+                        "command '{}' in {} has no 'url', 'command' or 'steps'",
+                        cmd.key, list.context
+                    ),
+                );
+            }
+            // Which command a key names depends on the selection, so only
+            // flag keys that no scope defines
+            for (i, step) in cmd.steps.iter().flatten().enumerate() {
+                let Step::Key(key) = step else {
+                    continue;
+                };
+                if !lists
+                    .iter()
+                    .flat_map(|l| l.commands)
+                    .any(|c| c.is_named(key))
+                {
+                    let mut steps_path = path.clone();
+                    steps_path.push(Seg::Key("steps"));
+                    let (file, mark) = locate(files, &steps_path);
+                    report.push(
+                        Severity::Warning,
+                        file,
+                        mark,
+                        format!(
+                            "step {} of command '{}' in {} refers to unknown command '{}'",
+                            i + 1,
+                            cmd.key,
+                            list.context,
+                            key
+                        ),
+                    );
+                }
+            }
+            if let Some(ref b) = cmd.browser {
+                path.push(Seg::Key("browser"));
+                check_browser(b, files, &path, report);
+            }
+        }
+    }
+
+    if let Some(ref b) = config.default_browser {
+        check_browser(b, files, &[Seg::Key("defaultBrowser")], report);
+    }
+    for client in &config.clients {
+        let client_path = [Seg::Key("clients"), Seg::Named("name", &client.name)];
+        check_entry(&client.path, &client.browser, &client_path, files, report);
+        for project in client.projects.iter().flatten() {
+            let project_path = [
+                Seg::Key("clients"),
+                Seg::Named("name", &client.name),
+                Seg::Key("projects"),
+                Seg::Named("name", &project.name),
+            ];
+            check_entry(
+                &project.path,
+                &project.browser,
+                &project_path,
+                files,
+                report,
+            );
+        }
+    }
+}
+
+fn check_selection(config: &Config, files: &[ConfigFile], report: &mut Report) {
+    let Some(ref client_name) = config.current_client else {
+        return;
+    };
+    let Some(client) = config.clients.iter().find(|c| &c.name == client_name) else {
+        let (file, mark) = locate(files, &[Seg::Key("currentClient")]);
+        report.push(
+            Severity::Warning,
+            file,
+            mark,
+            format!("currentClient '{}' does not match any client", client_name),
+        );
+        return;
+    };
+    if let Some(ref project_name) = config.current_project {
+        let exists = client
+            .projects
+            .iter()
+            .flatten()
+            .any(|p| &p.name == project_name);
+        if !exists {
+            let (file, mark) = locate(files, &[Seg::Key("currentProject")]);
+            report.push(
+                Severity::Warning,
+                file,
+                mark,
+                format!(
+                    "currentProject '{}' is not a project of client '{}'",
+                    project_name, client_name
+                ),
+            );
+        }
+    }
+}
+
+fn defines(commands: &Option<Vec<ProjectCommand>>, key: &str) -> bool {
+    commands.iter().flatten().any(|c| c.key == key)
+}
+
+/// Commands that narrower scopes always override: a global command every
+/// client redefines is never used, and a client command every project
+/// redefines is only used with the client itself selected.
+fn check_shadowed(config: &Config, files: &[ConfigFile], report: &mut Report) {
+    if !config.clients.is_empty() {
+        for cmd in config.global.iter().flatten() {
+            if config
+                .clients
+                .iter()
+                .all(|c| defines(&c.commands, &cmd.key))
+            {
+                let (file, mark) =
+                    locate(files, &[Seg::Key("global"), Seg::Named("key", &cmd.key)]);
+                report.push(
+                    Severity::Warning,
+                    file,
+                    mark,
+                    format!(
+                        "global command '{}' is overridden by every client and is never used",
+                        cmd.key
+                    ),
+                );
+            }
+        }
+    }
+
+    for client in &config.clients {
+        let projects = client.projects.as_deref().unwrap_or_default();
+        if projects.is_empty() {
+            continue;
+        }
+        for cmd in client.commands.iter().flatten() {
+            if projects.iter().all(|p| defines(&p.commands, &cmd.key)) {
+                let (file, mark) = locate(
+                    files,
+                    &[
+                        Seg::Key("clients"),
+                        Seg::Named("name", &client.name),
+                        Seg::Key("commands"),
+                        Seg::Named("key", &cmd.key),
+                    ],
+                );
+                report.push(
+                    Severity::Warning,
+                    file,
+                    mark,
+                    format!(
+                        "command '{}' in client '{}' is overridden by every project; it is only used with the client itself selected",
+                        cmd.key, client.name
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Directory and browser checks for a client or project at `entry`.
+fn check_entry(
+    dir: &Option<String>,
+    browser: &Option<String>,
+    entry: &[Seg],
+    files: &[ConfigFile],
+    report: &mut Report,
+) {
+    if let Some(dir) = dir {
+        let resolved = config::expand_tilde(dir);
+        if !resolved.is_dir() {
+            let mut path = entry.to_vec();
+            path.push(Seg::Key("path"));
+            let (file, mark) = locate(files, &path);
+            let problem = if resolved.exists() {
+                "is not a directory"
+            } else {
+                "does not exist"
+            };
+            report.push(
+                Severity::Warning,
+                file,
+                mark,
+                format!("path '{}' {}", dir, problem),
+            );
+        }
+    }
+    if let Some(browser) = browser {
+        let mut path = entry.to_vec();
+        path.push(Seg::Key("browser"));
+        check_browser(browser, files, &path, report);
+    }
+}
+
+fn check_browser(browser: &str, files: &[ConfigFile], path: &[Seg], report: &mut Report) {
+    if !browser::browser_available(browser) {
+        let (file, mark) = locate(files, path);
+        report.push(
+            Severity::Warning,
+            file,
+            mark,
+            format!("browser '{}' was not found on this machine", browser),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write `local` (and `shared`, referenced via `include`) to a fresh temp
+    /// dir and run every check on them.
+    fn diagnose(name: &str, local: &str, shared: Option<&str>) -> Vec<Diagnostic> {
+        let dir = std::env::temp_dir().join(format!("ps-doctor-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let local_path = dir.join("local.yml");
+        let mut contents = local.to_string();
+        if let Some(shared) = shared {
+            let shared_path = dir.join("shared.yml");
+            fs::write(&shared_path, shared).unwrap();
+            contents = format!("include: {}\n{}", shared_path.display(), contents);
+        }
+        fs::write(&local_path, contents).unwrap();
+        let mut report = Report::default();
+        check_files(&local_path, &mut report);
+        fs::remove_dir_all(&dir).ok();
+        report.diagnostics
+    }
+
+    fn messages(diagnostics: &[Diagnostic]) -> Vec<String> {
+        diagnostics
+            .iter()
+            .map(|d| {
+                format!(
+                    "{}:{}: {}",
+                    d.line.unwrap_or(0),
+                    d.column.unwrap_or(0),
+                    d.message
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reports_every_schema_error_with_position() {
+        let diagnostics = diagnose(
+            "schema",
+            "\
+clients:
+- name: acme
+  pathh: /src
+  commands:
+  - key: ci
+    url: https://ci
+    command: make
+  - key: docs
+    webview: yes
+",
+            None,
+        );
+        let messages = messages(&diagnostics);
+        assert!(
+            messages.contains(
+                &"3:3: unknown field 'pathh' in client 'acme' (did you mean 'path'?)".to_string()
+            ),
+            "got: {:?}",
+            messages
+        );
+        assert!(
+            messages.iter().any(|m| m
+                .starts_with("5:5: command 'ci' in client 'acme' has both 'url' and 'command'")),
+            "got: {:?}",
+            messages
+        );
+        assert!(
+            messages.contains(
+                &"9:14: 'webview' in command 'docs' in client 'acme' must be true or false"
+                    .to_string()
+            ),
+            "got: {:?}",
+            messages
+        );
+    }
+
+    #[test]
+    fn reports_bad_env_names_and_values() {
+        let diagnostics = diagnose(
+            "env",
+            "\
+env:
+  AWS-PROFILE: acme
+clients:
+- name: acme
+  env:
+    KUBECONFIG: [a, b]
+",
+            None,
+        );
+        let messages = messages(&diagnostics);
+        assert!(
+            messages.contains(&"2:3: 'env' has an invalid variable name 'AWS-PROFILE'".to_string()),
+            "got: {:?}",
+            messages
+        );
+        assert!(
+            messages
+                .contains(&"6:17: 'env.KUBECONFIG' in client 'acme' must be a string".to_string()),
+            "got: {:?}",
+            messages
+        );
+    }
+
+    #[test]
+    fn checks_inline_steps() {
+        let diagnostics = diagnose(
+            "steps",
+            "\
+global:
+- key: morning
+  url: https://x
+  steps:
+  - docs
+  - command: make
+    browser: edge
+  - urll: https://y
+  - [a]
+",
+            None,
+        );
+        let messages = messages(&diagnostics);
+        for expected in [
+            "2:3: command 'morning' in global commands has 'steps' and a 'url' or 'command' — a command with steps only runs its steps",
+            "6:5: step 2 of command 'morning' in global commands has both 'command' and 'browser' — 'command' runs directly, not in a browser",
+            "8:5: unknown field 'urll' in step 3 of command 'morning' in global commands (did you mean 'url'?)",
+            "9:5: step 4 of command 'morning' in global commands must be a command key or a mapping",
+        ] {
+            assert!(
+                messages.contains(&expected.to_string()),
+                "missing {:?} in {:?}",
+                expected,
+                messages
+            );
+        }
+    }
+
+    #[test]
+    fn step_keys_match_commands_by_key_or_alias_in_any_scope() {
+        let diagnostics = diagnose(
+            "step-keys",
+            "\
+global:
+- key: morning
+  steps: [Chat, deploy, nope]
+clients:
+- name: acme
+  commands:
+  - key: chat
+    aliases: [slack]
+    url: https://chat
+  - key: evening
+    steps: [slack]
+  projects:
+  - name: api
+    commands:
+    - key: deploy
+      command: make deploy
+",
+            None,
+        );
+        assert_eq!(
+            messages(&diagnostics),
+            ["3:10: step 3 of command 'morning' in global commands refers to unknown command 'nope'"]
+        );
+    }
+
+    #[test]
+    fn steps_count_as_a_command_body() {
+        let diagnostics = diagnose(
+            "body",
+            "global:\n- key: docs\n  url: https://x\n- key: chain\n  steps: [docs]\n- key: empty\n",
+            None,
+        );
+        assert_eq!(
+            messages(&diagnostics),
+            ["6:3: command 'empty' in global commands has no 'url', 'command' or 'steps'"]
+        );
+    }
+
+    #[test]
+    fn syntax_error_is_reported_once() {
+        let diagnostics = diagnose("syntax", "clients: [\n", None);
+        let errors: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+            .collect();
+        assert_eq!(errors.len(), 1, "got: {:?}", diagnostics);
+        assert!(errors[0].line.is_some());
+    }
+
+    #[test]
+    fn errors_in_include_point_at_included_file() {
+        let diagnostics = diagnose(
+            "include",
+            "clients: []\n",
+            Some("clients:\n- name: team\n  colour: red\n"),
+        );
+        let error = diagnostics
+            .iter()
+            .find(|d| d.severity == Severity::Error)
+            .expect("an error");
+        assert!(error.file.ends_with("shared.yml"), "got: {:?}", error);
+        assert_eq!((error.line, error.column), (Some(3), Some(3)));
+    }
+
+    #[test]
+    fn warns_on_stale_selection_and_missing_path() {
+        let diagnostics = diagnose(
+            "stale",
+            "\
+currentClient: acme
+currentProject: gone
+clients:
+- name: acme
+  path: /definitely/not/a/real/dir
+",
+            None,
+        );
+        let messages = messages(&diagnostics);
+        assert!(
+            messages.contains(
+                &"2:17: currentProject 'gone' is not a project of client 'acme'".to_string()
+            ),
+            "got: {:?}",
+            messages
+        );
+        assert!(
+            messages.contains(&"5:9: path '/definitely/not/a/real/dir' does not exist".to_string()),
+            "got: {:?}",
+            messages
+        );
+        assert!(diagnostics.iter().all(|d| d.severity == Severity::Warning));
+    }
+
+    #[test]
+    fn warns_on_shadowed_and_duplicate_commands() {
+        let diagnostics = diagnose(
+            "shadow",
+            "\
+global:
+- key: docs
+  url: https://docs
+clients:
+- name: acme
+  commands:
+  - key: docs
+    url: https://acme/docs
+  - key: docs
+    url: https://acme/docs2
+",
+            None,
+        );
+        let messages = messages(&diagnostics);
+        assert!(
+            messages.contains(
+                &"2:3: global command 'docs' is overridden by every client and is never used"
+                    .to_string()
+            ),
+            "got: {:?}",
+            messages
+        );
+        assert!(
+            messages.contains(&"9:5: duplicate command 'docs' in client 'acme' (first defined on line 7) and is never used".to_string()),
+            "got: {:?}",
+            messages
+        );
+    }
+
+    #[test]
+    fn local_field_is_located_before_included_one() {
+        let diagnostics = diagnose(
+            "locate",
+            "clients:\n- name: team\n  browser: no-such-browser-xyz\n",
+            Some("clients:\n- name: team\n  browser: also-missing-xyz\n"),
+        );
+        let browser = diagnostics
+            .iter()
+            .find(|d| d.message.contains("no-such-browser-xyz"))
+            .expect("browser warning");
+        assert!(browser.file.ends_with("local.yml"), "got: {:?}", browser);
+        assert!(!diagnostics
+            .iter()
+            .any(|d| d.message.contains("also-missing-xyz")));
+    }
+
+    #[test]
+    fn clean_config_has_no_diagnostics() {
+        let diagnostics = diagnose(
+            "clean",
+            "currentClient: acme\nclients:\n- name: acme\n  commands:\n  - key: ci\n    url: https://ci\n",
+            None,
+        );
+        assert!(diagnostics.is_empty(), "got: {:?}", diagnostics);
+    }
+
+    #[test]
+    fn include_cycle_is_reported_at_the_include_entry() {
+        let dir = std::env::temp_dir().join(format!("ps-doctor-cycle-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("shared.yml"),
+            "clients: []\ninclude: [local.yml]\n",
+        )
+        .unwrap();
+        let local = dir.join("local.yml");
+        fs::write(&local, "include: shared.yml\n").unwrap();
+
+        let mut report = Report::default();
+        check_files(&local, &mut report);
+        fs::remove_dir_all(&dir).ok();
+
+        let cycle = report
+            .diagnostics
+            .iter()
+            .find(|d| d.message.starts_with("include cycle: "))
+            .expect("cycle diagnostic");
+        assert_eq!(cycle.severity, Severity::Error);
+        assert!(cycle.file.ends_with("shared.yml"), "got: {:?}", cycle);
+        assert_eq!((cycle.line, cycle.column), (Some(2), Some(11)));
+    }
+}
@@ -0,0 +1,210 @@
+//! A YAML tree that remembers where each node was written, so diagnostics can
+//! point at a line and column. serde_yaml drops positions once parsing
+//! succeeds, so the config files are parsed a second time with yaml-rust2.
+
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust2::scanner::{Marker, TScalarStyle};
+
+/// 1-based line and column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mark {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl From<Marker> for Mark {
+    fn from(marker: Marker) -> Self {
+        // yaml-rust2 lines are 1-based, columns 0-based.
+        Self {
+            line: marker.line(),
+            column: marker.col() + 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind {
+    /// Scalar text and whether it was quoted (quoted scalars are always strings).
+    Scalar(String, bool),
+    Seq(Vec<Node>),
+    /// Key/value pairs in document order (duplicates kept).
+    Map(Vec<(Node, Node)>),
+    Alias,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub mark: Mark,
+    pub kind: NodeKind,
+}
+
+/// One step of a [`Node::at`] lookup.
+#[derive(Clone)]
+pub enum Seg<'a> {
+    /// Value of a mapping key.
+    Key(&'a str),
+    /// Sequence item whose `field` is the scalar `value`.
+    Named(&'a str, &'a str),
+}
+
+impl Node {
+    /// `~`, `null` or an empty plain scalar.
+    pub fn is_null(&self) -> bool {
+        matches!(&self.kind, NodeKind::Scalar(s, false) if matches!(s.as_str(), "" | "~" | "null" | "Null" | "NULL"))
+    }
+
+    /// Scalar text, or `None` for collections and null.
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.kind {
+            NodeKind::Scalar(s, _) if !self.is_null() => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The `(key, value)` entry for `key` in a mapping (the first, if repeated).
+    pub fn entry(&self, key: &str) -> Option<(&Node, &Node)> {
+        match &self.kind {
+            NodeKind::Map(entries) => entries
+                .iter()
+                .find(|(k, _)| k.as_str() == Some(key))
+                .map(|(k, v)| (k, v)),
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        self.entry(key).map(|(_, v)| v)
+    }
+
+    pub fn items(&self) -> &[Node] {
+        match &self.kind {
+            NodeKind::Seq(items) => items,
+            _ => &[],
+        }
+    }
+
+    /// Follow `path` from this node.
+    pub fn at(&self, path: &[Seg]) -> Option<&Node> {
+        path.iter().try_fold(self, |node, seg| match seg {
+            Seg::Key(key) => node.get(key),
+            Seg::Named(field, value) => node
+                .items()
+                .iter()
+                .find(|item| item.get(field).and_then(Node::as_str) == Some(*value)),
+        })
+    }
+}
+
+/// Parse the first document of `source`. An empty document is an empty
+/// mapping, matching how the config loader treats it.
+pub fn parse(source: &str) -> Result<Node, (Mark, String)> {
+    let mut builder = Builder::default();
+    Parser::new_from_str(source)
+        .load(&mut builder, false)
+        .map_err(|e| (Mark::from(*e.marker()), e.info().to_string()))?;
+    Ok(builder.root.unwrap_or(Node {
+        mark: Mark { line: 1, column: 1 },
+        kind: NodeKind::Map(Vec::new()),
+    }))
+}
+
+#[derive(Default)]
+struct Builder {
+    /// Open collections; a mapping collects a flat key, value, key, … list.
+    stack: Vec<(Mark, Vec<Node>, bool)>,
+    root: Option<Node>,
+}
+
+impl Builder {
+    fn push(&mut self, node: Node) {
+        match self.stack.last_mut() {
+            Some((_, children, _)) => children.push(node),
+            None => {
+                if self.root.is_none() {
+                    self.root = Some(node);
+                }
+            }
+        }
+    }
+
+    fn close(&mut self) {
+        let Some((mark, children, is_map)) = self.stack.pop() else {
+            return;
+        };
+        let node = if is_map {
+            let mut children = children.into_iter();
+            let mut entries = Vec::new();
+            while let (Some(k), Some(v)) = (children.next(), children.next()) {
+                entries.push((k, v));
+            }
+            // Point a mapping at its first key rather than at the `{`/indent.
+            let mark = entries.first().map_or(mark, |(k, _)| k.mark);
+            Node {
+                mark,
+                kind: NodeKind::Map(entries),
+            }
+        } else {
+            Node {
+                mark,
+                kind: NodeKind::Seq(children),
+            }
+        };
+        self.push(node);
+    }
+}
+
+impl MarkedEventReceiver for Builder {
+    fn on_event(&mut self, event: Event, marker: Marker) {
+        let mark = Mark::from(marker);
+        match event {
+            Event::Scalar(text, style, _, _) => self.push(Node {
+                mark,
+                kind: NodeKind::Scalar(text, style != TScalarStyle::Plain),
+            }),
+            Event::Alias(_) => self.push(Node {
+                mark,
+                kind: NodeKind::Alias,
+            }),
+            Event::SequenceStart(..) => self.stack.push((mark, Vec::new(), false)),
+            Event::MappingStart(..) => self.stack.push((mark, Vec::new(), true)),
+            Event::SequenceEnd | Event::MappingEnd => self.close(),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nodes_carry_one_based_positions() {
+        let root = parse("clients:\n- name: acme\n  path: /src\n").unwrap();
+        let path = root
+            .at(&[
+                Seg::Key("clients"),
+                Seg::Named("name", "acme"),
+                Seg::Key("path"),
+            ])
+            .unwrap();
+        assert_eq!(path.as_str(), Some("/src"));
+        assert_eq!(path.mark, Mark { line: 3, column: 9 });
+        let (key, _) = root.entry("clients").unwrap();
+        assert_eq!(key.mark, Mark { line: 1, column: 1 });
+    }
+
+    #[test]
+    fn syntax_errors_report_position() {
+        let (mark, message) = parse("a: 1\n b: [\n").unwrap_err();
+        assert_eq!(mark.line, 2);
+        assert!(message.contains("mapping values"), "got: {}", message);
+    }
+
+    #[test]
+    fn null_and_quoted_scalars() {
+        let root = parse("a: ~\nb: 'null'\nc:\n").unwrap();
+        assert!(root.get("a").unwrap().is_null());
+        assert_eq!(root.get("b").unwrap().as_str(), Some("null"));
+        assert!(root.get("c").unwrap().is_null());
+    }
+}
@@ -0,0 +1,211 @@
+//! `config` subcommands: inspect and check the config files.
+
+mod doctor;
+mod marked;
+mod show;
+
+use crate::config::{self, Config, ConfigManager};
+use crate::utils::output::{self, OutputFormat};
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use colored::*;
+use doctor::{Diagnostic, Severity};
+use serde::Serialize;
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Report every config error with its file, line and column
+    Validate,
+    /// Like validate, plus warnings: stale selection, shadowed commands,
+    /// missing directories and unknown browsers
+    Doctor,
+    /// Print the local config file
+    Show {
+        /// Print the merged config, annotating every value with the file it
+        /// came from and the merge rule that applied
+        #[arg(long)]
+        effective: bool,
+    },
+}
+
+#[derive(Serialize)]
+struct DoctorOutput {
+    errors: usize,
+    warnings: usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl DoctorOutput {
+    /// One `location: severity: message` line per diagnostic.
+    fn plain(&self) -> String {
+        self.diagnostics
+            .iter()
+            .map(|d| {
+                format!(
+                    "{}: {}: {}",
+                    d.location(),
+                    severity_label(d.severity),
+                    d.message
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn severity_label(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    }
+}
+
+pub fn execute(action: ConfigAction, format: Option<OutputFormat>) -> Result<()> {
+    match action {
+        ConfigAction::Validate => check(false, format),
+        ConfigAction::Doctor => check(true, format),
+        ConfigAction::Show { effective } => show(effective, format),
+    }
+}
+
+#[derive(Serialize)]
+struct SourceOutput {
+    label: String,
+    path: String,
+}
+
+#[derive(Serialize)]
+struct EffectiveOutput {
+    /// Lowest priority first.
+    sources: Vec<SourceOutput>,
+    config: Config,
+    provenance: Vec<show::Provenance>,
+}
+
+fn show(effective: bool, format: Option<OutputFormat>) -> Result<()> {
+    let config_manager = ConfigManager::new()?;
+    let mut sources = config_manager.sources()?;
+
+    if !effective {
+        let local = sources.pop().context("No local config")?;
+        return match format {
+            Some(format) => output::print(format, &local.config, |c| {
+                serde_yaml::to_string(c).unwrap_or_default()
+            }),
+            None => {
+                let text = std::fs::read_to_string(&local.path).with_context(|| {
+                    format!("Failed to read config file: {}", local.path.display())
+                })?;
+                print!("{}", text);
+                Ok(())
+            }
+        };
+    }
+
+    // The local file is always last; anything before it came via `include`
+    // and is labelled by file name (or full path when two names clash).
+    let file_name = |s: &config::ConfigSource| {
+        s.path.file_name().map_or_else(
+            || s.path.display().to_string(),
+            |n| n.to_string_lossy().into_owned(),
+        )
+    };
+    let labels: Vec<String> = sources
+        .iter()
+        .enumerate()
+        .map(|(i, source)| {
+            if i + 1 == sources.len() {
+                "local".to_string()
+            } else if sources[..sources.len() - 1]
+                .iter()
+                .filter(|s| file_name(s) == file_name(source))
+                .count()
+                > 1
+            {
+                source.path.display().to_string()
+            } else {
+                file_name(source)
+            }
+        })
+        .collect();
+    let merged = config::merge_all(sources.iter().map(|s| s.config.clone()));
+    let annotated = show::effective(&merged, &sources, &labels);
+
+    match format {
+        Some(format) => {
+            let report = EffectiveOutput {
+                sources: sources
+                    .iter()
+                    .zip(&labels)
+                    .map(|(s, label)| SourceOutput {
+                        label: label.clone(),
+                        path: s.path.display().to_string(),
+                    })
+                    .collect(),
+                config: merged,
+                provenance: annotated.provenance.clone(),
+            };
+            output::print(format, &report, |_| annotated.render(str::to_string))
+        }
+        None => {
+            for (source, label) in sources.iter().zip(&labels) {
+                println!(
+                    "{}",
+                    format!("# {}: {}", label, source.path.display()).dimmed()
+                );
+            }
+            println!(
+                "{}",
+                "# [replace] later file wins, [merge] combined field by field, [append] added after earlier files' entries"
+                    .dimmed()
+            );
+            println!("{}", annotated.render(|n| n.dimmed().to_string()));
+            Ok(())
+        }
+    }
+}
+
+/// Run the checks and exit non-zero when any error was found.
+fn check(include_warnings: bool, format: Option<OutputFormat>) -> Result<()> {
+    let diagnostics: Vec<Diagnostic> = doctor::run()?
+        .into_iter()
+        .filter(|d| include_warnings || d.severity == Severity::Error)
+        .collect();
+    let count = |severity| {
+        diagnostics
+            .iter()
+            .filter(|d| d.severity == severity)
+            .count()
+    };
+    let report = DoctorOutput {
+        errors: count(Severity::Error),
+        warnings: count(Severity::Warning),
+        diagnostics,
+    };
+
+    if let Some(format) = format {
+        output::print(format, &report, DoctorOutput::plain)?;
+    } else {
+        for d in &report.diagnostics {
+            let label = match d.severity {
+                Severity::Error => severity_label(d.severity).red().bold(),
+                Severity::Warning => severity_label(d.severity).yellow().bold(),
+            };
+            println!("{}: {}: {}", d.location().bold(), label, d.message);
+        }
+        if report.diagnostics.is_empty() {
+            println!("{}", "No problems found".green());
+        } else {
+            println!(
+                "\n{} error(s), {} warning(s)",
+                report.errors, report.warnings
+            );
+        }
+    }
+
+    if report.errors > 0 {
+        // The report is the error message; skip main's error handling.
+        std::process::exit(1);
+    }
+    Ok(())
+}
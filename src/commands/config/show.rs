@@ -0,0 +1,700 @@
+//! `config show`: print the config, or with `--effective` the merged config
+//! with every entry and field annotated with the file it came from and the
+//! overlay rule `merge_configs` applied.
+
+use crate::config::{Client, Config, ConfigSource, EnvMap, Include, Project, ProjectCommand, Step};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rule {
+    /// Several files set the value; the highest-priority one wins.
+    Replace,
+    /// An entry in several files, combined field by field.
+    Merge,
+    /// An entry only in a higher-priority file, added after the others.
+    Append,
+}
+
+/// Where one value of the effective config came from.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Provenance {
+    /// e.g. `clients[acme].commands[ci].url`.
+    pub path: String,
+    /// Labels of the files that set it, lowest priority first.
+    pub from: Vec<String>,
+    pub rule: Option<Rule>,
+}
+
+/// One rendered line of the annotated YAML.
+struct Line {
+    text: String,
+    note: Option<String>,
+}
+
+/// Reads one top-level string setting.
+type ConfigField = fn(&Config) -> &Option<String>;
+
+/// Reads one top-level number setting.
+type NumberField = fn(&Config) -> Option<u64>;
+
+/// An entry (client, project or command) as seen in each source file.
+type PerSource<'a, T> = Vec<Option<&'a T>>;
+
+/// Builds the annotated listing and the provenance records together.
+struct Walker<'a> {
+    labels: &'a [String],
+    lines: Vec<Line>,
+    provenance: Vec<Provenance>,
+}
+
+/// Indices of the sources where `has` holds.
+fn defined_in<T>(per_source: &[Option<&T>], has: impl Fn(&T) -> bool) -> Vec<usize> {
+    per_source
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.is_some_and(&has))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+fn field_rule(defined: &[usize]) -> Option<Rule> {
+    (defined.len() > 1).then_some(Rule::Replace)
+}
+
+/// `list_in`: sources that have a non-empty list the entry belongs to.
+fn entry_rule(defined: &[usize], list_in: &[usize]) -> Option<Rule> {
+    if defined.len() > 1 {
+        Some(Rule::Merge)
+    } else if defined.first() > list_in.first() {
+        Some(Rule::Append)
+    } else {
+        None
+    }
+}
+
+/// A YAML scalar, quoted where YAML needs it.
+fn scalar(value: &str) -> String {
+    serde_yaml::to_string(value)
+        .map(|s| s.trim_end().to_string())
+        .unwrap_or_else(|_| value.to_string())
+}
+
+/// How a list item is written inside a one-line `[a, b]` flow list.
+trait FlowItem {
+    fn flow(&self) -> String;
+}
+
+impl FlowItem for String {
+    fn flow(&self) -> String {
+        scalar(self)
+    }
+}
+
+impl FlowItem for Step {
+    fn flow(&self) -> String {
+        let step = match self {
+            Step::Key(key) => return scalar(key),
+            Step::Inline(step) => step,
+        };
+        let fields = [
+            ("url", &step.url),
+            ("command", &step.command),
+            ("browser", &step.browser),
+            ("args", &step.args),
+            ("cwd", &step.cwd),
+        ];
+        let mut parts: Vec<String> = fields
+            .iter()
+            .filter_map(|(name, value)| {
+                value
+                    .as_ref()
+                    .map(|value| format!("{}: {}", name, scalar(value)))
+            })
+            .collect();
+        if step.webview {
+            parts.push("webview: true".to_string());
+        }
+        format!("{{{}}}", parts.join(", "))
+    }
+}
+
+/// `[a, b]` on one line.
+fn flow_list<I: FlowItem>(items: &[I]) -> String {
+    format!(
+        "[{}]",
+        items
+            .iter()
+            .map(FlowItem::flow)
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+impl<'a> Walker<'a> {
+    fn push(
+        &mut self,
+        indent: usize,
+        text: String,
+        path: String,
+        defined: &[usize],
+        rule: Option<Rule>,
+    ) {
+        let from: Vec<String> = defined.iter().map(|&i| self.labels[i].clone()).collect();
+        let note = match rule {
+            Some(rule) => format!("{} [{}]", from.join(", "), rule_name(rule)),
+            None => from.join(", "),
+        };
+        self.lines.push(Line {
+            text: format!("{}{}", "  ".repeat(indent), text),
+            note: Some(note),
+        });
+        self.provenance.push(Provenance { path, from, rule });
+    }
+
+    /// An unannotated line (a list header).
+    fn header(&mut self, indent: usize, text: &str) {
+        self.lines.push(Line {
+            text: format!("{}{}", "  ".repeat(indent), text),
+            note: None,
+        });
+    }
+
+    fn string_field<T>(
+        &mut self,
+        indent: usize,
+        path: &str,
+        name: &str,
+        value: &Option<String>,
+        per_source: &[Option<&T>],
+        get: impl Fn(&T) -> &Option<String>,
+    ) {
+        if let Some(value) = value {
+            let defined = defined_in(per_source, |e| get(e).is_some());
+            self.push(
+                indent,
+                format!("{}: {}", name, scalar(value)),
+                format!("{}.{}", path, name),
+                &defined,
+                field_rule(&defined),
+            );
+        }
+    }
+
+    /// Lists like `onEnter` are replaced whole by the higher-priority file.
+    fn list_field<T, I: FlowItem>(
+        &mut self,
+        indent: usize,
+        path: &str,
+        name: &str,
+        value: &Option<Vec<I>>,
+        per_source: &[Option<&T>],
+        get: impl Fn(&T) -> &Option<Vec<I>>,
+    ) {
+        if let Some(value) = value {
+            let defined = defined_in(per_source, |e| get(e).is_some());
+            self.push(
+                indent,
+                format!("{}: {}", name, flow_list(value)),
+                format!("{}.{}", path, name),
+                &defined,
+                field_rule(&defined),
+            );
+        }
+    }
+
+    /// Flags are OR-ed across files, so every file setting one contributes.
+    fn flag_field<T>(
+        &mut self,
+        indent: usize,
+        path: &str,
+        name: &str,
+        value: bool,
+        per_source: &[Option<&T>],
+        get: impl Fn(&T) -> bool,
+    ) {
+        if value {
+            let defined = defined_in(per_source, get);
+            self.push(
+                indent,
+                format!("{}: true", name),
+                format!("{}.{}", path, name),
+                &defined,
+                None,
+            );
+        }
+    }
+
+    /// Environment maps merge per variable, so each one has its own source.
+    fn env<T>(
+        &mut self,
+        indent: usize,
+        path: &str,
+        merged: &Option<EnvMap>,
+        per_source: &[Option<&T>],
+        get: impl Fn(&T) -> &Option<EnvMap>,
+    ) {
+        let Some(merged) = merged else {
+            return;
+        };
+        self.header(indent, "env:");
+        let env_path = if path.is_empty() {
+            "env".to_string()
+        } else {
+            format!("{}.env", path)
+        };
+        for (name, value) in merged {
+            let defined = defined_in(per_source, |e| {
+                get(e).as_ref().is_some_and(|m| m.contains_key(name))
+            });
+            self.push(
+                indent + 1,
+                format!("{}: {}", name, scalar(value)),
+                format!("{}.{}", env_path, name),
+                &defined,
+                field_rule(&defined),
+            );
+        }
+    }
+
+    fn commands(
+        &mut self,
+        indent: usize,
+        path: &str,
+        name: &str,
+        merged: &Option<Vec<ProjectCommand>>,
+        lists: &[Option<&Vec<ProjectCommand>>],
+    ) {
+        let Some(merged) = merged else {
+            return;
+        };
+        self.header(indent, &format!("{}:", name));
+        let list_in = defined_in(lists, |l| !l.is_empty());
+        for cmd in merged {
+            let per_source: PerSource<ProjectCommand> = lists
+                .iter()
+                .map(|l| l.and_then(|l| l.iter().find(|c| c.key == cmd.key)))
+                .collect();
+            let defined = defined_in(&per_source, |_| true);
+            let cmd_path = format!("{}.{}[{}]", path, name, cmd.key);
+            self.push(
+                indent,
+                format!("- key: {}", scalar(&cmd.key)),
+                cmd_path.clone(),
+                &defined,
+                entry_rule(&defined, &list_in),
+            );
+            let i = indent + 1;
+            self.list_field(i, &cmd_path, "aliases", &cmd.aliases, &per_source, |c| {
+                &c.aliases
+            });
+            self.string_field(i, &cmd_path, "url", &cmd.url, &per_source, |c| &c.url);
+            self.string_field(i, &cmd_path, "command", &cmd.command, &per_source, |c| {
+                &c.command
+            });
+            self.string_field(i, &cmd_path, "browser", &cmd.browser, &per_source, |c| {
+                &c.browser
+            });
+            self.string_field(i, &cmd_path, "args", &cmd.args, &per_source, |c| &c.args);
+            self.string_field(i, &cmd_path, "cwd", &cmd.cwd, &per_source, |c| &c.cwd);
+            self.list_field(i, &cmd_path, "steps", &cmd.steps, &per_source, |c| &c.steps);
+            self.flag_field(i, &cmd_path, "parallel", cmd.parallel, &per_source, |c| {
+                c.parallel
+            });
+            self.flag_field(i, &cmd_path, "webview", cmd.webview, &per_source, |c| {
+                c.webview
+            });
+            self.flag_field(i, &cmd_path, "pinned", cmd.pinned, &per_source, |c| {
+                c.pinned
+            });
+        }
+    }
+
+    fn projects(
+        &mut self,
+        indent: usize,
+        path: &str,
+        client: &Client,
+        clients: &[Option<&Client>],
+    ) {
+        let Some(ref merged) = client.projects else {
+            return;
+        };
+        self.header(indent, "projects:");
+        let lists: Vec<Option<&Vec<Project>>> = clients
+            .iter()
+            .map(|c| c.and_then(|c| c.projects.as_ref()))
+            .collect();
+        let list_in = defined_in(&lists, |l| !l.is_empty());
+        for project in merged {
+            let per_source: PerSource<Project> = lists
+                .iter()
+                .map(|l| l.and_then(|l| l.iter().find(|p| p.name == project.name)))
+                .collect();
+            let defined = defined_in(&per_source, |_| true);
+            let project_path = format!("{}.projects[{}]", path, project.name);
+            self.push(
+                indent,
+                format!("- name: {}", scalar(&project.name)),
+                project_path.clone(),
+                &defined,
+                entry_rule(&defined, &list_in),
+            );
+            let i = indent + 1;
+            self.string_field(i, &project_path, "path", &project.path, &per_source, |p| {
+                &p.path
+            });
+            self.string_field(
+                i,
+                &project_path,
+                "description",
+                &project.description,
+                &per_source,
+                |p| &p.description,
+            );
+            self.string_field(
+                i,
+                &project_path,
+                "browser",
+                &project.browser,
+                &per_source,
+                |p| &p.browser,
+            );
+            self.env(i, &project_path, &project.env, &per_source, |p| &p.env);
+            self.list_field(
+                i,
+                &project_path,
+                "onEnter",
+                &project.on_enter,
+                &per_source,
+                |p| &p.on_enter,
+            );
+            self.list_field(
+                i,
+                &project_path,
+                "onLeave",
+                &project.on_leave,
+                &per_source,
+                |p| &p.on_leave,
+            );
+            let command_lists: Vec<_> = per_source
+                .iter()
+                .map(|p| p.and_then(|p| p.commands.as_ref()))
+                .collect();
+            self.commands(
+                i,
+                &project_path,
+                "commands",
+                &project.commands,
+                &command_lists,
+            );
+        }
+    }
+
+    fn clients(&mut self, merged: &[Client], sources: &[ConfigSource]) {
+        if merged.is_empty() {
+            return;
+        }
+        self.header(0, "clients:");
+        let list_in: Vec<usize> = sources
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| !s.config.clients.is_empty())
+            .map(|(i, _)| i)
+            .collect();
+        for client in merged {
+            let per_source: PerSource<Client> = sources
+                .iter()
+                .map(|s| s.config.clients.iter().find(|c| c.name == client.name))
+                .collect();
+            let defined = defined_in(&per_source, |_| true);
+            let path = format!("clients[{}]", client.name);
+            self.push(
+                0,
+                format!("- name: {}", scalar(&client.name)),
+                path.clone(),
+                &defined,
+                entry_rule(&defined, &list_in),
+            );
+            self.string_field(1, &path, "path", &client.path, &per_source, |c| &c.path);
+            self.string_field(
+                1,
+                &path,
+                "description",
+                &client.description,
+                &per_source,
+                |c| &c.description,
+            );
+            self.string_field(1, &path, "browser", &client.browser, &per_source, |c| {
+                &c.browser
+            });
+            self.env(1, &path, &client.env, &per_source, |c| &c.env);
+            self.list_field(1, &path, "onEnter", &client.on_enter, &per_source, |c| {
+                &c.on_enter
+            });
+            self.list_field(1, &path, "onLeave", &client.on_leave, &per_source, |c| {
+                &c.on_leave
+            });
+            let command_lists: Vec<_> = per_source
+                .iter()
+                .map(|c| c.and_then(|c| c.commands.as_ref()))
+                .collect();
+            self.commands(1, &path, "commands", &client.commands, &command_lists);
+            self.projects(1, &path, client, &per_source);
+        }
+    }
+
+    /// A section (`shortcuts`, `repos`, `providers`, `webserver`) the
+    /// higher-priority file replaces wholesale.
+    fn section<T: Serialize>(&mut self, name: &str, merged: &Option<T>, defined: &[usize]) {
+        let Some(value) = merged else {
+            return;
+        };
+        self.push(
+            0,
+            format!("{}:", name),
+            name.to_string(),
+            defined,
+            field_rule(defined),
+        );
+        let body = serde_yaml::to_string(value).unwrap_or_default();
+        for line in body.lines() {
+            self.header(1, line);
+        }
+    }
+}
+
+/// A `Rule` as its serialized name.
+fn rule_name(rule: Rule) -> &'static str {
+    match rule {
+        Rule::Replace => "replace",
+        Rule::Merge => "merge",
+        Rule::Append => "append",
+    }
+}
+
+/// The effective config with provenance, ready to print.
+pub struct Effective {
+    pub provenance: Vec<Provenance>,
+    lines: Vec<Line>,
+}
+
+impl Effective {
+    /// The annotated YAML listing; `note` styles the trailing comments.
+    pub fn render(&self, note: impl Fn(&str) -> String) -> String {
+        let width = self
+            .lines
+            .iter()
+            .filter(|l| l.note.is_some())
+            .map(|l| l.text.chars().count())
+            .max()
+            .unwrap_or(0);
+        self.lines
+            .iter()
+            .map(|l| match &l.note {
+                Some(n) => {
+                    let pad = width - l.text.chars().count();
+                    format!(
+                        "{}{}  {}",
+                        l.text,
+                        " ".repeat(pad),
+                        note(&format!("# {}", n))
+                    )
+                }
+                None => l.text.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Walk `merged` against `sources` (lowest priority first, each with a
+/// display label) and record where every value came from.
+pub fn effective(merged: &Config, sources: &[ConfigSource], labels: &[String]) -> Effective {
+    let mut walker = Walker {
+        labels,
+        lines: Vec::new(),
+        provenance: Vec::new(),
+    };
+    let configs: PerSource<Config> = sources.iter().map(|s| Some(&s.config)).collect();
+
+    // Only the local file's `include` survives the merge.
+    if let Some(ref include) = merged.include {
+        let text = match include {
+            Include::One(path) => scalar(path),
+            Include::Many(paths) => flow_list(paths),
+        };
+        walker.push(
+            0,
+            format!("include: {}", text),
+            "include".to_string(),
+            &[sources.len() - 1],
+            None,
+        );
+    }
+
+    let scalars: [(&str, ConfigField); 5] = [
+        ("currentClient", |c| &c.current_client),
+        ("currentProject", |c| &c.current_project),
+        ("previousClient", |c| &c.previous_client),
+        ("previousProject", |c| &c.previous_project),
+        ("defaultBrowser", |c| &c.default_browser),
+    ];
+    for (name, get) in scalars {
+        if let Some(value) = get(merged) {
+            let defined = defined_in(&configs, |c| get(c).is_some());
+            walker.push(
+                0,
+                format!("{}: {}", name, scalar(value)),
+                name.to_string(),
+                &defined,
+                field_rule(&defined),
+            );
+        }
+    }
+    let numbers: [(&str, NumberField); 3] = [
+        ("monitor", |c| c.monitor.map(u64::from)),
+        ("hookTimeout", |c| c.hook_timeout),
+        ("historySize", |c| c.history_size.map(|n| n as u64)),
+    ];
+    for (name, get) in numbers {
+        if let Some(value) = get(merged) {
+            let defined = defined_in(&configs, |c| get(c).is_some());
+            walker.push(
+                0,
+                format!("{}: {}", name, value),
+                name.to_string(),
+                &defined,
+                field_rule(&defined),
+            );
+        }
+    }
+
+    walker.env(0, "", &merged.env, &configs, |c| &c.env);
+
+    let global_lists: Vec<_> = sources.iter().map(|s| s.config.global.as_ref()).collect();
+    walker.commands(0, "", "global", &merged.global, &global_lists);
+
+    let defined = defined_in(&configs, |c| c.shortcuts.is_some());
+    walker.section("shortcuts", &merged.shortcuts, &defined);
+    let defined = defined_in(&configs, |c| c.repos.is_some());
+    walker.section("repos", &merged.repos, &defined);
+    let defined = defined_in(&configs, |c| c.providers.is_some());
+    walker.section("providers", &merged.providers, &defined);
+    let defined = defined_in(&configs, |c| c.webserver.is_some());
+    walker.section("webserver", &merged.webserver, &defined);
+
+    walker.clients(&merged.clients, sources);
+
+    Effective {
+        provenance: walker.provenance,
+        lines: walker.lines,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn source(yaml: &str) -> ConfigSource {
+        ConfigSource {
+            path: PathBuf::new(),
+            config: serde_yaml::from_str(yaml).unwrap(),
+        }
+    }
+
+    fn provenance_of(sources: &[ConfigSource]) -> Vec<Provenance> {
+        let merged =
+            crate::config::merge_configs(sources[0].config.clone(), sources[1].config.clone());
+        let labels = vec!["include".to_string(), "local".to_string()];
+        effective(&merged, sources, &labels).provenance
+    }
+
+    fn find<'a>(provenance: &'a [Provenance], path: &str) -> &'a Provenance {
+        provenance
+            .iter()
+            .find(|p| p.path == path)
+            .unwrap_or_else(|| panic!("no provenance for {}: {:?}", path, provenance))
+    }
+
+    #[test]
+    fn records_source_and_rule_for_entries_and_fields() {
+        let sources = [
+            source(
+                "\
+defaultBrowser: chrome
+clients:
+- name: acme
+  path: /shared/acme
+  description: Acme
+  commands:
+  - key: ci
+    url: https://ci
+- name: team
+",
+            ),
+            source(
+                "\
+defaultBrowser: firefox
+clients:
+- name: acme
+  path: /local/acme
+  commands:
+  - key: docs
+    url: https://docs
+- name: zeta
+",
+            ),
+        ];
+        let provenance = provenance_of(&sources);
+        let local = vec!["local".to_string()];
+        let include = vec!["include".to_string()];
+        let both = vec!["include".to_string(), "local".to_string()];
+
+        let p = find(&provenance, "defaultBrowser");
+        assert_eq!((&p.from, p.rule), (&both, Some(Rule::Replace)));
+        let p = find(&provenance, "clients[acme]");
+        assert_eq!((&p.from, p.rule), (&both, Some(Rule::Merge)));
+        let p = find(&provenance, "clients[acme].path");
+        assert_eq!((&p.from, p.rule), (&both, Some(Rule::Replace)));
+        let p = find(&provenance, "clients[acme].description");
+        assert_eq!((&p.from, p.rule), (&include, None));
+        let p = find(&provenance, "clients[acme].commands[ci]");
+        assert_eq!((&p.from, p.rule), (&include, None));
+        let p = find(&provenance, "clients[acme].commands[docs]");
+        assert_eq!((&p.from, p.rule), (&local, Some(Rule::Append)));
+        let p = find(&provenance, "clients[team]");
+        assert_eq!((&p.from, p.rule), (&include, None));
+        let p = find(&provenance, "clients[zeta]");
+        assert_eq!((&p.from, p.rule), (&local, Some(Rule::Append)));
+    }
+
+    #[test]
+    fn shortcuts_section_is_replaced_wholesale() {
+        let sources = [
+            source("shortcuts:\n  enabled: false\n"),
+            source("shortcuts:\n  exclude: [foo]\n"),
+        ];
+        let provenance = provenance_of(&sources);
+        let p = find(&provenance, "shortcuts");
+        assert_eq!(p.from, vec!["include".to_string(), "local".to_string()]);
+        assert_eq!(p.rule, Some(Rule::Replace));
+    }
+
+    #[test]
+    fn render_aligns_notes() {
+        let sources = [
+            source("clients: []\n"),
+            source("currentClient: acme\nclients:\n- name: acme\n"),
+        ];
+        let merged =
+            crate::config::merge_configs(sources[0].config.clone(), sources[1].config.clone());
+        let labels = vec!["include".to_string(), "local".to_string()];
+        let text = effective(&merged, &sources, &labels).render(str::to_string);
+        assert_eq!(
+            text,
+            "currentClient: acme  # local\nclients:\n- name: acme         # local"
+        );
+    }
+}
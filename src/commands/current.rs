@@ -1,10 +1,20 @@
 use crate::config::ConfigManager;
+use crate::utils::output::{self, OutputFormat, SelectionOutput};
 use anyhow::Result;
 use colored::*;
 
-pub fn execute() -> Result<()> {
+pub fn execute(format: Option<OutputFormat>) -> Result<()> {
     let config_manager = ConfigManager::new()?;
 
+    if let Some(format) = format {
+        let selection = SelectionOutput::from_config(&config_manager);
+        output::print(format, &selection, SelectionOutput::label)?;
+        if selection.client.is_none() {
+            std::process::exit(output::EXIT_NO_SELECTION);
+        }
+        return Ok(());
+    }
+
     match config_manager.resolve_current() {
         Some((client_name, _, Some((project_name, _)))) => {
             println!(
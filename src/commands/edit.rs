@@ -0,0 +1,653 @@
+//! Config editing subcommands: `client`, `project` and `cmd`. `client
+//! import` generates its entry in [`import`].
+//!
+//! Edits are written to `~/.project-switch.yml`. Entries that only exist in
+//! an included file are refused unless `--shared` is given, in which case
+//! the change is written to the included file instead (`--shared=<file>`
+//! picks one when there are several).
+
+use super::import;
+use crate::config::{
+    Client, ConfigLayer, ConfigManager, EditTarget, Project, ProjectCommand, Step,
+};
+use anyhow::Result;
+use clap::{Args, Subcommand, ValueEnum};
+use colored::*;
+use std::path::PathBuf;
+
+/// Optional fields shared by clients and projects.
+#[derive(Args)]
+pub struct EntryFields {
+    /// Working directory for the entry
+    #[arg(long)]
+    path: Option<String>,
+    /// Free-form description
+    #[arg(long)]
+    description: Option<String>,
+    /// Browser for URL commands in this entry
+    #[arg(long)]
+    browser: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum ClientAction {
+    /// Add a client
+    Add {
+        name: String,
+        #[command(flatten)]
+        fields: EntryFields,
+    },
+    /// Remove a client and everything nested in it
+    Rm { name: String },
+    /// Rename a client (the current/previous selection follows)
+    Rename { name: String, new_name: String },
+    /// Add a client for a directory of git checkouts, with a project and
+    /// repo/ci/issues commands per checkout
+    Import {
+        dir: PathBuf,
+        /// Client name (defaults to the directory's name)
+        #[arg(long)]
+        name: Option<String>,
+        /// How many levels below the directory to look for checkouts
+        #[arg(long, default_value_t = import::DEFAULT_DEPTH)]
+        depth: usize,
+        /// Print the generated entry instead of writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ProjectAction {
+    /// Add a nested project to a client
+    Add {
+        client: String,
+        name: String,
+        #[command(flatten)]
+        fields: EntryFields,
+    },
+    /// Remove a nested project from a client
+    Rm { client: String, name: String },
+}
+
+/// Where a command lives. Defaults to the current selection (its project
+/// when one is selected, otherwise its client).
+#[derive(Args)]
+pub struct CommandScope {
+    /// Client owning the command (defaults to the current client)
+    #[arg(long, conflicts_with = "global")]
+    client: Option<String>,
+    /// Nested project owning the command (defaults to the current project
+    /// when --client is omitted)
+    #[arg(long, conflicts_with = "global")]
+    project: Option<String>,
+    /// Use the global command list
+    #[arg(long)]
+    global: bool,
+}
+
+/// Command fields settable from the CLI.
+#[derive(Args)]
+pub struct CommandFields {
+    /// URL to open (user args are appended)
+    #[arg(long)]
+    url: Option<String>,
+    /// Terminal command to run instead of a URL
+    #[arg(long)]
+    command: Option<String>,
+    /// Run other commands in order instead of a URL or command
+    /// (comma-separated keys)
+    #[arg(long, value_delimiter = ',', value_name = "KEYS")]
+    steps: Option<Vec<String>>,
+    /// Start the steps all at once instead of one after another
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    parallel: Option<bool>,
+    /// Browser for this command's URL
+    #[arg(long)]
+    browser: Option<String>,
+    /// Extra args (browser args for URLs, appended args for commands)
+    #[arg(long, allow_hyphen_values = true)]
+    args: Option<String>,
+    /// Directory the command runs in (relative to the project/client path)
+    #[arg(long)]
+    cwd: Option<String>,
+    /// Open the URL in the webview window
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    webview: Option<bool>,
+    /// Pin to the top of the recent list
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    pinned: Option<bool>,
+}
+
+/// Optional command fields that `cmd set --unset` can clear.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CommandField {
+    Url,
+    Command,
+    Browser,
+    Args,
+    Cwd,
+    Steps,
+}
+
+#[derive(Subcommand)]
+pub enum CmdAction {
+    /// Add a command
+    Add {
+        key: String,
+        #[command(flatten)]
+        fields: CommandFields,
+        #[command(flatten)]
+        scope: CommandScope,
+    },
+    /// Remove a command
+    Rm {
+        key: String,
+        #[command(flatten)]
+        scope: CommandScope,
+    },
+    /// Change fields of an existing command
+    Set {
+        key: String,
+        #[command(flatten)]
+        fields: CommandFields,
+        /// Clear a field (repeatable)
+        #[arg(long, value_enum)]
+        unset: Vec<CommandField>,
+        #[command(flatten)]
+        scope: CommandScope,
+    },
+}
+
+/// `--shared` as parsed: absent, bare, or `--shared=<file>`.
+pub type SharedArg = Option<Option<PathBuf>>;
+
+fn edit_target(shared: &SharedArg) -> EditTarget<'_> {
+    match shared {
+        None => EditTarget::Local,
+        Some(file) => EditTarget::Shared(file.as_deref()),
+    }
+}
+
+/// Error for an entry missing from the file being edited. When the merged
+/// config has it, it must come from the other file, so say where to look.
+fn not_in_target(what: &str, in_merged: bool, target: EditTarget) -> anyhow::Error {
+    match (in_merged, target) {
+        (false, _) => anyhow::anyhow!("{} not found", what),
+        (true, EditTarget::Local) => anyhow::anyhow!(
+            "{} is only defined in an included file — pass --shared to edit it there",
+            what
+        ),
+        (true, EditTarget::Shared(_)) => {
+            anyhow::anyhow!("{} is not defined in that included file", what)
+        }
+    }
+}
+
+/// Error for adding an entry the merged config already has. One defined in
+/// another file would be silently shadowed by (or shadow) the new entry, so
+/// say where it is instead.
+fn already_exists(what: &str, in_target: bool, target: EditTarget) -> anyhow::Error {
+    match (in_target, target) {
+        (true, _) => anyhow::anyhow!("{} already exists", what),
+        (false, EditTarget::Local) => anyhow::anyhow!(
+            "{} already exists in an included file — pass --shared to edit it there",
+            what
+        ),
+        (false, EditTarget::Shared(_)) => {
+            anyhow::anyhow!("{} already exists in another config file", what)
+        }
+    }
+}
+
+/// Suffix naming the edited file when it isn't the local config.
+fn target_note(config_manager: &ConfigManager, target: EditTarget) -> String {
+    match target {
+        EditTarget::Local => String::new(),
+        EditTarget::Shared(_) => config_manager
+            .target_path(target)
+            .map(|p| format!(" (in {})", p.display()))
+            .unwrap_or_default(),
+    }
+}
+
+pub fn execute_client(action: ClientAction, shared: SharedArg) -> Result<()> {
+    let mut config_manager = ConfigManager::new()?;
+    let target = edit_target(&shared);
+
+    let message = match action {
+        ClientAction::Add { name, fields } => {
+            let in_merged = config_manager.client_exists(&name);
+            config_manager.edit_layer(target, |layer| {
+                let in_target = layer.client_mut(&name).is_some();
+                if in_merged || in_target {
+                    return Err(already_exists(
+                        &format!("Client '{}'", name),
+                        in_target,
+                        target,
+                    ));
+                }
+                layer.clients.push(Client {
+                    name: name.clone(),
+                    path: fields.path,
+                    description: fields.description,
+                    browser: fields.browser,
+                    env: None,
+                    on_enter: None,
+                    on_leave: None,
+                    commands: None,
+                    projects: None,
+                });
+                Ok(())
+            })?;
+            format!("Added client: {}", name)
+        }
+        ClientAction::Rm { name } => {
+            let in_merged = config_manager.client_exists(&name);
+            config_manager.edit_layer(target, |layer| {
+                let before = layer.clients.len();
+                layer.clients.retain(|c| c.name != name);
+                if layer.clients.len() == before {
+                    return Err(not_in_target(
+                        &format!("Client '{}'", name),
+                        in_merged,
+                        target,
+                    ));
+                }
+                Ok(())
+            })?;
+            config_manager.deselect_removed(&name, None)?;
+            format!("Removed client: {}", name)
+        }
+        ClientAction::Rename { name, new_name } => {
+            let in_merged = config_manager.client_exists(&name);
+            let new_in_merged = config_manager.client_exists(&new_name);
+            config_manager.edit_layer(target, |layer| {
+                let new_in_target = layer.client_mut(&new_name).is_some();
+                if new_in_merged || new_in_target {
+                    return Err(already_exists(
+                        &format!("Client '{}'", new_name),
+                        new_in_target,
+                        target,
+                    ));
+                }
+                let client = layer.client_mut(&name).ok_or_else(|| {
+                    not_in_target(&format!("Client '{}'", name), in_merged, target)
+                })?;
+                client.name = new_name.clone();
+                Ok(())
+            })?;
+            config_manager.rename_selected_client(&name, &new_name)?;
+            crate::history::rename_client(&name, &new_name).ok();
+            format!("Renamed client: {} -> {}", name, new_name)
+        }
+        ClientAction::Import {
+            dir,
+            name,
+            depth,
+            dry_run,
+        } => {
+            let (dir, name) = import::resolve_dir(&dir, name)?;
+            // A client of that name anywhere, included files too, would be
+            // shadowed by or shadow the import.
+            if config_manager.client_exists(&name) {
+                anyhow::bail!(
+                    "Client '{}' already exists — use --name to import it under another name",
+                    name
+                );
+            }
+            let client = import::generate(&dir, &name, depth)?;
+            let projects = client.projects.as_ref().map_or(0, Vec::len);
+            if dry_run {
+                print!("{}", serde_yaml::to_string(&[&client])?);
+                format!("Would import client: {} ({} projects)", name, projects)
+            } else {
+                config_manager.edit_layer(target, |layer| {
+                    layer.clients.push(client);
+                    Ok(())
+                })?;
+                format!("Imported client: {} ({} projects)", name, projects)
+            }
+        }
+    };
+
+    println!(
+        "{}",
+        format!("{}{}", message, target_note(&config_manager, target)).green()
+    );
+    Ok(())
+}
+
+pub fn execute_project(action: ProjectAction, shared: SharedArg) -> Result<()> {
+    let mut config_manager = ConfigManager::new()?;
+    let target = edit_target(&shared);
+
+    let message = match action {
+        ProjectAction::Add {
+            client,
+            name,
+            fields,
+        } => {
+            let client_in_merged = config_manager.client_exists(&client);
+            let in_merged = config_manager.project_exists(&client, &name);
+            config_manager.edit_layer(target, |layer| {
+                let owner = layer.client_mut(&client).ok_or_else(|| {
+                    not_in_target(&format!("Client '{}'", client), client_in_merged, target)
+                })?;
+                let projects = owner.projects.get_or_insert_with(Vec::new);
+                let in_target = projects.iter().any(|p| p.name == name);
+                if in_merged || in_target {
+                    return Err(already_exists(
+                        &format!("Project '{}' in client '{}'", name, client),
+                        in_target,
+                        target,
+                    ));
+                }
+                projects.push(Project {
+                    name: name.clone(),
+                    path: fields.path,
+                    description: fields.description,
+                    browser: fields.browser,
+                    env: None,
+                    on_enter: None,
+                    on_leave: None,
+                    commands: None,
+                });
+                Ok(())
+            })?;
+            format!("Added project: {} / {}", client, name)
+        }
+        ProjectAction::Rm { client, name } => {
+            let client_in_merged = config_manager.client_exists(&client);
+            let in_merged = config_manager.project_exists(&client, &name);
+            config_manager.edit_layer(target, |layer| {
+                let owner = layer.client_mut(&client).ok_or_else(|| {
+                    not_in_target(&format!("Client '{}'", client), client_in_merged, target)
+                })?;
+                let projects = owner.projects.get_or_insert_with(Vec::new);
+                let before = projects.len();
+                projects.retain(|p| p.name != name);
+                if projects.len() == before {
+                    return Err(not_in_target(
+                        &format!("Project '{}' in client '{}'", name, client),
+                        in_merged,
+                        target,
+                    ));
+                }
+                if projects.is_empty() {
+                    owner.projects = None;
+                }
+                Ok(())
+            })?;
+            config_manager.deselect_removed(&client, Some(&name))?;
+            format!("Removed project: {} / {}", client, name)
+        }
+    };
+
+    println!(
+        "{}",
+        format!("{}{}", message, target_note(&config_manager, target)).green()
+    );
+    Ok(())
+}
+
+/// A command list's owner, resolved from [`CommandScope`].
+enum Scope {
+    Global,
+    Client(String),
+    Project(String, String),
+}
+
+impl Scope {
+    fn resolve(scope: CommandScope, config_manager: &ConfigManager) -> Result<Self> {
+        if scope.global {
+            return Ok(Scope::Global);
+        }
+        let (client, project) = match scope.client {
+            Some(client) => (client, scope.project),
+            None => {
+                let client = config_manager
+                    .get_current_client()
+                    .cloned()
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("No current client selected — pass --client or --global")
+                    })?;
+                let project = scope
+                    .project
+                    .or_else(|| config_manager.get_current_project().cloned());
+                (client, project)
+            }
+        };
+        Ok(match project {
+            Some(project) => Scope::Project(client, project),
+            None => Scope::Client(client),
+        })
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Scope::Global => "global commands".to_string(),
+            Scope::Client(c) => format!("client '{}'", c),
+            Scope::Project(c, p) => format!("project '{}' in client '{}'", p, c),
+        }
+    }
+
+    /// Whether the owner exists in the merged config.
+    fn exists_in(&self, config_manager: &ConfigManager) -> bool {
+        match self {
+            Scope::Global => true,
+            Scope::Client(c) => config_manager.client_exists(c),
+            Scope::Project(c, p) => config_manager.project_exists(c, p),
+        }
+    }
+
+    /// The merged command list of the owner.
+    fn merged_commands<'a>(
+        &self,
+        config_manager: &'a ConfigManager,
+    ) -> Option<&'a [ProjectCommand]> {
+        let commands = match self {
+            Scope::Global => config_manager.get_global_commands(),
+            Scope::Client(c) => config_manager.get_client(c)?.commands.as_ref(),
+            Scope::Project(c, p) => config_manager
+                .get_client(c)?
+                .projects
+                .as_ref()?
+                .iter()
+                .find(|proj| &proj.name == p)?
+                .commands
+                .as_ref(),
+        };
+        commands.map(Vec::as_slice)
+    }
+
+    /// The owner's command list within `layer`.
+    fn commands_mut<'a>(
+        &self,
+        layer: &'a mut ConfigLayer,
+        in_merged: bool,
+        target: EditTarget,
+    ) -> Result<&'a mut Option<Vec<ProjectCommand>>> {
+        let missing = || {
+            let what = match self {
+                Scope::Global => unreachable!("the global list always exists"),
+                Scope::Client(c) => format!("Client '{}'", c),
+                Scope::Project(c, p) => format!("Project '{}' in client '{}'", p, c),
+            };
+            not_in_target(&what, in_merged, target)
+        };
+        Ok(match self {
+            Scope::Global => &mut layer.global,
+            Scope::Client(c) => &mut layer.client_mut(c).ok_or_else(missing)?.commands,
+            Scope::Project(c, p) => {
+                &mut layer
+                    .client_mut(c)
+                    .and_then(|client| client.projects.as_mut())
+                    .and_then(|projects| projects.iter_mut().find(|proj| &proj.name == p))
+                    .ok_or_else(missing)?
+                    .commands
+            }
+        })
+    }
+}
+
+/// Apply the provided fields to `cmd`, then clear the `unset` ones.
+fn apply_fields(cmd: &mut ProjectCommand, fields: CommandFields, unset: &[CommandField]) {
+    if let Some(url) = fields.url {
+        cmd.url = Some(url);
+    }
+    if let Some(command) = fields.command {
+        cmd.command = Some(command);
+    }
+    if let Some(keys) = fields.steps {
+        cmd.steps = Some(keys.into_iter().map(Step::Key).collect());
+    }
+    if let Some(parallel) = fields.parallel {
+        cmd.parallel = parallel;
+    }
+    if let Some(browser) = fields.browser {
+        cmd.browser = Some(browser);
+    }
+    if let Some(args) = fields.args {
+        cmd.args = Some(args);
+    }
+    if let Some(cwd) = fields.cwd {
+        cmd.cwd = Some(cwd);
+    }
+    if let Some(webview) = fields.webview {
+        cmd.webview = webview;
+    }
+    if let Some(pinned) = fields.pinned {
+        cmd.pinned = pinned;
+    }
+    for field in unset {
+        match field {
+            CommandField::Url => cmd.url = None,
+            CommandField::Command => cmd.command = None,
+            CommandField::Browser => cmd.browser = None,
+            CommandField::Args => cmd.args = None,
+            CommandField::Cwd => cmd.cwd = None,
+            CommandField::Steps => {
+                cmd.steps = None;
+                cmd.parallel = false;
+            }
+        }
+    }
+}
+
+pub fn execute_cmd(action: CmdAction, shared: SharedArg) -> Result<()> {
+    let mut config_manager = ConfigManager::new()?;
+    let target = edit_target(&shared);
+
+    let message = match action {
+        CmdAction::Add { key, fields, scope } => {
+            if fields.url.is_none() && fields.command.is_none() && fields.steps.is_none() {
+                anyhow::bail!("Command '{}' needs --url, --command or --steps", key);
+            }
+            let scope = Scope::resolve(scope, &config_manager)?;
+            let in_merged = scope.exists_in(&config_manager);
+            config_manager.edit_layer(target, |layer| {
+                let commands = scope
+                    .commands_mut(layer, in_merged, target)?
+                    .get_or_insert_with(Vec::new);
+                if commands.iter().any(|c| c.key == key) {
+                    anyhow::bail!("Command '{}' already exists in {}", key, scope.describe());
+                }
+                let mut cmd = ProjectCommand {
+                    key: key.clone(),
+                    aliases: None,
+                    url: None,
+                    command: None,
+                    browser: None,
+                    args: None,
+                    cwd: None,
+                    steps: None,
+                    parallel: false,
+                    webview: false,
+                    pinned: false,
+                };
+                apply_fields(&mut cmd, fields, &[]);
+                commands.push(cmd);
+                Ok(())
+            })?;
+            format!("Added command: {} ({})", key, scope.describe())
+        }
+        CmdAction::Rm { key, scope } => {
+            let scope = Scope::resolve(scope, &config_manager)?;
+            let in_merged = scope.exists_in(&config_manager);
+            let key_in_merged = scope
+                .merged_commands(&config_manager)
+                .is_some_and(|cmds| cmds.iter().any(|c| c.key == key));
+            config_manager.edit_layer(target, |layer| {
+                let commands = scope.commands_mut(layer, in_merged, target)?;
+                let list = commands.get_or_insert_with(Vec::new);
+                let before = list.len();
+                list.retain(|c| c.key != key);
+                if list.len() == before {
+                    return Err(not_in_target(
+                        &format!("Command '{}' in {}", key, scope.describe()),
+                        key_in_merged,
+                        target,
+                    ));
+                }
+                if list.is_empty() {
+                    *commands = None;
+                }
+                Ok(())
+            })?;
+            format!("Removed command: {} ({})", key, scope.describe())
+        }
+        CmdAction::Set {
+            key,
+            fields,
+            unset,
+            scope,
+        } => {
+            let nothing_to_set = fields.url.is_none()
+                && fields.command.is_none()
+                && fields.steps.is_none()
+                && fields.parallel.is_none()
+                && fields.browser.is_none()
+                && fields.args.is_none()
+                && fields.cwd.is_none()
+                && fields.webview.is_none()
+                && fields.pinned.is_none();
+            if nothing_to_set && unset.is_empty() {
+                anyhow::bail!("Nothing to change for command '{}'", key);
+            }
+            let scope = Scope::resolve(scope, &config_manager)?;
+            let in_merged = scope.exists_in(&config_manager);
+            let key_in_merged = scope
+                .merged_commands(&config_manager)
+                .is_some_and(|cmds| cmds.iter().any(|c| c.key == key));
+            config_manager.edit_layer(target, |layer| {
+                let cmd = scope
+                    .commands_mut(layer, in_merged, target)?
+                    .as_mut()
+                    .and_then(|cmds| cmds.iter_mut().find(|c| c.key == key))
+                    .ok_or_else(|| {
+                        not_in_target(
+                            &format!("Command '{}' in {}", key, scope.describe()),
+                            key_in_merged,
+                            target,
+                        )
+                    })?;
+                apply_fields(cmd, fields, &unset);
+                if cmd.url.is_none() && cmd.command.is_none() && cmd.steps.is_none() {
+                    anyhow::bail!(
+                        "Command '{}' would have no 'url', 'command' or 'steps'",
+                        key
+                    );
+                }
+                Ok(())
+            })?;
+            format!("Updated command: {} ({})", key, scope.describe())
+        }
+    };
+
+    println!(
+        "{}",
+        format!("{}{}", message, target_note(&config_manager, target)).green()
+    );
+    Ok(())
+}
@@ -0,0 +1,147 @@
+//! `env`: the environment `command:` entries get for the current selection
+//! (global, then client, then project variables), or with `--export` as shell
+//! code that the `init` function evaluates after switching.
+
+use crate::commands::init::{self, Shell};
+use crate::config::{self, ConfigManager, EnvMap};
+use crate::utils::interpolate::{self, Vars};
+use crate::utils::output::{self, OutputFormat};
+use anyhow::Result;
+
+/// Names exported by the last `env --export`, so the next one can unset the
+/// variables the new selection doesn't define.
+const EXPORTED_VAR: &str = "PROJECT_SWITCH_ENV";
+
+/// Shell code setting `env` and unsetting the `previous` names it lacks.
+fn export_script(shell: Shell, env: &EnvMap, previous: &[&str]) -> String {
+    let set = |name: &str, value: &str| {
+        let value = init::quote(shell, value);
+        match shell {
+            Shell::Bash | Shell::Zsh => format!("export {}={}", name, value),
+            Shell::Fish => format!("set -gx {} {}", name, value),
+            Shell::Pwsh => format!("$env:{} = {}", name, value),
+        }
+    };
+    let unset = |name: &str| match shell {
+        Shell::Bash | Shell::Zsh => format!("unset {}", name),
+        Shell::Fish => format!("set -e {}", name),
+        Shell::Pwsh => format!(
+            "Remove-Item -Path Env:{} -ErrorAction SilentlyContinue",
+            name
+        ),
+    };
+
+    let mut lines: Vec<String> = previous
+        .iter()
+        .filter(|name| config::valid_env_name(name) && !env.contains_key(**name))
+        .map(|name| unset(name))
+        .collect();
+    lines.extend(env.iter().map(|(name, value)| set(name, value)));
+    if env.is_empty() {
+        lines.push(unset(EXPORTED_VAR));
+    } else {
+        let names: Vec<&str> = env.keys().map(String::as_str).collect();
+        lines.push(set(EXPORTED_VAR, &names.join(" ")));
+    }
+    lines.join("\n") + "\n"
+}
+
+pub fn execute(export: Option<Shell>, format: Option<OutputFormat>) -> Result<()> {
+    let config_manager = ConfigManager::new()?;
+    let env = interpolate::expand_env(
+        &config_manager.get_effective_env(),
+        &Vars::from_selection(&config_manager),
+    )?;
+    if let Some(name) = env.keys().find(|name| !config::valid_env_name(name)) {
+        anyhow::bail!("Invalid environment variable name '{}'", name);
+    }
+
+    let plain = |env: &EnvMap| {
+        env.iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    match (export, format) {
+        (Some(shell), _) => {
+            let previous = std::env::var(EXPORTED_VAR).unwrap_or_default();
+            let previous: Vec<&str> = previous.split_whitespace().collect();
+            print!("{}", export_script(shell, &env, &previous));
+            Ok(())
+        }
+        (None, Some(format)) => output::print(format, &env, plain),
+        (None, None) => {
+            if !env.is_empty() {
+                println!("{}", plain(&env));
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(pairs: &[(&str, &str)]) -> EnvMap {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn export_sets_new_and_unsets_stale_variables() {
+        let script = export_script(
+            Shell::Bash,
+            &env(&[("AWS_PROFILE", "acme"), ("KUBECONFIG", "/k/it's")]),
+            &["AWS_PROFILE", "REGISTRY"],
+        );
+        assert_eq!(
+            script,
+            "unset REGISTRY\nexport AWS_PROFILE='acme'\nexport KUBECONFIG='/k/it'\\''s'\nexport PROJECT_SWITCH_ENV='AWS_PROFILE KUBECONFIG'\n"
+        );
+    }
+
+    #[test]
+    fn export_syntax_per_shell() {
+        let vars = env(&[("AWS_PROFILE", "acme")]);
+        assert!(export_script(Shell::Fish, &vars, &["OLD"])
+            .starts_with("set -e OLD\nset -gx AWS_PROFILE 'acme'\n"));
+        assert!(export_script(Shell::Pwsh, &vars, &[]).starts_with("$env:AWS_PROFILE = 'acme'\n"));
+        assert_eq!(
+            export_script(Shell::Zsh, &EnvMap::new(), &["A", "bad;name"]),
+            "unset A\nunset PROJECT_SWITCH_ENV\n"
+        );
+    }
+
+    #[test]
+    fn effective_env_layers_project_over_client_over_global() {
+        let cm = ConfigManager::from_test_yaml(
+            "\
+currentClient: acme
+currentProject: api
+env:
+  REGISTRY: registry.example.com
+  AWS_PROFILE: default
+clients:
+- name: acme
+  env:
+    AWS_PROFILE: acme
+    KUBECONFIG: ~/.kube/acme
+  projects:
+  - name: api
+    env:
+      KUBECONFIG: ~/.kube/acme-api
+",
+        );
+        assert_eq!(
+            cm.get_effective_env(),
+            env(&[
+                ("AWS_PROFILE", "acme"),
+                ("KUBECONFIG", "~/.kube/acme-api"),
+                ("REGISTRY", "registry.example.com"),
+            ])
+        );
+    }
+}
@@ -0,0 +1,291 @@
+//! `history` subcommands: list, prune and clear launcher history per scope.
+
+use crate::config::ConfigManager;
+use crate::history::{self, Entry, Scope};
+use crate::utils::output::{self, OutputFormat};
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use colored::*;
+use serde::Serialize;
+
+/// Which entries a `history` subcommand works on. Defaults to the current
+/// selection (its project when one is selected, otherwise the whole client).
+#[derive(Args)]
+pub struct ScopeArgs {
+    /// Entries recorded in this client (its projects included unless
+    /// --project is given)
+    #[arg(long, conflicts_with_all = ["global", "all"])]
+    client: Option<String>,
+    /// Entries recorded in this nested project (of --client, or the current
+    /// client when --client is omitted)
+    #[arg(long, conflicts_with_all = ["global", "all"])]
+    project: Option<String>,
+    /// Entries recorded outside any client: expressions and file paths
+    #[arg(long, conflicts_with = "all")]
+    global: bool,
+    /// Entries of every scope
+    #[arg(long)]
+    all: bool,
+}
+
+#[derive(Subcommand)]
+pub enum HistoryAction {
+    /// List entries, best frecency first
+    List {
+        #[command(flatten)]
+        scope: ScopeArgs,
+    },
+    /// Remove entries whose client or project no longer exists, and file
+    /// paths that are gone
+    Prune {
+        #[command(flatten)]
+        scope: ScopeArgs,
+    },
+    /// Remove entries
+    Clear {
+        #[command(flatten)]
+        scope: ScopeArgs,
+    },
+}
+
+/// A resolved [`ScopeArgs`].
+enum Filter {
+    All,
+    /// Exactly this scope, or with `project: None` the client and all its
+    /// projects.
+    Scope(Scope),
+}
+
+impl Filter {
+    fn new(args: &ScopeArgs, config_manager: &ConfigManager) -> Result<Self> {
+        if args.all {
+            return Ok(Filter::All);
+        }
+        if args.global {
+            return Ok(Filter::Scope(Scope::default()));
+        }
+        let current = Scope::current(config_manager);
+        let scope =
+            match (&args.client, &args.project) {
+                (Some(client), project) => Scope {
+                    client: Some(client.clone()),
+                    project: project.clone(),
+                },
+                (None, Some(project)) => Scope {
+                    client: Some(current.client.ok_or_else(|| {
+                        anyhow::anyhow!("No current client selected; pass --client")
+                    })?),
+                    project: Some(project.clone()),
+                },
+                (None, None) => current,
+            };
+        Ok(Filter::Scope(scope))
+    }
+
+    fn matches(&self, entry: &Entry) -> bool {
+        match self {
+            Filter::All => true,
+            Filter::Scope(scope) if scope.client.is_some() && scope.project.is_none() => {
+                entry.client == scope.client
+            }
+            Filter::Scope(scope) => entry.in_scope(scope),
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            Filter::All => "all scopes".to_string(),
+            Filter::Scope(scope) => scope_label(&scope.client, &scope.project),
+        }
+    }
+}
+
+fn scope_label(client: &Option<String>, project: &Option<String>) -> String {
+    match (client, project) {
+        (Some(c), Some(p)) => format!("{} / {}", c, p),
+        (Some(c), None) => c.clone(),
+        (None, _) => "global".to_string(),
+    }
+}
+
+/// How long ago `timestamp` was, in the largest whole unit.
+fn ago(timestamp: u64, now: u64) -> String {
+    if timestamp == 0 {
+        return "unknown".to_string();
+    }
+    let secs = now.saturating_sub(timestamp);
+    match secs {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{}m ago", secs / 60),
+        3600..=86_399 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86_400),
+    }
+}
+
+#[derive(Serialize)]
+struct EntryOutput {
+    key: String,
+    count: u32,
+    /// Unix timestamp in seconds; 0 for entries migrated from the old format.
+    #[serde(rename = "lastUsed")]
+    last_used: u64,
+    client: Option<String>,
+    project: Option<String>,
+    frecency: f64,
+}
+
+/// Entries one per line: key, count, last used and scope, tab-separated.
+fn plain(entries: &[EntryOutput]) -> String {
+    entries
+        .iter()
+        .map(|e| {
+            format!(
+                "{}\t{}\t{}\t{}",
+                e.key,
+                e.count,
+                e.last_used,
+                scope_label(&e.client, &e.project)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Whether the entry's scope still exists in the config and, for file
+/// paths, the path itself.
+fn is_live(entry: &Entry, config_manager: &ConfigManager) -> bool {
+    let scope_exists = match (&entry.client, &entry.project) {
+        (Some(c), Some(p)) => config_manager.project_exists(c, p),
+        (Some(c), None) => config_manager.client_exists(c),
+        (None, _) => true,
+    };
+    let path_exists =
+        !crate::launcher::is_file_path(&entry.key) || std::path::Path::new(&entry.key).exists();
+    scope_exists && path_exists
+}
+
+pub fn execute(action: HistoryAction, format: Option<OutputFormat>) -> Result<()> {
+    let config_manager = ConfigManager::new()?;
+    match action {
+        HistoryAction::List { scope } => {
+            let filter = Filter::new(&scope, &config_manager)?;
+            list(&filter, format)
+        }
+        HistoryAction::Prune { scope } => {
+            let filter = Filter::new(&scope, &config_manager)?;
+            let removed = history::retain(|e| !filter.matches(e) || is_live(e, &config_manager))?;
+            println!(
+                "{}",
+                format!("Pruned {} stale entries ({})", removed, filter.label()).green()
+            );
+            Ok(())
+        }
+        HistoryAction::Clear { scope } => {
+            let filter = Filter::new(&scope, &config_manager)?;
+            let removed = history::retain(|e| !filter.matches(e))?;
+            println!(
+                "{}",
+                format!("Cleared {} entries ({})", removed, filter.label()).green()
+            );
+            Ok(())
+        }
+    }
+}
+
+fn list(filter: &Filter, format: Option<OutputFormat>) -> Result<()> {
+    let now = history::now();
+    let entries: Vec<EntryOutput> = history::entries()
+        .into_iter()
+        .filter(|e| filter.matches(e))
+        .map(|e| EntryOutput {
+            frecency: e.frecency(now),
+            key: e.key,
+            count: e.count,
+            last_used: e.last_used,
+            client: e.client,
+            project: e.project,
+        })
+        .collect();
+
+    if let Some(format) = format {
+        return output::print(format, &entries, |e| plain(e));
+    }
+    if entries.is_empty() {
+        println!("{}", format!("No history ({})", filter.label()).yellow());
+        return Ok(());
+    }
+    let width = entries.iter().map(|e| e.key.len()).max().unwrap_or(0);
+    for e in &entries {
+        println!(
+            "{:<width$}  {:>4}×  {:<10}  {}",
+            e.key.green().bold(),
+            e.count,
+            ago(e.last_used, now),
+            scope_label(&e.client, &e.project).dimmed(),
+            width = width,
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(key: &str, client: Option<&str>, project: Option<&str>) -> Entry {
+        Entry {
+            key: key.to_string(),
+            count: 1,
+            last_used: 1,
+            client: client.map(str::to_string),
+            project: project.map(str::to_string),
+        }
+    }
+
+    fn filter(client: Option<&str>, project: Option<&str>) -> Filter {
+        Filter::Scope(Scope {
+            client: client.map(str::to_string),
+            project: project.map(str::to_string),
+        })
+    }
+
+    #[test]
+    fn client_filter_includes_its_projects() {
+        let f = filter(Some("acme"), None);
+        assert!(f.matches(&entry("a", Some("acme"), None)));
+        assert!(f.matches(&entry("a", Some("acme"), Some("web"))));
+        assert!(!f.matches(&entry("a", Some("globex"), None)));
+        assert!(!f.matches(&entry("=1+1", None, None)));
+    }
+
+    #[test]
+    fn project_and_global_filters_are_exact() {
+        let f = filter(Some("acme"), Some("web"));
+        assert!(f.matches(&entry("a", Some("acme"), Some("web"))));
+        assert!(!f.matches(&entry("a", Some("acme"), None)));
+        let f = filter(None, None);
+        assert!(f.matches(&entry("=1+1", None, None)));
+        assert!(!f.matches(&entry("a", Some("acme"), None)));
+        assert!(Filter::All.matches(&entry("a", Some("acme"), None)));
+    }
+
+    #[test]
+    fn prune_drops_removed_scopes() {
+        let cm = ConfigManager::from_test_yaml(
+            "clients:\n  - name: acme\n    projects:\n      - name: web\n",
+        );
+        assert!(is_live(&entry("a", Some("acme"), Some("web")), &cm));
+        assert!(!is_live(&entry("a", Some("acme"), Some("gone")), &cm));
+        assert!(!is_live(&entry("a", Some("gone"), None), &cm));
+        assert!(is_live(&entry("=1+1", None, None), &cm));
+    }
+
+    #[test]
+    fn ago_uses_largest_unit() {
+        assert_eq!(ago(100, 130), "just now");
+        assert_eq!(ago(100, 100 + 5 * 60), "5m ago");
+        assert_eq!(ago(100, 100 + 3 * 3600), "3h ago");
+        assert_eq!(ago(100, 100 + 2 * 86_400), "2d ago");
+        assert_eq!(ago(0, 100), "unknown");
+    }
+}
@@ -0,0 +1,176 @@
+//! `client import <dir>`: a client generated from the git checkouts in a
+//! directory. Each checkout becomes a project with its `path` and, from its
+//! `origin` remote, a `repo` command opening the repository's page plus `ci`
+//! and `issues` on hosts whose URL layout is known (see [`git::links`]).
+//! When `<dir>` is itself a checkout, the client gets those commands and no
+//! projects.
+
+use crate::config::{self, Client, Project, ProjectCommand};
+use crate::utils::git;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_DEPTH: usize = 3;
+
+fn url_command(key: &str, url: String) -> ProjectCommand {
+    ProjectCommand {
+        key: key.to_string(),
+        aliases: None,
+        url: Some(url),
+        command: None,
+        browser: None,
+        args: None,
+        cwd: None,
+        steps: None,
+        parallel: false,
+        webview: false,
+        pinned: false,
+    }
+}
+
+/// The commands for the checkout at `repo`; none without an `origin` remote
+/// on a web host.
+fn remote_commands(repo: &Path) -> Option<Vec<ProjectCommand>> {
+    let links = git::links(&git::origin_url(repo)?)?;
+    let mut commands = vec![url_command("repo", links.web)];
+    commands.extend(links.ci.map(|url| url_command("ci", url)));
+    commands.extend(links.issues.map(|url| url_command("issues", url)));
+    Some(commands)
+}
+
+/// The project for the checkout at `repo`, named by its path below `dir`.
+fn repo_project(dir: &Path, repo: &Path) -> Project {
+    let name = repo
+        .strip_prefix(dir)
+        .unwrap_or(repo)
+        .to_string_lossy()
+        .replace('\\', "/");
+    Project {
+        name,
+        path: Some(config::contract_tilde(repo)),
+        description: None,
+        browser: None,
+        env: None,
+        on_enter: None,
+        on_leave: None,
+        commands: remote_commands(repo),
+    }
+}
+
+/// The client `name` for the checkouts at most `depth` levels below the
+/// absolute directory `dir`.
+pub fn generate(dir: &Path, name: &str, depth: usize) -> Result<Client> {
+    let repos = git::find_repos(dir, depth);
+    if repos.is_empty() {
+        anyhow::bail!(
+            "No git repositories found in {} (searched {} levels down; see --depth)",
+            dir.display(),
+            depth
+        );
+    }
+    let mut client = Client {
+        name: name.to_string(),
+        path: Some(config::contract_tilde(dir)),
+        description: None,
+        browser: None,
+        env: None,
+        on_enter: None,
+        on_leave: None,
+        commands: None,
+        projects: None,
+    };
+    if repos == [dir] {
+        client.commands = remote_commands(dir);
+    } else {
+        client.projects = Some(repos.iter().map(|repo| repo_project(dir, repo)).collect());
+    }
+    Ok(client)
+}
+
+/// `dir` as an absolute path, and the client name: `name`, or the
+/// directory's own name.
+pub fn resolve_dir(dir: &Path, name: Option<String>) -> Result<(PathBuf, String)> {
+    if !dir.is_dir() {
+        anyhow::bail!("Not a directory: {}", dir.display());
+    }
+    let dir =
+        std::path::absolute(dir).with_context(|| format!("Failed to resolve {}", dir.display()))?;
+    let name = match name {
+        Some(name) => name,
+        None => dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .ok_or_else(|| {
+                anyhow::anyhow!("Can't name a client after {}; pass --name", dir.display())
+            })?,
+    };
+    Ok((dir, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn checkout(root: &Path, rel: &str, remote: Option<&str>) {
+        let git_dir = root.join(rel).join(".git");
+        fs::create_dir_all(&git_dir).unwrap();
+        if let Some(url) = remote {
+            fs::write(
+                git_dir.join("config"),
+                format!("[remote \"origin\"]\n\turl = {}\n", url),
+            )
+            .unwrap();
+        }
+    }
+
+    fn keys(commands: &Option<Vec<ProjectCommand>>) -> Vec<&str> {
+        commands.iter().flatten().map(|c| c.key.as_str()).collect()
+    }
+
+    #[test]
+    fn generate_makes_a_project_per_checkout_with_host_commands() {
+        let root = std::env::temp_dir().join(format!("ps-import-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        checkout(&root, "web", Some("git@github.com:acme/web.git"));
+        checkout(
+            &root,
+            "infra/api",
+            Some("git@ssh.dev.azure.com:v3/acme/Platform/api"),
+        );
+        checkout(&root, "wiki", Some("git@bitbucket.org:acme/wiki.git"));
+        checkout(&root, "scratch", None);
+
+        let client = generate(&root, "acme", DEFAULT_DEPTH).unwrap();
+        assert_eq!(client.name, "acme");
+        assert_eq!(client.path, Some(config::contract_tilde(&root)));
+        assert!(client.commands.is_none());
+        let projects = client.projects.unwrap();
+        let names: Vec<&str> = projects.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["infra/api", "scratch", "web", "wiki"]);
+        assert_eq!(
+            projects[0].path,
+            Some(config::contract_tilde(&root.join("infra/api")))
+        );
+        assert_eq!(keys(&projects[0].commands), ["repo", "ci", "issues"]);
+        assert_eq!(
+            projects[0].commands.as_ref().unwrap()[1].url.as_deref(),
+            Some("https://dev.azure.com/acme/Platform/_build")
+        );
+        assert!(projects[1].commands.is_none());
+        assert_eq!(
+            projects[2].commands.as_ref().unwrap()[2].url.as_deref(),
+            Some("https://github.com/acme/web/issues")
+        );
+        assert_eq!(keys(&projects[3].commands), ["repo"]);
+
+        // A checkout imported on its own carries the commands itself.
+        let web = generate(&root.join("web"), "web", DEFAULT_DEPTH).unwrap();
+        assert!(web.projects.is_none());
+        assert_eq!(keys(&web.commands), ["repo", "ci", "issues"]);
+
+        assert!(generate(&root.join("web"), "web", 0).is_ok());
+        assert!(generate(&root.join("infra"), "infra", 0).is_err());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
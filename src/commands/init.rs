@@ -0,0 +1,139 @@
+//! `init <shell>`: print a shell function wrapping the binary, so switching
+//! client also changes the shell's directory (a child process can't) and
+//! `<fn> cd [client] [project]` jumps to a client/project `path`. The
+//! selection's `env:` variables are loaded at startup and after every switch.
+//!
+//! Meant to be evaluated from the shell's startup file, e.g.
+//! `eval "$(project-switch init bash)"`.
+
+use anyhow::{Context, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Pwsh,
+}
+
+/// Quote `text` as a single literal word for `shell`.
+pub fn quote(shell: Shell, text: &str) -> String {
+    match shell {
+        Shell::Bash | Shell::Zsh => format!("'{}'", text.replace('\'', r"'\''")),
+        Shell::Fish => format!("'{}'", text.replace('\\', r"\\").replace('\'', r"\'")),
+        Shell::Pwsh => format!("'{}'", text.replace('\'', "''")),
+    }
+}
+
+/// The wrapper function `name` for `shell`, running the binary at `exe`.
+fn script(shell: Shell, name: &str, exe: &str) -> String {
+    let exe = quote(shell, exe);
+    let shell_name = if shell == Shell::Zsh { "zsh" } else { "bash" };
+    match shell {
+        Shell::Bash | Shell::Zsh => format!(
+            r#"{name}() {{
+    local dir
+    case "$1" in
+        cd)
+            dir="$({exe} path "${{@:2}}")" && cd -- "$dir"
+            ;;
+        switch)
+            {exe} "$@" || return
+            eval "$({exe} env --export {shell_name})"
+            dir="$({exe} path 2>/dev/null)" && cd -- "$dir"
+            ;;
+        *)
+            {exe} "$@"
+            ;;
+    esac
+}}
+eval "$({exe} env --export {shell_name} 2>/dev/null)"
+"#
+        ),
+        Shell::Fish => format!(
+            r#"function {name}
+    switch "$argv[1]"
+        case cd
+            set -l dir ({exe} path $argv[2..-1]); and cd -- $dir
+        case switch
+            {exe} $argv; or return
+            {exe} env --export fish | source
+            set -l dir ({exe} path 2>/dev/null); and cd -- $dir
+        case '*'
+            {exe} $argv
+    end
+end
+{exe} env --export fish 2>/dev/null | source
+"#
+        ),
+        // `ps` is a built-in alias for Get-Process, and aliases win over functions.
+        Shell::Pwsh => format!(
+            r#"Remove-Item -Path Alias:{name} -Force -ErrorAction SilentlyContinue
+function {name} {{
+    $exe = {exe}
+    $rest = @($args | Select-Object -Skip 1)
+    switch ($args[0]) {{
+        'cd' {{
+            $dir = & $exe path @rest
+            if ($LASTEXITCODE -eq 0) {{ Set-Location -LiteralPath $dir }}
+        }}
+        'switch' {{
+            & $exe @args
+            if ($LASTEXITCODE -eq 0) {{
+                & $exe env --export pwsh | Out-String | Invoke-Expression
+                $dir = & $exe path 2>$null
+                if ($LASTEXITCODE -eq 0) {{ Set-Location -LiteralPath $dir }}
+            }}
+        }}
+        default {{ & $exe @args }}
+    }}
+}}
+& {exe} env --export pwsh 2>$null | Out-String | Invoke-Expression
+"#
+        ),
+    }
+}
+
+pub fn execute(shell: Shell, name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        anyhow::bail!(
+            "Invalid function name '{}': use letters, digits, '-' and '_'",
+            name
+        );
+    }
+    let exe = std::env::current_exe().context("Failed to locate the project-switch binary")?;
+    print!("{}", script(shell, name, &exe.display().to_string()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_the_binary_path_per_shell() {
+        assert_eq!(quote(Shell::Bash, "/opt/it's/ps"), r"'/opt/it'\''s/ps'");
+        assert_eq!(quote(Shell::Fish, "/opt/it's/ps"), r"'/opt/it\'s/ps'");
+        assert_eq!(
+            quote(Shell::Pwsh, r"C:\Program Files\it's.exe"),
+            r"'C:\Program Files\it''s.exe'"
+        );
+    }
+
+    #[test]
+    fn scripts_define_the_named_function_around_the_binary() {
+        let bash = script(Shell::Bash, "ps", "/bin/project-switch");
+        assert!(bash.starts_with("ps() {"), "got: {}", bash);
+        assert!(
+            bash.contains(r#"dir="$('/bin/project-switch' path "${@:2}")" && cd -- "$dir""#),
+            "got: {}",
+            bash
+        );
+        assert!(script(Shell::Fish, "p", "x").starts_with("function p\n"));
+        assert!(script(Shell::Pwsh, "ps", "x").contains("function ps {"));
+    }
+}
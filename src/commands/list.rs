@@ -1,14 +1,18 @@
-use crate::config::ConfigManager;
+use crate::config::{self, ConfigManager, EnvMap, InlineStep, ProjectCommand, Step};
+use crate::launcher::fuzzy::fuzzy_match;
 use crate::launcher::{
-    encode_url_args, eval_calc_input, filter_items, get_path_entries, is_file_path, merge_args,
-    order_recent_keys, resolve_item, strip_ansi_codes, CalcResult, ListItem, ListItemKind,
+    encode_url_args, eval_calc_input, fill_template, filter_items, find_named, get_path_entries,
+    is_file_path, merge_args, order_recent_keys, resolve_item, strip_ansi_codes, truncate,
+    CalcResult, ListItem, ListItemKind, Usage,
 };
 use crate::utils::browser;
-use crate::utils::shortcuts;
+use crate::utils::interpolate::{self, Vars};
+use crate::utils::output::{self, CommandOutput, ListOutput, OutputFormat, SelectionOutput};
 use crate::utils::url::is_url;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use colored::*;
 use inquire::Autocomplete;
+use serde::Serialize;
 
 const PATH_PREFIX: &str = "[path] ";
 
@@ -28,18 +32,53 @@ fn get_file_suggestions(input: &str) -> Vec<String> {
 
 const APP_PREFIX: &str = "[app] ";
 
-fn format_suggestion(item: &ListItem) -> String {
+/// `text` without a leading `[tag] ` (an app or provider item's prefix).
+fn strip_tag(text: &str) -> &str {
+    text.strip_prefix('[')
+        .and_then(|rest| rest.split_once("] "))
+        .map_or(text, |(_, rest)| rest)
+}
+
+/// Style `key` with `style`, underlining the characters `query` matched.
+fn highlight_key(key: &str, query: &str, style: fn(&str) -> ColoredString) -> String {
+    let ranges = fuzzy_match(query, key)
+        .map(|m| m.ranges)
+        .unwrap_or_default();
+    let mut out = String::new();
+    let mut pos = 0;
+    for range in ranges {
+        out.push_str(&style(&key[pos..range.start]).to_string());
+        out.push_str(&style(&key[range.clone()]).underline().to_string());
+        pos = range.end;
+    }
+    out.push_str(&style(&key[pos..]).to_string());
+    out
+}
+
+fn format_suggestion(item: &ListItem, query: &str) -> String {
     match &item.kind {
         ListItemKind::Command => {
-            let truncated = if item.display_detail.len() > 60 {
-                format!("{}...", &item.display_detail[..57])
-            } else {
-                item.display_detail.clone()
-            };
-            format!("{} → {}", item.key.green().bold(), truncated.bright_blue())
+            let truncated = truncate(&item.display_detail, 60);
+            let key = highlight_key(&item.key, query, |s| s.green().bold());
+            format!("{} → {}", key, truncated.bright_blue())
         }
         ListItemKind::Shortcut { .. } => {
-            format!("{}{}", APP_PREFIX.cyan(), item.key.yellow())
+            let key = highlight_key(&item.key, query, |s| s.yellow());
+            format!("{}{}", APP_PREFIX.cyan(), key)
+        }
+        ListItemKind::Provided { provider, .. } => {
+            let key = highlight_key(&item.key, query, |s| s.magenta());
+            let tag = format!("[{}] ", provider);
+            if item.display_detail.is_empty() {
+                format!("{}{}", tag.cyan(), key)
+            } else {
+                format!(
+                    "{}{} → {}",
+                    tag.cyan(),
+                    key,
+                    item.display_detail.bright_blue()
+                )
+            }
         }
     }
 }
@@ -48,6 +87,7 @@ fn format_suggestion(item: &ListItem) -> String {
 struct ListAutocomplete {
     items: Vec<ListItem>,
     recent_keys: Vec<String>,
+    usage: Usage,
 }
 
 impl ListAutocomplete {
@@ -59,7 +99,7 @@ impl ListAutocomplete {
                 .filter_map(|key| {
                     // Known list item
                     if let Some(item) = self.items.iter().find(|item| item.key == *key) {
-                        return Some(format_suggestion(item));
+                        return Some(format_suggestion(item, ""));
                     }
                     // Calculator expression
                     if let Some(expr) = key.strip_prefix('=') {
@@ -79,9 +119,9 @@ impl ListAutocomplete {
                 return recent;
             }
         }
-        filter_items(&self.items, keyword)
+        filter_items(&self.items, keyword, &self.usage)
             .into_iter()
-            .map(format_suggestion)
+            .map(|item| format_suggestion(item, keyword))
             .collect()
     }
 }
@@ -116,13 +156,10 @@ impl Autocomplete for ListAutocomplete {
 
         let suggestions: Vec<String> = if has_space {
             // Check for exact match on the keyword part
-            let exact_match = self
-                .items
-                .iter()
-                .find(|item| item.key.to_lowercase() == keyword.to_lowercase());
+            let exact_match = find_named(&self.items, keyword);
 
             if let Some(matched) = exact_match {
-                vec![format_suggestion(matched)]
+                vec![format_suggestion(matched, "")]
             } else {
                 self.matching_suggestions(keyword)
             }
@@ -152,14 +189,13 @@ impl Autocomplete for ListAutocomplete {
                     return Ok(Some(full_path.to_string()));
                 }
             }
-            // Command format: "key → url"
-            if let Some(arrow_pos) = clean.find(" → ") {
-                return Ok(Some(clean[..arrow_pos].trim().to_string()));
-            }
-            // Shortcut format: "[app] Name"
-            if let Some(rest) = clean.strip_prefix(APP_PREFIX) {
-                return Ok(Some(rest.to_string()));
-            }
+            // Command format: "key → url", shortcut "[app] Name", provider
+            // item "[provider] key → detail"
+            let clean = strip_tag(&clean);
+            let key = clean
+                .find(" → ")
+                .map_or(clean, |arrow_pos| &clean[..arrow_pos]);
+            return Ok(Some(key.trim().to_string()));
         }
         Ok(Some(input.to_string()))
     }
@@ -177,7 +213,7 @@ pub fn selection_display_name(config_manager: &ConfigManager) -> String {
 /// Load only command items from config (fast — no filesystem scanning).
 /// Effective command set precedence when a project is active:
 /// project > client > global.
-fn load_command_items(
+pub fn load_command_items(
     config_manager: &ConfigManager,
 ) -> (Vec<crate::config::ProjectCommand>, Vec<ListItem>) {
     let resolved = config_manager.resolve_current();
@@ -203,11 +239,8 @@ fn load_command_items(
         .iter()
         .map(|cmd| ListItem {
             key: cmd.key.clone(),
-            display_detail: cmd
-                .url
-                .clone()
-                .or_else(|| cmd.command.clone())
-                .unwrap_or_default(),
+            aliases: cmd.aliases.clone().unwrap_or_default(),
+            display_detail: cmd.detail(),
             kind: ListItemKind::Command,
             pinned: cmd.pinned,
         })
@@ -216,199 +249,525 @@ fn load_command_items(
     (all_commands, all_items)
 }
 
-/// Collect shortcut items from the filesystem.
-fn collect_shortcut_items(extra_paths: &[String], exclude: &[String]) -> Vec<ListItem> {
-    shortcuts::collect_shortcuts(extra_paths, exclude)
-        .into_iter()
-        .map(|entry| ListItem {
-            key: entry.name,
-            display_detail: entry.path.display().to_string(),
-            kind: ListItemKind::Shortcut {
-                path: entry.path.display().to_string(),
-            },
-            pinned: false,
-        })
-        .collect()
-}
-
+/// Command items followed by every provider's items for `query` (blocking
+/// until all providers are done).
 pub fn load_items(
     config_manager: &ConfigManager,
+    query: &str,
 ) -> (Vec<crate::config::ProjectCommand>, Vec<ListItem>) {
     let (all_commands, mut all_items) = load_command_items(config_manager);
+    all_items.extend(crate::providers::collect(
+        &crate::providers::registered(config_manager),
+        query,
+    ));
+    (all_commands, all_items)
+}
+
+/// A launcher action resolved from input text, before anything is launched.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum Action {
+    /// Evaluate a calculator expression (`=expr`).
+    Calculate { expression: String, result: String },
+    /// Open a file, directory or app shortcut with the OS handler.
+    Launch { path: String },
+    /// Show a URL in the reusable webview window.
+    Webview { url: String, title: String },
+    /// Run a terminal command, in `cwd` when set, with `env` added to the
+    /// inherited environment.
+    Run {
+        command: String,
+        args: Option<String>,
+        cwd: Option<String>,
+        #[serde(skip_serializing_if = "EnvMap::is_empty")]
+        env: EnvMap,
+    },
+    /// Open a URL in a browser (the browser string may carry extra args).
+    Open { url: String, browser: String },
+    /// A `steps:` command: its actions one after another, or all at once
+    /// when `parallel`.
+    Steps {
+        key: String,
+        parallel: bool,
+        steps: Vec<Action>,
+    },
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Calculate { expression, result } => {
+                write!(f, "calculate {} = {}", expression, result)
+            }
+            Action::Launch { path } => write!(f, "launch {}", path),
+            Action::Webview { url, .. } => write!(f, "webview {}", url),
+            Action::Run {
+                command, args, cwd, ..
+            } => {
+                write!(f, "run {}", command)?;
+                if let Some(a) = args {
+                    write!(f, " {}", a)?;
+                }
+                match cwd {
+                    Some(dir) => write!(f, " (in {})", dir),
+                    None => Ok(()),
+                }
+            }
+            Action::Open { url, browser } => write!(f, "open {} in {}", url, browser),
+            Action::Steps {
+                key,
+                parallel,
+                steps,
+            } => {
+                let mode = if *parallel { "parallel" } else { "in order" };
+                write!(f, "steps of {} ({}):", key, mode)?;
+                for (i, step) in steps.iter().enumerate() {
+                    // Nested step lists indent one level further.
+                    let step = step.to_string().replace('\n', "\n   ");
+                    write!(f, "\n  {}. {}", i + 1, step)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
 
-    let shortcuts_config = config_manager.get_shortcuts_config();
-    if shortcuts_config.enabled {
-        let extra_paths = shortcuts_config.extra_paths.unwrap_or_default();
-        let exclude = shortcuts_config.exclude.unwrap_or_default();
-        all_items.extend(collect_shortcut_items(&extra_paths, &exclude));
+impl Action {
+    /// One-line description for error messages: the [`Display`] form, or
+    /// just the key for a `steps:` command.
+    ///
+    /// [`Display`]: std::fmt::Display
+    fn summary(&self) -> String {
+        match self {
+            Action::Steps { key, .. } => format!("steps of {}", key),
+            other => other.to_string(),
+        }
     }
 
-    (all_commands, all_items)
+    /// Whether running this summons the webview window. Parallel steps run
+    /// those on the calling thread, one at a time, so window work stays off
+    /// the worker threads and two steps can't both find no window and open
+    /// one each.
+    fn is_ui_bound(&self) -> bool {
+        match self {
+            Action::Webview { .. } => true,
+            Action::Steps { steps, .. } => steps.iter().any(Action::is_ui_bound),
+            _ => false,
+        }
+    }
 }
 
-/// Execute an action from the GUI launcher. Called when user presses Enter.
-/// Takes the raw input text from the GUI and dispatches the appropriate action.
-pub fn execute_action(input: &str) -> Result<()> {
-    let config_manager = ConfigManager::new()?;
-    let resolved = config_manager.resolve_current();
-    let scope_browser: Option<String> = resolved.as_ref().and_then(|(_, client, project)| {
-        project
-            .as_ref()
-            .and_then(|(_, p)| p.browser.clone())
-            .or_else(|| client.browser.clone())
-    });
+/// An [`Action`] plus the history key recorded when it runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedAction {
+    pub action: Action,
+    pub history_key: Option<String>,
+}
+
+/// Resolve raw launcher input to the action [`execute_action`] would take,
+/// without side effects. Order: calculator expression, file path, then
+/// commands and provider items via [`resolve_item`], then a bare URL.
+///
+/// `loaded` are the items the caller already has (the launcher's list);
+/// provider items are matched against those instead of asking every
+/// provider again. Without them, providers are only asked when the input
+/// doesn't name a command.
+pub fn resolve_action(
+    config_manager: &ConfigManager,
+    input: &str,
+    usage: &Usage,
+    loaded: Option<&[ListItem]>,
+) -> Result<ResolvedAction> {
+    // Calculator mode
+    if let Some(expr) = input.strip_prefix('=') {
+        return match eval_calc_input(expr) {
+            CalcResult::Ok(result) => Ok(ResolvedAction {
+                action: Action::Calculate {
+                    expression: expr.trim().to_string(),
+                    result,
+                },
+                history_key: Some(input.to_string()),
+            }),
+            _ => anyhow::bail!("Invalid expression: '{}'", expr.trim()),
+        };
+    }
 
     // File path mode
     if is_file_path(input) {
-        let path = std::path::Path::new(input);
-        if path.exists() {
-            crate::history::record(input).ok();
-            browser::launch_shortcut(input, false)?;
-            return Ok(());
-        } else {
+        if !std::path::Path::new(input).exists() {
             anyhow::bail!("Path does not exist: '{}'", input);
         }
+        return Ok(ResolvedAction {
+            action: Action::Launch {
+                path: input.to_string(),
+            },
+            history_key: Some(input.to_string()),
+        });
     }
 
-    let (sorted_commands, all_items) = load_items(&config_manager);
-
     let keyword = input.split_whitespace().next().unwrap_or(input);
+    let (sorted_commands, mut all_items) = load_command_items(config_manager);
+    match loaded {
+        Some(items) => all_items.extend(
+            items
+                .iter()
+                .filter(|item| item.kind.provider().is_some())
+                .cloned(),
+        ),
+        None if find_named(&all_items, input).is_some()
+            || find_named(&all_items, keyword).is_some() => {}
+        None => all_items.extend(crate::providers::collect(
+            &crate::providers::registered(config_manager),
+            keyword,
+        )),
+    }
 
-    match resolve_item(&all_items, input) {
-        Some((item, args)) => {
-            crate::history::record(&item.key).ok();
-            match &item.kind {
-                ListItemKind::Shortcut { path } => {
-                    browser::launch_shortcut(path, false)?;
-                }
-                ListItemKind::Command => {
-                    let selected_command = sorted_commands
-                        .iter()
-                        .find(|cmd| cmd.key.to_lowercase() == item.key.to_lowercase())
-                        .ok_or_else(|| anyhow::anyhow!("Command '{}' not found", item.key))?;
-
-                    if selected_command.webview {
-                        let url = selected_command.url.as_ref().ok_or_else(|| {
-                            anyhow::anyhow!(
-                                "Command '{}' has 'webview: true' but no 'url' configured",
-                                selected_command.key
-                            )
-                        })?;
-                        return crate::commands::webview::summon_or_open(
-                            url,
-                            config_manager.get_monitor(),
-                            Some(&selected_command.key),
-                        );
-                    }
+    let Some((item, args)) = resolve_item(&all_items, input, usage) else {
+        if is_url(keyword) {
+            let url = if keyword.starts_with("http://") || keyword.starts_with("https://") {
+                keyword.to_string()
+            } else {
+                format!("https://{}", keyword)
+            };
+            return Ok(ResolvedAction {
+                action: Action::Open {
+                    url,
+                    browser: config_manager.get_effective_browser().to_string(),
+                },
+                history_key: None,
+            });
+        }
+        anyhow::bail!("No command found matching '{}'", keyword);
+    };
 
-                    if let Some(ref cmd_str) = selected_command.command {
-                        let final_args =
-                            merge_args(selected_command.args.as_deref(), args.as_deref());
-                        browser::open_command_with_args(
-                            cmd_str,
-                            None,
-                            final_args.as_deref(),
-                            false,
-                        )?;
-                    } else {
-                        let url = selected_command.url.as_ref().ok_or_else(|| {
-                            anyhow::anyhow!(
-                                "Command '{}' has neither 'url' nor 'command' configured",
-                                selected_command.key
-                            )
-                        })?;
-
-                        let resolved_browser = selected_command
-                            .browser
-                            .as_deref()
-                            .or(scope_browser.as_deref())
-                            .or_else(|| Some(config_manager.get_default_browser()));
-
-                        let effective_browser;
-                        let browser_arg = match resolved_browser {
-                            Some(b) => {
-                                let b = match selected_command.args.as_deref() {
-                                    Some(a) => {
-                                        effective_browser = format!("{} {}", b, a);
-                                        effective_browser.as_str()
-                                    }
-                                    None => b,
-                                };
-                                Some(b)
-                            }
-                            None => None,
-                        };
+    let history_key = Some(item.key.clone());
+    if item.kind.provider().is_some() {
+        let providers = crate::providers::registered(config_manager);
+        return Ok(ResolvedAction {
+            action: crate::providers::resolve(&providers, item, args.as_deref())?,
+            history_key,
+        });
+    }
+    let selected_command = sorted_commands
+        .iter()
+        .find(|cmd| cmd.key.to_lowercase() == item.key.to_lowercase())
+        .ok_or_else(|| anyhow::anyhow!("Command '{}' not found", item.key))?;
 
-                        let final_url;
-                        let effective_url = if browser_arg.is_some() {
-                            if let Some(ref user_args) = args {
-                                final_url = encode_url_args(url, user_args);
-                                &final_url
-                            } else {
-                                url
-                            }
-                        } else {
-                            url
-                        };
+    Ok(ResolvedAction {
+        action: command_action(
+            config_manager,
+            &sorted_commands,
+            selected_command,
+            args.as_deref(),
+            &mut Vec::new(),
+        )?,
+        history_key,
+    })
+}
 
-                        let final_args = if browser_arg.is_some() {
-                            None
-                        } else {
-                            merge_args(selected_command.args.as_deref(), args.as_deref())
-                        };
+/// A command running just the inline `step`.
+fn inline_command(key: String, step: &InlineStep) -> ProjectCommand {
+    ProjectCommand {
+        key,
+        aliases: None,
+        url: step.url.clone(),
+        command: step.command.clone(),
+        browser: step.browser.clone(),
+        args: step.args.clone(),
+        cwd: step.cwd.clone(),
+        steps: None,
+        parallel: false,
+        webview: step.webview,
+        pinned: false,
+    }
+}
 
-                        browser::open_command_with_args(
-                            effective_url,
-                            browser_arg,
-                            final_args.as_deref(),
-                            false,
-                        )?;
-                    }
+/// The action of a standalone inline step named `key` (a provider item's
+/// action) with the user `args`, resolved like a command of the current
+/// selection.
+pub fn inline_action(
+    config_manager: &ConfigManager,
+    key: &str,
+    step: &InlineStep,
+    args: Option<&str>,
+) -> Result<Action> {
+    let cmd = inline_command(key.to_string(), step);
+    command_action(config_manager, &[], &cmd, args, &mut Vec::new())
+}
+
+/// The action `cmd` takes with the user `args`. A `steps:` command expands
+/// each step against `commands` (the effective command set); `chain` holds
+/// the keys being expanded, so a step referring back to one is an error.
+fn command_action(
+    config_manager: &ConfigManager,
+    commands: &[ProjectCommand],
+    cmd: &ProjectCommand,
+    args: Option<&str>,
+    chain: &mut Vec<String>,
+) -> Result<Action> {
+    if let Some(ref steps) = cmd.steps {
+        if chain.contains(&cmd.key) {
+            chain.push(cmd.key.clone());
+            anyhow::bail!("Command steps form a cycle: {}", chain.join(" -> "));
+        }
+        chain.push(cmd.key.clone());
+        let mut actions = Vec::with_capacity(steps.len());
+        for (i, step) in steps.iter().enumerate() {
+            let action = match step {
+                Step::Key(key) => {
+                    let target = commands.iter().find(|c| c.is_named(key)).ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Step {} of command '{}' refers to unknown command '{}'",
+                            i + 1,
+                            cmd.key,
+                            key
+                        )
+                    })?;
+                    command_action(config_manager, commands, target, args, chain)?
+                }
+                Step::Inline(step) => {
+                    let mut inline = inline_command(format!("{} step {}", cmd.key, i + 1), step);
+                    inline.browser = inline.browser.or_else(|| cmd.browser.clone());
+                    command_action(config_manager, commands, &inline, args, chain)?
                 }
+            };
+            actions.push(action);
+        }
+        chain.pop();
+        return Ok(Action::Steps {
+            key: cmd.key.clone(),
+            parallel: cmd.parallel,
+            steps: actions,
+        });
+    }
+
+    // Placeholders are resolved in the configured text before the user args
+    // go in, so `${...}` typed by the user stays as it is.
+    let vars = Vars::from_selection(config_manager);
+    let cmd = &expand_placeholders(cmd, &vars)?;
+
+    // `{0}`/`{query}` parameters consume the user args instead of appending them.
+    let fill = |template: &str, encode: bool| {
+        fill_template(template, args, encode).map_err(|param| {
+            anyhow::anyhow!(
+                "Command '{}' needs an argument for {}: {}",
+                cmd.key,
+                param,
+                template
+            )
+        })
+    };
+
+    if cmd.webview {
+        let url = cmd.url.as_ref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Command '{}' has 'webview: true' but no 'url' configured",
+                cmd.key
+            )
+        })?;
+        return Ok(Action::Webview {
+            url: fill(url, true)?.unwrap_or_else(|| url.clone()),
+            title: cmd.key.clone(),
+        });
+    }
+
+    if let Some(ref cmd_str) = cmd.command {
+        let cwd = command_dir(config_manager, cmd);
+        let env = interpolate::expand_env(&config_manager.get_effective_env(), &vars)?;
+        return Ok(match fill(cmd_str, false)? {
+            Some(command) => Action::Run {
+                command,
+                args: cmd.args.clone(),
+                cwd,
+                env,
+            },
+            None => Action::Run {
+                command: cmd_str.clone(),
+                args: merge_args(cmd.args.as_deref(), args),
+                cwd,
+                env,
+            },
+        });
+    }
+
+    let url = cmd.url.as_ref().ok_or_else(|| {
+        anyhow::anyhow!(
+            "Command '{}' has neither 'url' nor 'command' configured",
+            cmd.key
+        )
+    })?;
+
+    // Browser hierarchy: command > project > client > config > default.
+    // Command-level args are browser args (e.g. a profile flag).
+    let browser = cmd
+        .browser
+        .as_deref()
+        .unwrap_or_else(|| config_manager.get_effective_browser());
+    let browser = match cmd.args.as_deref() {
+        Some(a) => format!("{} {}", browser, a),
+        None => browser.to_string(),
+    };
+
+    let url = match (fill(url, true)?, args) {
+        (Some(filled), _) => filled,
+        (None, Some(user_args)) => encode_url_args(url, user_args),
+        (None, None) => url.clone(),
+    };
+
+    Ok(Action::Open { url, browser })
+}
+
+/// `cmd` with the `${...}` placeholders in its `url`, `command`, `browser`,
+/// `args` and `cwd` resolved.
+fn expand_placeholders(cmd: &ProjectCommand, vars: &Vars) -> Result<ProjectCommand> {
+    let expand = |text: &Option<String>| {
+        text.as_deref()
+            .map(|text| interpolate::expand(text, vars))
+            .transpose()
+    };
+    Ok(ProjectCommand {
+        url: expand(&cmd.url)?,
+        command: expand(&cmd.command)?,
+        browser: expand(&cmd.browser)?,
+        args: expand(&cmd.args)?,
+        cwd: expand(&cmd.cwd)?,
+        ..cmd.clone()
+    })
+}
+
+/// Directory a `command:` entry runs in: its `cwd` resolved against the
+/// selection's path (project > client), else that path itself.
+fn command_dir(config_manager: &ConfigManager, cmd: &ProjectCommand) -> Option<String> {
+    let scope = config_manager.get_scope_path().map(config::expand_tilde);
+    let dir = match (&cmd.cwd, scope) {
+        (Some(cwd), Some(scope)) => scope.join(config::expand_tilde(cwd)),
+        (Some(cwd), None) => config::expand_tilde(cwd),
+        (None, scope) => scope?,
+    };
+    Some(dir.display().to_string())
+}
+
+/// Carry out a resolved action.
+pub fn perform(action: &Action, monitor: Option<u32>) -> Result<()> {
+    match action {
+        Action::Calculate { result, .. } => {
+            println!("{}", format!("= {}", result).bold().green());
+            Ok(())
+        }
+        Action::Launch { path } => browser::launch_shortcut(path, false),
+        Action::Webview { url, title } => {
+            crate::commands::webview::summon_or_open(url, monitor, Some(title))
+        }
+        Action::Run {
+            command,
+            args,
+            cwd,
+            env,
+        } => browser::run_terminal_command(command, args.as_deref(), cwd.as_deref(), env, false),
+        Action::Open { url, browser } => {
+            browser::open_command_with_args(url, Some(browser), None, false)
+        }
+        Action::Steps {
+            key,
+            parallel: false,
+            steps,
+        } => {
+            for (i, step) in steps.iter().enumerate() {
+                perform(step, monitor).with_context(|| {
+                    format!(
+                        "Step {} of {} in '{}' failed, skipping the rest ({})",
+                        i + 1,
+                        steps.len(),
+                        key,
+                        step.summary()
+                    )
+                })?;
             }
+            Ok(())
         }
-        None => {
-            if is_url(keyword) {
-                let url = if keyword.starts_with("http://") || keyword.starts_with("https://") {
-                    keyword.to_string()
-                } else {
-                    format!("https://{}", keyword)
-                };
-                let browser_name = scope_browser
-                    .as_deref()
-                    .unwrap_or_else(|| config_manager.get_default_browser());
-                return browser::open_url_in_browser(&url, browser_name, false);
+        Action::Steps {
+            key,
+            parallel: true,
+            steps,
+        } => {
+            let failures: Vec<String> = std::thread::scope(|scope| {
+                let running: Vec<_> = steps
+                    .iter()
+                    .map(|step| {
+                        (!step.is_ui_bound()).then(|| scope.spawn(move || perform(step, monitor)))
+                    })
+                    .collect();
+                // Steps that open a window run here while the others run
+                let here: Vec<Result<()>> = steps
+                    .iter()
+                    .zip(&running)
+                    .map(|(step, handle)| match handle {
+                        Some(_) => Ok(()),
+                        None => perform(step, monitor),
+                    })
+                    .collect();
+                running
+                    .into_iter()
+                    .zip(here)
+                    .zip(steps)
+                    .enumerate()
+                    .filter_map(|(i, ((handle, result), step))| {
+                        let outcome = match handle {
+                            Some(handle) => handle.join(),
+                            None => Ok(result),
+                        };
+                        let error = match outcome {
+                            Ok(result) => format!("{:#}", result.err()?),
+                            Err(_) => "panicked".to_string(),
+                        };
+                        Some(format!("step {} ({}): {}", i + 1, step.summary(), error))
+                    })
+                    .collect()
+            });
+            if !failures.is_empty() {
+                anyhow::bail!(
+                    "{} of {} steps in '{}' failed: {}",
+                    failures.len(),
+                    steps.len(),
+                    key,
+                    failures.join("; ")
+                );
             }
-            anyhow::bail!("No command found matching '{}'", keyword);
+            Ok(())
         }
     }
+}
 
-    Ok(())
+/// Execute an action from the GUI launcher. Called when user presses Enter.
+/// Takes the raw input text from the GUI and dispatches the appropriate action;
+/// `loaded` are the items the launcher lists (see [`resolve_action`]).
+pub fn execute_action(input: &str, loaded: Option<&[ListItem]>) -> Result<()> {
+    let config_manager = ConfigManager::new()?;
+    let usage = Usage::from_recent(&crate::history::load());
+    let resolved = resolve_action(&config_manager, input, &usage, loaded)?;
+    if let Some(ref key) = resolved.history_key {
+        crate::history::record(key).ok();
+    }
+    perform(&resolved.action, config_manager.get_monitor())
 }
 
 pub fn execute_gui(monitor: Option<u32>) -> Result<()> {
     let config_manager = ConfigManager::new()?;
     let display_name = selection_display_name(&config_manager);
 
-    // Load commands only (fast) — shortcuts are deferred to a background thread
-    let (_, command_items) = load_command_items(&config_manager);
-
-    // Spawn async shortcut collection so the window can open immediately
-    let shortcuts_config = config_manager.get_shortcuts_config();
-    let shortcut_rx = if shortcuts_config.enabled {
-        let (tx, rx) = std::sync::mpsc::channel();
-        let extra_paths = shortcuts_config.extra_paths.unwrap_or_default();
-        let exclude = shortcuts_config.exclude.unwrap_or_default();
-        std::thread::spawn(move || {
-            let _ = tx.send(collect_shortcut_items(&extra_paths, &exclude));
-        });
-        Some(rx)
-    } else {
-        None
-    };
+    // Commands and sync providers now; background providers (shortcuts) are
+    // merged into the window as they finish so it can open immediately
+    let (_, mut items) = load_command_items(&config_manager);
+    let (provided, provider_rx) =
+        crate::providers::start(crate::providers::registered(&config_manager));
+    items.extend(provided.into_iter().flat_map(|batch| batch.items));
 
     let recent_keys = crate::history::load();
-    let mut state = crate::ui::WindowState::new(command_items, recent_keys);
+    let mut state = crate::ui::WindowState::new(items, recent_keys);
+    if let Some(rx) = provider_rx {
+        state.receive_items(rx);
+    }
+    state.set_targets(crate::commands::switch::targets(&config_manager));
     state.show();
 
     eframe::run_native(
@@ -419,7 +778,6 @@ pub fn execute_gui(monitor: Option<u32>) -> Result<()> {
             Ok(Box::new(crate::ui::LauncherApp::new(
                 state,
                 display_name,
-                shortcut_rx,
                 monitor,
             )))
         }),
@@ -427,13 +785,31 @@ pub fn execute_gui(monitor: Option<u32>) -> Result<()> {
     .map_err(|e| anyhow::anyhow!("GUI error: {}", e))
 }
 
-pub fn execute(_debug: bool) -> Result<()> {
+/// Print the active selection and its effective command set without prompting.
+fn print_listing(config_manager: &ConfigManager, format: OutputFormat) -> Result<()> {
+    let (commands, _) = load_command_items(config_manager);
+    let browser = config_manager.get_effective_browser();
+    let listing = ListOutput {
+        selection: SelectionOutput::from_config(config_manager),
+        commands: commands
+            .iter()
+            .map(|cmd| CommandOutput::from_command(cmd, browser))
+            .collect(),
+    };
+    output::print(format, &listing, ListOutput::plain)
+}
+
+pub fn execute(_debug: bool, format: Option<OutputFormat>) -> Result<()> {
     let config_manager = ConfigManager::new()?;
 
+    if let Some(format) = format {
+        return print_listing(&config_manager, format);
+    }
+
     let display_name_owned = selection_display_name(&config_manager);
     let display_name = display_name_owned.as_str();
 
-    let (_, all_items) = load_items(&config_manager);
+    let (_, all_items) = load_items(&config_manager, "");
 
     if all_items.is_empty() {
         println!(
@@ -451,6 +827,7 @@ pub fn execute(_debug: bool) -> Result<()> {
     let recent_keys = crate::history::load();
     let autocomplete = ListAutocomplete {
         items: all_items.clone(),
+        usage: Usage::from_recent(&recent_keys),
         recent_keys,
     };
 
@@ -483,16 +860,435 @@ pub fn execute(_debug: bool) -> Result<()> {
     // Clean the input (strip ANSI codes from inquire's colored output)
     let cleaned_input = {
         let stripped = strip_ansi_codes(&user_input);
-        if let Some(arrow_pos) = stripped.find(" → ") {
-            stripped[..arrow_pos].trim().to_string()
-        } else if let Some(rest) = stripped.strip_prefix(APP_PREFIX) {
-            rest.to_string()
-        } else if let Some(rest) = stripped.strip_prefix(PATH_PREFIX) {
+        if let Some(rest) = stripped.strip_prefix(PATH_PREFIX) {
             rest.to_string()
         } else {
-            stripped
+            let stripped = strip_tag(&stripped);
+            match stripped.find(" → ") {
+                Some(arrow_pos) => stripped[..arrow_pos].trim().to_string(),
+                None => stripped.to_string(),
+            }
         }
     };
 
-    execute_action(&cleaned_input)
+    execute_action(&cleaned_input, Some(&all_items))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "\
+currentClient: acme
+defaultBrowser: firefox
+global:
+- key: search
+  url: https://google.com/search?q=
+clients:
+- name: acme
+  browser: chrome
+  commands:
+  - key: build
+    command: cargo build
+    args: --release
+  - key: docs
+    url: https://docs.acme.dev/
+    browser: edge
+    args: -P work
+  - key: board
+    url: https://board.acme.dev/
+    webview: true
+  - key: jira
+    url: https://jira.acme.dev/browse/{0}?focus={1?description}
+";
+
+    fn resolve(input: &str) -> Result<ResolvedAction> {
+        resolve_action(
+            &ConfigManager::from_test_yaml(CONFIG),
+            input,
+            &Usage::default(),
+            None,
+        )
+    }
+
+    #[test]
+    fn resolve_url_command_encodes_args_in_scope_browser() {
+        let resolved = resolve("search hello world").unwrap();
+        assert_eq!(
+            resolved.action,
+            Action::Open {
+                url: "https://google.com/search?q=hello%20world".to_string(),
+                browser: "chrome".to_string(),
+            }
+        );
+        assert_eq!(resolved.history_key.as_deref(), Some("search"));
+    }
+
+    #[test]
+    fn resolve_url_command_appends_command_args_to_browser() {
+        let resolved = resolve("docs").unwrap();
+        assert_eq!(
+            resolved.action,
+            Action::Open {
+                url: "https://docs.acme.dev/".to_string(),
+                browser: "edge -P work".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn resolve_terminal_command_merges_args() {
+        let resolved = resolve("build --verbose").unwrap();
+        assert_eq!(
+            resolved.action,
+            Action::Run {
+                command: "cargo build".to_string(),
+                args: Some("--release --verbose".to_string()),
+                cwd: None,
+                env: EnvMap::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn resolve_url_template_fills_params_instead_of_appending() {
+        let resolved = resolve("jira ACME-12 comments").unwrap();
+        assert_eq!(
+            resolved.action,
+            Action::Open {
+                url: "https://jira.acme.dev/browse/ACME-12?focus=comments".to_string(),
+                browser: "chrome".to_string(),
+            }
+        );
+        let err = resolve("jira").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Command 'jira' needs an argument for {0}: https://jira.acme.dev/browse/{0}?focus={1?description}"
+        );
+    }
+
+    #[test]
+    fn resolve_webview_command() {
+        let resolved = resolve("board").unwrap();
+        assert_eq!(
+            resolved.action,
+            Action::Webview {
+                url: "https://board.acme.dev/".to_string(),
+                title: "board".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn resolve_alias_records_the_key() {
+        let config_manager = ConfigManager::from_test_yaml(
+            "global:\n- key: github\n  aliases: [gh, repo]\n  url: https://github.com/\n",
+        );
+        let resolved =
+            resolve_action(&config_manager, "gh rust-lang", &Usage::default(), None).unwrap();
+        assert_eq!(resolved.history_key.as_deref(), Some("github"));
+        assert_eq!(
+            resolved.action,
+            Action::Open {
+                url: "https://github.com/rust-lang".to_string(),
+                browser: "firefox".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn resolve_partial_key() {
+        let resolved = resolve("bui").unwrap();
+        assert_eq!(resolved.history_key.as_deref(), Some("build"));
+    }
+
+    #[test]
+    fn resolve_bare_url_not_recorded() {
+        let resolved = resolve("example.com").unwrap();
+        assert_eq!(
+            resolved.action,
+            Action::Open {
+                url: "https://example.com".to_string(),
+                browser: "chrome".to_string(),
+            }
+        );
+        assert_eq!(resolved.history_key, None);
+    }
+
+    #[test]
+    fn resolve_calculator_expression() {
+        let resolved = resolve("=6*7").unwrap();
+        assert_eq!(
+            resolved.action,
+            Action::Calculate {
+                expression: "6*7".to_string(),
+                result: "42".to_string(),
+            }
+        );
+        assert_eq!(resolved.history_key.as_deref(), Some("=6*7"));
+        assert!(resolve("=6*").is_err());
+    }
+
+    #[test]
+    fn resolve_unknown_input_errors() {
+        let err = resolve("nope").unwrap_err();
+        assert_eq!(err.to_string(), "No command found matching 'nope'");
+    }
+
+    #[test]
+    fn provider_items_resolve_from_the_loaded_list() {
+        let loaded = [ListItem {
+            key: "Notepad".to_string(),
+            aliases: Vec::new(),
+            display_detail: String::new(),
+            kind: ListItemKind::Shortcut {
+                path: "/apps/notepad.lnk".to_string(),
+            },
+            pinned: false,
+        }];
+        let config_manager = ConfigManager::from_test_yaml(CONFIG);
+        let resolved =
+            resolve_action(&config_manager, "notepad", &Usage::default(), Some(&loaded)).unwrap();
+        assert_eq!(
+            resolved.action,
+            Action::Launch {
+                path: "/apps/notepad.lnk".to_string()
+            }
+        );
+        // Commands still come from the config, not the loaded list.
+        let resolved = resolve_action(&config_manager, "build", &Usage::default(), Some(&[]));
+        assert_eq!(resolved.unwrap().history_key.as_deref(), Some("build"));
+    }
+
+    #[test]
+    fn strip_tag_removes_app_and_provider_prefixes() {
+        assert_eq!(strip_tag("[app] Notepad"), "Notepad");
+        assert_eq!(strip_tag("[jira] PROJ-1 → Fix login"), "PROJ-1 → Fix login");
+        assert_eq!(strip_tag("build → cargo build"), "build → cargo build");
+    }
+
+    #[test]
+    fn action_display_is_readable() {
+        let action = Action::Run {
+            command: "cargo build".to_string(),
+            args: Some("--release".to_string()),
+            cwd: Some("/src/acme".to_string()),
+            env: EnvMap::new(),
+        };
+        assert_eq!(
+            action.to_string(),
+            "run cargo build --release (in /src/acme)"
+        );
+    }
+
+    #[test]
+    fn terminal_commands_run_in_selection_path_or_cwd() {
+        let config_manager = ConfigManager::from_test_yaml(
+            "currentClient: acme\ncurrentProject: api\nglobal:\n- key: top\n  command: ls\nclients:\n- name: acme\n  path: /src/acme\n  commands:\n  - key: test\n    command: cargo test\n    cwd: crates/core\n  - key: tmp\n    command: ls\n    cwd: /tmp\n  - key: docs\n    url: https://docs.acme.dev/\n    cwd: docs\n  projects:\n  - name: api\n    path: /src/acme-api\n",
+        );
+        let cwd =
+            |input: &str| match resolve_action(&config_manager, input, &Usage::default(), None)
+                .unwrap()
+                .action
+            {
+                Action::Run { cwd, .. } => cwd,
+                other => panic!("not a command: {}", other),
+            };
+        // Project path overrides client path, for global commands too.
+        assert_eq!(cwd("top").as_deref(), Some("/src/acme-api"));
+        assert_eq!(cwd("test").as_deref(), Some("/src/acme-api/crates/core"));
+        assert_eq!(cwd("tmp").as_deref(), Some("/tmp"));
+        // URLs don't run in a directory.
+        assert!(matches!(
+            resolve_action(&config_manager, "docs", &Usage::default(), None)
+                .unwrap()
+                .action,
+            Action::Open { .. }
+        ));
+
+        let no_path = ConfigManager::from_test_yaml(CONFIG);
+        assert!(matches!(
+            resolve_action(&no_path, "build", &Usage::default(), None)
+                .unwrap()
+                .action,
+            Action::Run { cwd: None, .. }
+        ));
+    }
+
+    #[test]
+    fn terminal_commands_carry_the_selection_env() {
+        let config_manager = ConfigManager::from_test_yaml(
+            "currentClient: acme\nenv:\n  REGION: eu\nclients:\n- name: acme\n  env:\n    AWS_PROFILE: ${client.name}\n  commands:\n  - key: ls\n    command: aws s3 ls\n",
+        );
+        let action = resolve_action(&config_manager, "ls", &Usage::default(), None)
+            .unwrap()
+            .action;
+        let Action::Run { env, .. } = action else {
+            panic!("not a command: {}", action);
+        };
+        assert_eq!(env.get("AWS_PROFILE").map(String::as_str), Some("acme"));
+        assert_eq!(env.get("REGION").map(String::as_str), Some("eu"));
+    }
+
+    #[test]
+    fn placeholders_expand_in_the_config_but_not_in_user_args() {
+        let config_manager = ConfigManager::from_test_yaml(
+            "currentClient: acme\nclients:\n- name: acme\n  path: /src/acme\n  commands:\n  - key: say\n    command: echo ${client.name} ${HOME}\n    cwd: ${client.path}/api\n  - key: deploy\n    command: deploy\n    args: --env ${project.name}\n",
+        );
+        let resolve = |input: &str| resolve_action(&config_manager, input, &Usage::default(), None);
+        assert_eq!(
+            resolve("say ${env:HOME} ${client.name}")
+                .unwrap()
+                .action
+                .to_string(),
+            "run echo acme ${HOME} ${env:HOME} ${client.name} (in /src/acme/api)"
+        );
+        assert_eq!(
+            resolve("deploy").unwrap_err().to_string(),
+            "'${project.name}' is not defined for the current selection"
+        );
+    }
+
+    const STEPS_CONFIG: &str = "\
+currentClient: acme
+global:
+- key: search
+  aliases: [google]
+  url: https://google.com/search?q={query?rust}
+clients:
+- name: acme
+  browser: chrome
+  commands:
+  - key: docs
+    url: https://docs.acme.dev/
+    browser: edge
+  - key: morning
+    browser: firefox
+    steps:
+    - docs
+    - Google
+    - url: https://grafana.acme.dev/
+    - command: npm run dev
+  - key: loop
+    steps: [again]
+  - key: again
+    steps: [loop]
+  - key: broken
+    steps: [nope]
+";
+
+    #[test]
+    fn resolve_steps_expands_keys_and_inline_actions() {
+        let config_manager = ConfigManager::from_test_yaml(STEPS_CONFIG);
+        let resolved = resolve_action(&config_manager, "morning", &Usage::default(), None).unwrap();
+        assert_eq!(resolved.history_key.as_deref(), Some("morning"));
+        let Action::Steps {
+            key,
+            parallel,
+            steps,
+        } = resolved.action
+        else {
+            panic!("not steps: {}", resolved.action);
+        };
+        assert_eq!((key.as_str(), parallel), ("morning", false));
+        // Referenced commands keep their own browser; inline URLs take the
+        // command's before the selection's.
+        assert_eq!(
+            steps,
+            [
+                Action::Open {
+                    url: "https://docs.acme.dev/".to_string(),
+                    browser: "edge".to_string(),
+                },
+                Action::Open {
+                    url: "https://google.com/search?q=rust".to_string(),
+                    browser: "chrome".to_string(),
+                },
+                Action::Open {
+                    url: "https://grafana.acme.dev/".to_string(),
+                    browser: "firefox".to_string(),
+                },
+                Action::Run {
+                    command: "npm run dev".to_string(),
+                    args: None,
+                    cwd: None,
+                    env: EnvMap::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve_steps_rejects_cycles_and_unknown_keys() {
+        let config_manager = ConfigManager::from_test_yaml(STEPS_CONFIG);
+        let err = resolve_action(&config_manager, "loop", &Usage::default(), None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Command steps form a cycle: loop -> again -> loop"
+        );
+        let err = resolve_action(&config_manager, "broken", &Usage::default(), None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Step 1 of command 'broken' refers to unknown command 'nope'"
+        );
+    }
+
+    #[test]
+    fn webview_steps_are_ui_bound_even_when_nested() {
+        let open = Action::Open {
+            url: "https://docs.acme.dev/".to_string(),
+            browser: "edge".to_string(),
+        };
+        let webview = Action::Webview {
+            url: "https://chat.acme.dev/".to_string(),
+            title: "chat".to_string(),
+        };
+        let steps = |steps| Action::Steps {
+            key: "morning".to_string(),
+            parallel: false,
+            steps,
+        };
+        assert!(!open.is_ui_bound());
+        assert!(webview.is_ui_bound());
+        assert!(!steps(vec![open.clone()]).is_ui_bound());
+        assert!(steps(vec![open, steps(vec![webview])]).is_ui_bound());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn steps_stop_at_the_first_failure_unless_parallel() {
+        let dir = std::env::temp_dir().join(format!("ps-steps-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let run = |command: &str| Action::Run {
+            command: command.to_string(),
+            args: None,
+            cwd: Some(dir.display().to_string()),
+            env: EnvMap::new(),
+        };
+        let steps = |parallel| Action::Steps {
+            key: "morning".to_string(),
+            parallel,
+            steps: vec![run("exit 3"), run("touch ran")],
+        };
+
+        let err = perform(&steps(false), None).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("Step 1 of 2 in 'morning' failed, skipping the rest"),
+            "got: {:#}",
+            err
+        );
+        assert!(!dir.join("ran").exists());
+
+        let err = perform(&steps(true), None).unwrap_err();
+        let ran = dir.join("ran").exists();
+        std::fs::remove_dir_all(&dir).ok();
+        assert!(ran);
+        assert!(
+            err.to_string()
+                .starts_with("1 of 2 steps in 'morning' failed: step 1 (run exit 3"),
+            "got: {:#}",
+            err
+        );
+    }
 }
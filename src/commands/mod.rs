@@ -1,5 +1,13 @@
+pub mod config;
 pub mod current;
+pub mod edit;
+pub mod env;
+pub mod history;
+pub mod import;
+pub mod init;
 pub mod list;
 pub mod open;
+pub mod path;
+pub mod run;
 pub mod switch;
 pub mod webview;
@@ -19,14 +19,13 @@ pub fn execute(key: &str) -> Result<()> {
         return browser::open_url_in_browser(&url, config_manager.get_default_browser(), false);
     }
 
-    let (current_client_name, client, project) = config_manager.resolve_current()
-        .ok_or_else(|| anyhow::anyhow!("No current client selected. Use \"project-switch switch\" to select a client first"))?;
+    let (current_client_name, _, project) = config_manager.resolve_current().ok_or_else(|| {
+        anyhow::anyhow!(
+            "No current client selected. Use \"project-switch switch\" to select a client first"
+        )
+    })?;
 
-    // Browser inheritance: project > client > global default.
-    let scope_browser = project
-        .as_ref()
-        .and_then(|(_, p)| p.browser.as_deref())
-        .or(client.browser.as_deref());
+    let scope_browser = config_manager.get_scope_browser();
 
     let command = match config_manager.get_effective_command(key) {
         Some(cmd) => cmd,
@@ -38,7 +37,7 @@ pub fn execute(key: &str) -> Result<()> {
                 } else {
                     format!("https://{}", key)
                 };
-                let browser = scope_browser.unwrap_or_else(|| config_manager.get_default_browser());
+                let browser = config_manager.get_effective_browser();
                 return browser::open_url_in_browser(&url, browser, false);
             }
             let scope = match &project {
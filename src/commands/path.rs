@@ -0,0 +1,99 @@
+//! `path`: print the directory of the current (or a named) client/project,
+//! for the `cd` shell function that `init` installs.
+
+use crate::commands::switch;
+use crate::config::{self, ConfigManager};
+use anyhow::Result;
+use std::path::PathBuf;
+
+/// The `path` of the named client/project, or of the active selection when
+/// `client` is `None`. Precedence: project > client.
+fn resolve(
+    config_manager: &ConfigManager,
+    client: Option<&str>,
+    project: Option<&str>,
+) -> Result<PathBuf> {
+    let (label, path) = match client {
+        Some(query) => {
+            let (client_name, project_name) =
+                switch::resolve_target(config_manager, query, project)?;
+            let entry = config_manager
+                .get_client(&client_name)
+                .ok_or_else(|| anyhow::anyhow!("Client '{}' not found", client_name))?;
+            let project_path = project_name.as_ref().and_then(|p| {
+                entry
+                    .projects
+                    .as_ref()
+                    .and_then(|projects| projects.iter().find(|proj| &proj.name == p))
+                    .and_then(|proj| proj.path.as_deref())
+            });
+            let label = match &project_name {
+                Some(p) => format!("{} / {}", client_name, p),
+                None => client_name.clone(),
+            };
+            (label, project_path.or(entry.path.as_deref()))
+        }
+        None => {
+            let label = match config_manager.resolve_current() {
+                Some((client_name, _, Some((project_name, _)))) => {
+                    format!("{} / {}", client_name, project_name)
+                }
+                Some((client_name, _, None)) => client_name.clone(),
+                None => anyhow::bail!(
+                    "No current client selected. Use \"project-switch switch\" to select a client first"
+                ),
+            };
+            (label, config_manager.get_scope_path())
+        }
+    };
+    path.map(config::expand_tilde)
+        .ok_or_else(|| anyhow::anyhow!("No path configured for {}", label))
+}
+
+pub fn execute(client: Option<&str>, project: Option<&str>) -> Result<()> {
+    let config_manager = ConfigManager::new()?;
+    println!("{}", resolve(&config_manager, client, project)?.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "\
+currentClient: acme
+currentProject: web
+clients:
+- name: acme
+  path: /src/acme
+  projects:
+  - name: web
+    path: /src/acme-web
+  - name: docs
+- name: beta
+";
+
+    #[test]
+    fn current_selection_prefers_project_path() {
+        let cm = ConfigManager::from_test_yaml(CONFIG);
+        assert_eq!(
+            resolve(&cm, None, None).unwrap(),
+            PathBuf::from("/src/acme-web")
+        );
+    }
+
+    #[test]
+    fn named_target_is_fuzzy_matched_and_falls_back_to_client_path() {
+        let cm = ConfigManager::from_test_yaml(CONFIG);
+        assert_eq!(
+            resolve(&cm, Some("ac"), None).unwrap(),
+            PathBuf::from("/src/acme")
+        );
+        assert_eq!(
+            resolve(&cm, Some("acme"), Some("doc")).unwrap(),
+            PathBuf::from("/src/acme")
+        );
+        let err = resolve(&cm, Some("beta"), None).unwrap_err();
+        assert_eq!(err.to_string(), "No path configured for beta");
+    }
+}
@@ -0,0 +1,35 @@
+//! Non-interactive executor: resolves input exactly like the launcher does
+//! (commands, shortcuts, URLs, file paths, `=` expressions) without any UI.
+
+use crate::commands::list;
+use crate::config::ConfigManager;
+use crate::launcher::Usage;
+use crate::utils::output::{self, OutputFormat};
+use anyhow::Result;
+
+pub fn execute(
+    key: &str,
+    args: &[String],
+    dry_run: bool,
+    format: Option<OutputFormat>,
+) -> Result<()> {
+    let input = std::iter::once(key)
+        .chain(args.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if !dry_run {
+        return list::execute_action(&input, None);
+    }
+
+    let config_manager = ConfigManager::new()?;
+    let usage = Usage::from_recent(&crate::history::load());
+    let resolved = list::resolve_action(&config_manager, &input, &usage, None)?;
+    match format {
+        Some(format) => output::print(format, &resolved.action, |a| a.to_string()),
+        None => {
+            println!("{}", resolved.action);
+            Ok(())
+        }
+    }
+}
@@ -1,15 +1,93 @@
 use crate::config::ConfigManager;
+use crate::hooks::{self, Event};
+use crate::ui::state::SwitchTarget;
+use crate::utils::output::{self, OutputFormat, SelectionOutput, SwitchOutput};
 use anyhow::Result;
 use colored::*;
 use inquire::Select;
 
-pub fn execute() -> Result<()> {
+/// Argument that jumps back to the previous selection, like `cd -`.
+const PREVIOUS_SELECTION: &str = "-";
+
+/// Resolve a user-typed name against `names`. Tiers are tried in order —
+/// exact, case-insensitive, prefix, substring, then subsequence (all
+/// case-insensitive) — and the first tier with any candidate decides: one
+/// candidate wins, several are reported as ambiguous.
+fn match_name<'a>(names: &[&'a str], query: &str, kind: &str) -> Result<&'a str> {
+    let query_lower = query.to_lowercase();
+    let is_subsequence = |name: &str| {
+        let mut chars = name.chars();
+        query_lower.chars().all(|q| chars.any(|c| c == q))
+    };
+    let tiers: [&dyn Fn(&str) -> bool; 5] = [
+        &|name| name == query,
+        &|name| name.to_lowercase() == query_lower,
+        &|name| name.to_lowercase().starts_with(&query_lower),
+        &|name| name.to_lowercase().contains(&query_lower),
+        &|name| is_subsequence(&name.to_lowercase()),
+    ];
+
+    for tier in tiers {
+        let candidates: Vec<&str> = names.iter().copied().filter(|n| tier(n)).collect();
+        match candidates.as_slice() {
+            [] => continue,
+            [only] => return Ok(only),
+            many => anyhow::bail!(
+                "'{}' is ambiguous — it matches {}s: {}",
+                query,
+                kind,
+                many.join(", ")
+            ),
+        }
+    }
+    anyhow::bail!("No {} matching '{}'", kind, query)
+}
+
+/// Resolve positional `switch <client> [project]` arguments to exact names.
+pub fn resolve_target(
+    config_manager: &ConfigManager,
+    client_query: &str,
+    project_query: Option<&str>,
+) -> Result<(String, Option<String>)> {
+    let client_names: Vec<&str> = config_manager
+        .get_clients()
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect();
+    let client_name = match_name(&client_names, client_query, "client")?;
+
+    let project_name = match project_query {
+        Some(query) => {
+            let project_names: Vec<&str> = config_manager
+                .get_client(client_name)
+                .and_then(|c| c.projects.as_ref())
+                .map(|projects| projects.iter().map(|p| p.name.as_str()).collect())
+                .unwrap_or_default();
+            if project_names.is_empty() {
+                anyhow::bail!("Client '{}' has no projects", client_name);
+            }
+            Some(match_name(&project_names, query, "project")?.to_string())
+        }
+        None => None,
+    };
+
+    Ok((client_name.to_string(), project_name))
+}
+
+pub fn execute(
+    client: Option<&str>,
+    project: Option<&str>,
+    run_hooks: bool,
+    format: Option<OutputFormat>,
+) -> Result<()> {
     let mut config_manager = ConfigManager::new()?;
     let current_client = config_manager.get_current_client().cloned();
     let current_project = config_manager.get_current_project().cloned();
 
-    let clients = config_manager.get_clients();
-    if clients.is_empty() {
+    if config_manager.get_clients().is_empty() {
+        if format.is_some() {
+            anyhow::bail!("No clients found. Edit ~/.project-switch.yml to add one.");
+        }
         println!(
             "{}",
             "No clients found. Edit ~/.project-switch.yml to add one.".yellow()
@@ -17,10 +95,144 @@ pub fn execute() -> Result<()> {
         return Ok(());
     }
 
+    let (selected_client, selected_project) = match client {
+        Some(PREVIOUS_SELECTION) => {
+            if project.is_some() {
+                anyhow::bail!("'switch -' does not take a project argument");
+            }
+            let (client, project) = config_manager
+                .get_previous_selection()
+                .ok_or_else(|| anyhow::anyhow!("No previous selection to switch back to"))?;
+            (client.clone(), project.cloned())
+        }
+        Some(query) => resolve_target(&config_manager, query, project)?,
+        None => prompt_selection(
+            &config_manager,
+            current_client.as_deref(),
+            current_project.as_deref(),
+        )?,
+    };
+
+    let is_same_selection = current_client.as_deref() == Some(selected_client.as_str())
+        && current_project.as_deref() == selected_project.as_deref();
+
+    if !is_same_selection {
+        // Hook output would corrupt machine-readable output; it still goes to the log.
+        let quiet = format.is_some();
+        if run_hooks {
+            hooks::run(
+                &config_manager,
+                Event::Leave,
+                Some(&selected_client),
+                selected_project.as_deref(),
+                quiet,
+            );
+        }
+        config_manager.set_current_selection(&selected_client, selected_project.as_deref())?;
+        if run_hooks {
+            hooks::run(
+                &config_manager,
+                Event::Enter,
+                current_client.as_deref(),
+                current_project.as_deref(),
+                quiet,
+            );
+        }
+    }
+
+    if let Some(format) = format {
+        let switched = SwitchOutput {
+            changed: !is_same_selection,
+            selection: SelectionOutput::from_config(&config_manager),
+        };
+        return output::print(format, &switched, |s| s.selection.label());
+    }
+
+    let target = match &selected_project {
+        Some(p) => format!("project: {} / {}", selected_client, p),
+        None => format!("client: {}", selected_client),
+    };
+    if is_same_selection {
+        println!("{}", format!("Already on {}", target).blue());
+    } else {
+        println!("{}", format!("Switched to {}", target).green());
+    }
+
+    Ok(())
+}
+
+/// Every client followed by its nested projects, for the launcher's switch
+/// mode.
+pub fn targets(config_manager: &ConfigManager) -> Vec<SwitchTarget> {
+    let current_client = config_manager.get_current_client();
+    let current_project = config_manager.get_current_project();
+    let mut targets = Vec::new();
+    for client in config_manager.get_clients() {
+        let is_current_client = current_client == Some(&client.name);
+        targets.push(SwitchTarget {
+            client: client.name.clone(),
+            project: None,
+            current: is_current_client && current_project.is_none(),
+        });
+        for project in client.projects.iter().flatten() {
+            targets.push(SwitchTarget {
+                client: client.name.clone(),
+                project: Some(project.name.clone()),
+                current: is_current_client && current_project == Some(&project.name),
+            });
+        }
+    }
+    targets
+}
+
+/// Switch from the GUI launcher. The selection is saved right away so the
+/// launcher can reload in place; the onLeave/onEnter hooks then run on a
+/// background thread, logging only, so a slow hook can't freeze the window.
+/// Returns the manager with the new selection.
+pub fn switch_detached(client: &str, project: Option<&str>) -> Result<ConfigManager> {
+    let mut config_manager = ConfigManager::new()?;
+    let before = config_manager.clone();
+    let old_client = before.get_current_client().cloned();
+    let old_project = before.get_current_project().cloned();
+    config_manager.set_current_selection(client, project)?;
+
+    let is_same_selection =
+        old_client.as_deref() == Some(client) && old_project.as_deref() == project;
+    if !is_same_selection {
+        let after = config_manager.clone();
+        let (client, project) = (client.to_string(), project.map(str::to_string));
+        std::thread::spawn(move || {
+            hooks::run(
+                &before,
+                Event::Leave,
+                Some(&client),
+                project.as_deref(),
+                true,
+            );
+            hooks::run(
+                &after,
+                Event::Enter,
+                old_client.as_deref(),
+                old_project.as_deref(),
+                true,
+            );
+        });
+    }
+    Ok(config_manager)
+}
+
+/// Interactive client (then nested project) picker.
+fn prompt_selection(
+    config_manager: &ConfigManager,
+    current_client: Option<&str>,
+    current_project: Option<&str>,
+) -> Result<(String, Option<String>)> {
+    let clients = config_manager.get_clients();
+
     let options: Vec<String> = clients
         .iter()
         .map(|client| {
-            if Some(&client.name) == current_client.as_ref() {
+            if Some(client.name.as_str()) == current_client {
                 format!("▶ {} (current)", client.name).green().to_string()
             } else {
                 format!("  {}", client.name)
@@ -31,7 +243,6 @@ pub fn execute() -> Result<()> {
     let client_names: Vec<String> = clients.iter().map(|c| c.name.clone()).collect();
 
     let starting_cursor = current_client
-        .as_ref()
         .and_then(|current| client_names.iter().position(|name| name == current))
         .unwrap_or(0);
 
@@ -58,8 +269,8 @@ pub fn execute() -> Result<()> {
         let client_entry_label = format!("{} (client)", selected_client);
         let mut sub_options: Vec<String> = Vec::with_capacity(nested_project_names.len() + 1);
 
-        let is_current_client_only = current_client.as_deref() == Some(selected_client.as_str())
-            && current_project.is_none();
+        let is_current_client_only =
+            current_client == Some(selected_client.as_str()) && current_project.is_none();
         sub_options.push(if is_current_client_only {
             format!("▶ {} (current)", client_entry_label)
                 .green()
@@ -69,8 +280,8 @@ pub fn execute() -> Result<()> {
         });
 
         for name in &nested_project_names {
-            let is_current = current_client.as_deref() == Some(selected_client.as_str())
-                && current_project.as_deref() == Some(name.as_str());
+            let is_current = current_client == Some(selected_client.as_str())
+                && current_project == Some(name.as_str());
             if is_current {
                 sub_options.push(format!("▶ {} (current)", name).green().to_string());
             } else {
@@ -80,9 +291,8 @@ pub fn execute() -> Result<()> {
 
         let starting_cursor = if is_current_client_only {
             0
-        } else if current_client.as_deref() == Some(selected_client.as_str()) {
+        } else if current_client == Some(selected_client.as_str()) {
             current_project
-                .as_ref()
                 .and_then(|p| nested_project_names.iter().position(|n| n == p))
                 .map(|i| i + 1)
                 .unwrap_or(0)
@@ -109,33 +319,73 @@ pub fn execute() -> Result<()> {
         }
     };
 
-    let is_same_selection = current_client.as_deref() == Some(selected_client.as_str())
-        && current_project.as_deref() == selected_project.as_deref();
+    Ok((selected_client, selected_project))
+}
 
-    if is_same_selection {
-        match &selected_project {
-            Some(p) => println!(
-                "{}",
-                format!("Already on project: {} / {}", selected_client, p).blue()
-            ),
-            None => println!(
-                "{}",
-                format!("Already on client: {}", selected_client).blue()
-            ),
-        }
-    } else {
-        config_manager.set_current_selection(&selected_client, selected_project.as_deref())?;
-        match &selected_project {
-            Some(p) => println!(
-                "{}",
-                format!("Switched to project: {} / {}", selected_client, p).green()
-            ),
-            None => println!(
-                "{}",
-                format!("Switched to client: {}", selected_client).green()
-            ),
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES: [&str; 4] = ["EventsAir", "events-api", "nero", "Acme Corp"];
+
+    #[test]
+    fn match_name_exact_wins_over_prefix() {
+        assert_eq!(
+            match_name(&["web", "website"], "web", "project").unwrap(),
+            "web"
+        );
     }
 
-    Ok(())
+    #[test]
+    fn match_name_case_insensitive() {
+        assert_eq!(match_name(&NAMES, "NERO", "client").unwrap(), "nero");
+    }
+
+    #[test]
+    fn match_name_unique_prefix() {
+        assert_eq!(match_name(&NAMES, "acme", "client").unwrap(), "Acme Corp");
+    }
+
+    #[test]
+    fn match_name_unique_substring() {
+        assert_eq!(match_name(&NAMES, "api", "client").unwrap(), "events-api");
+    }
+
+    #[test]
+    fn match_name_subsequence() {
+        assert_eq!(match_name(&NAMES, "evair", "client").unwrap(), "EventsAir");
+    }
+
+    #[test]
+    fn match_name_ambiguous_lists_candidates() {
+        let err = match_name(&NAMES, "events", "client").unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("ambiguous"), "got: {}", msg);
+        assert!(msg.contains("EventsAir, events-api"), "got: {}", msg);
+    }
+
+    #[test]
+    fn match_name_no_match() {
+        let err = match_name(&NAMES, "zzz", "client").unwrap_err();
+        assert_eq!(err.to_string(), "No client matching 'zzz'");
+    }
+
+    #[test]
+    fn targets_list_clients_then_their_projects_marking_current() {
+        let cm = ConfigManager::from_test_yaml(
+            "currentClient: acme\ncurrentProject: web\nclients:\n- name: acme\n  projects:\n  - name: web\n- name: globex\n",
+        );
+        let targets: Vec<(String, bool)> = targets(&cm)
+            .iter()
+            .map(|t| (t.label(), t.current))
+            .collect();
+        assert_eq!(
+            targets,
+            [
+                ("acme".to_string(), false),
+                ("acme / web".to_string(), true),
+                ("globex".to_string(), false),
+            ]
+        );
+    }
 }
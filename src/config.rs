@@ -1,6 +1,8 @@
+use crate::utils::file;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -47,10 +49,50 @@ pub struct WebserverConfig {
     pub port: Option<u16>,
 }
 
+/// `repos:` — directories scanned for git checkouts to list in the launcher.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReposConfig {
+    pub roots: Vec<String>,
+    /// How many directory levels below each root to search (default 3).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth: Option<usize>,
+    /// Command run in a checkout to edit it (default `code .`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub editor: Option<String>,
+    /// Command run in a checkout to open a terminal there.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terminal: Option<String>,
+}
+
+/// A `providers:` entry: an external command that lists launcher items as
+/// JSON lines (see `providers::script`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProviderConfig {
+    pub name: String,
+    /// Shell command line printing the items.
+    pub command: String,
+    /// Seconds the command may run before it is killed (default 5).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// Seconds its items are reused for the same query and selection
+    /// (default 60; 0 runs it every time).
+    #[serde(rename = "cacheTtl", skip_serializing_if = "Option::is_none")]
+    pub cache_ttl: Option<u64>,
+    /// Collect the items before the launcher opens instead of adding them
+    /// when the command finishes.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub sync: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ProjectCommand {
     pub key: String,
+    /// Other names that find and run this command; history records the key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aliases: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -59,6 +101,16 @@ pub struct ProjectCommand {
     pub browser: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub args: Option<String>,
+    /// Directory a `command:` runs in, relative to the selection's `path`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// Actions run in order instead of a single `url`/`command`; stops at
+    /// the first one that fails.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steps: Option<Vec<Step>>,
+    /// Start all `steps` at once instead of one after another.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub parallel: bool,
     /// Open this command's URL in the reusable borderless webview window
     /// instead of a browser. Mutually exclusive with `command`.
     #[serde(default, skip_serializing_if = "is_false")]
@@ -69,9 +121,96 @@ pub struct ProjectCommand {
     pub pinned: bool,
 }
 
+impl ProjectCommand {
+    /// What the command opens or runs, for list displays: its URL, command
+    /// line or [`steps_detail`].
+    pub fn detail(&self) -> String {
+        if let Some(ref steps) = self.steps {
+            return steps_detail(steps, self.parallel);
+        }
+        self.url
+            .clone()
+            .or_else(|| self.command.clone())
+            .unwrap_or_default()
+    }
+
+    /// Whether `name` is the key or an alias (case-insensitive), as a
+    /// `steps:` entry refers to a command.
+    pub fn is_named(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        std::iter::once(&self.key)
+            .chain(self.aliases.iter().flatten())
+            .any(|n| n.to_lowercase() == name)
+    }
+}
+
+/// `steps` labelled by key, URL or command line and joined with `→` (in
+/// order) or `+` (parallel).
+pub fn steps_detail(steps: &[Step], parallel: bool) -> String {
+    let separator = if parallel { " + " } else { " → " };
+    steps
+        .iter()
+        .map(Step::label)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// One entry of a command's `steps:` list: the key of another command in
+/// scope, or an inline action.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Step {
+    Key(String),
+    Inline(InlineStep),
+}
+
+impl Step {
+    fn label(&self) -> &str {
+        match self {
+            Step::Key(key) => key,
+            Step::Inline(step) => step
+                .url
+                .as_deref()
+                .or(step.command.as_deref())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// An inline step: the action fields of a [`ProjectCommand`]. A URL step
+/// without a `browser` uses its command's, then the selection's.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InlineStep {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub browser: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub args: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub webview: bool,
+}
+
+/// Environment variables for `command:` entries, by name.
+pub type EnvMap = BTreeMap<String, String>;
+
+/// Whether `name` is a portable environment variable name (`[A-Za-z_][A-Za-z0-9_]*`).
+pub fn valid_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
 /// Declares an entry struct carrying the schema shared by clients and projects
-/// (`name`, `path`, `description`, `browser`, `commands`), plus any
-/// struct-specific fields listed in the body.
+/// (`name`, `path`, `description`, `browser`, `env`, `onEnter`, `onLeave`,
+/// `commands`), plus any struct-specific fields listed in the body.
 macro_rules! entry_struct {
     ($name:ident { $($extra:tt)* }) => {
         #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -85,6 +224,14 @@ macro_rules! entry_struct {
             #[serde(skip_serializing_if = "Option::is_none")]
             pub browser: Option<String>,
             #[serde(skip_serializing_if = "Option::is_none")]
+            pub env: Option<EnvMap>,
+            /// Shell commands run when the selection moves onto this entry.
+            #[serde(rename = "onEnter", skip_serializing_if = "Option::is_none")]
+            pub on_enter: Option<Vec<String>>,
+            /// Shell commands run when the selection moves off this entry.
+            #[serde(rename = "onLeave", skip_serializing_if = "Option::is_none")]
+            pub on_leave: Option<Vec<String>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
             pub commands: Option<Vec<ProjectCommand>>,
             $($extra)*
         }
@@ -97,25 +244,62 @@ entry_struct!(Client {
     pub projects: Option<Vec<Project>>,
 });
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+/// `include:` — one path or a list of paths. Later entries of a list take
+/// priority over earlier ones.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Include {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Include {
+    pub fn paths(&self) -> &[String] {
+        match self {
+            Include::One(path) => std::slice::from_ref(path),
+            Include::Many(paths) => paths,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Config {
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub include: Option<String>,
+    pub include: Option<Include>,
     #[serde(rename = "currentClient", skip_serializing_if = "Option::is_none")]
     pub current_client: Option<String>,
     #[serde(rename = "currentProject", skip_serializing_if = "Option::is_none")]
     pub current_project: Option<String>,
+    /// Selection active before the last switch, so `switch -` can toggle back.
+    #[serde(rename = "previousClient", skip_serializing_if = "Option::is_none")]
+    pub previous_client: Option<String>,
+    #[serde(rename = "previousProject", skip_serializing_if = "Option::is_none")]
+    pub previous_project: Option<String>,
     #[serde(rename = "defaultBrowser", skip_serializing_if = "Option::is_none")]
     pub default_browser: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub global: Option<Vec<ProjectCommand>>,
+    /// Environment for every `command:`; clients and projects add to it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<EnvMap>,
     #[serde(default)]
     pub shortcuts: Option<ShortcutsConfig>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repos: Option<ReposConfig>,
+    /// External commands contributing launcher items.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub providers: Option<Vec<ProviderConfig>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub webserver: Option<WebserverConfig>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub monitor: Option<u32>,
+    /// Seconds an `onEnter`/`onLeave` command may run before it is killed.
+    #[serde(rename = "hookTimeout", skip_serializing_if = "Option::is_none")]
+    pub hook_timeout: Option<u64>,
+    /// How many launcher history entries to keep.
+    #[serde(rename = "historySize", skip_serializing_if = "Option::is_none")]
+    pub history_size: Option<usize>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub clients: Vec<Client>,
 }
@@ -124,12 +308,15 @@ pub struct Config {
 /// when the nested-project key is set and valid, the selected project.
 pub type ResolvedSelection<'a> = (&'a String, &'a Client, Option<(&'a String, &'a Project)>);
 
-/// Expand tilde in an include path to the home directory.
-pub fn expand_include_path(path: &str) -> PathBuf {
-    expand_tilde(path)
+/// `~/.project-switch.yml`, the per-machine config file.
+pub fn local_config_path() -> Result<PathBuf> {
+    Ok(dirs::home_dir()
+        .context("Unable to determine home directory")?
+        .join(".project-switch.yml"))
 }
 
-fn expand_tilde(path: &str) -> PathBuf {
+/// Expand a leading `~` to the home directory.
+pub fn expand_tilde(path: &str) -> PathBuf {
     if let Some(rest) = path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\")) {
         if let Some(home) = dirs::home_dir() {
             return home.join(rest);
@@ -138,6 +325,19 @@ fn expand_tilde(path: &str) -> PathBuf {
     PathBuf::from(path)
 }
 
+/// `path` for writing into the config: below the home directory it is
+/// written `~/...`, so the entry works on machines with another home.
+pub fn contract_tilde(path: &Path) -> String {
+    if let Some(rel) =
+        dirs::home_dir().and_then(|home| path.strip_prefix(home).ok().map(Path::to_path_buf))
+    {
+        if !rel.as_os_str().is_empty() {
+            return format!("~/{}", rel.to_string_lossy().replace('\\', "/"));
+        }
+    }
+    path.display().to_string()
+}
+
 /// Rewrite an old-schema YAML document (`projects:` / `currentProject:`) to the new
 /// schema (`clients:` / `currentClient:`). Returns the migrated string and whether
 /// a migration was actually performed.
@@ -145,7 +345,7 @@ fn expand_tilde(path: &str) -> PathBuf {
 /// The trigger is top-level `projects:`. In the new schema `currentProject` is a
 /// valid key (the nested project selection), so it must not be touched once the
 /// config has already been migrated to `clients:`.
-fn migrate_schema(contents: &str) -> Result<(String, bool)> {
+pub(crate) fn migrate_schema(contents: &str) -> Result<(String, bool)> {
     let doc: Value =
         serde_yaml::from_str(contents).context("Failed to parse YAML for migration")?;
 
@@ -180,7 +380,7 @@ fn read_and_migrate(path: &Path) -> Result<String> {
         .with_context(|| format!("Failed to read config file: {}", path.display()))?;
     let (migrated_contents, did_migrate) = migrate_schema(&contents)?;
     if did_migrate {
-        fs::write(path, &migrated_contents)
+        file::write_atomic(path, &migrated_contents)
             .with_context(|| format!("Failed to write migrated config: {}", path.display()))?;
         eprintln!(
             "Migrated config schema (projects -> clients): {}",
@@ -190,19 +390,140 @@ fn read_and_migrate(path: &Path) -> Result<String> {
     Ok(migrated_contents)
 }
 
-fn merge_configs(base: Config, overlay: Config) -> Config {
+/// Read a config file as both a typed [`Config`] and raw YAML (for
+/// order-preserving rewrites).
+fn read_layer_file(path: &Path) -> Result<(Config, Value)> {
+    let contents = read_and_migrate(path)?;
+    let config: Config = serde_yaml::from_str(&contents)
+        .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
+    let raw: Value = serde_yaml::from_str(&contents).with_context(|| {
+        format!(
+            "Failed to parse config file as raw YAML: {}",
+            path.display()
+        )
+    })?;
+    Ok((config, raw))
+}
+
+/// A config file that contributes to the merged config.
+#[derive(Debug, Clone)]
+pub struct ConfigSource {
+    pub path: PathBuf,
+    pub config: Config,
+}
+
+/// Resolve an `include` entry of the file at `including`: `~` expands to the
+/// home directory and relative paths are relative to the including file.
+pub fn resolve_include(entry: &str, including: &Path) -> PathBuf {
+    let path = expand_tilde(entry);
+    match including.parent() {
+        Some(dir) if path.is_relative() => dir.join(path),
+        _ => path,
+    }
+}
+
+/// `path` with symlinks and `..` resolved, so the same file reached two ways
+/// compares equal.
+pub(crate) fn canonical_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// `a -> b -> a` when including `next` from the end of `chain` (canonical
+/// paths, outermost first) would loop back into the chain.
+pub(crate) fn include_cycle(chain: &[PathBuf], next: &Path) -> Option<String> {
+    let start = chain.iter().position(|p| p == next)?;
+    Some(
+        chain[start..]
+            .iter()
+            .map(|p| p.as_path())
+            .chain(std::iter::once(next))
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>()
+            .join(" -> "),
+    )
+}
+
+/// Every file `config` (read from `path`) includes, directly or through
+/// nested includes, lowest priority first: each file comes after the files it
+/// includes, and later `include` entries after earlier ones. A file reached
+/// twice is read once, at its first position; a file that includes itself,
+/// directly or not, is an error.
+fn read_includes(path: &Path, config: &Config) -> Result<Vec<ConfigSource>> {
+    let mut sources = Vec::new();
+    let mut chain = vec![canonical_path(path)];
+    collect_includes(path, config, &mut chain, &mut sources)?;
+    Ok(sources)
+}
+
+fn collect_includes(
+    path: &Path,
+    config: &Config,
+    chain: &mut Vec<PathBuf>,
+    sources: &mut Vec<ConfigSource>,
+) -> Result<()> {
+    for entry in config.include.iter().flat_map(Include::paths) {
+        let resolved = resolve_include(entry, path);
+        if !resolved.exists() {
+            eprintln!("Warning: included config not found: {}", resolved.display());
+            continue;
+        }
+        let key = canonical_path(&resolved);
+        if let Some(cycle) = include_cycle(chain, &key) {
+            anyhow::bail!("Include cycle: {}", cycle);
+        }
+        if sources.iter().any(|s| canonical_path(&s.path) == key) {
+            continue;
+        }
+        let contents = read_and_migrate(&resolved)?;
+        let included: Config = serde_yaml::from_str(&contents)
+            .with_context(|| format!("Failed to parse included config: {}", resolved.display()))?;
+        chain.push(key);
+        collect_includes(&resolved, &included, chain, sources)?;
+        chain.pop();
+        sources.push(ConfigSource {
+            path: resolved,
+            config: included,
+        });
+    }
+    Ok(())
+}
+
+/// Fold configs together, lowest priority first.
+pub(crate) fn merge_all(configs: impl IntoIterator<Item = Config>) -> Config {
+    configs
+        .into_iter()
+        .reduce(merge_configs)
+        .unwrap_or_default()
+}
+
+pub(crate) fn merge_configs(base: Config, overlay: Config) -> Config {
     Config {
         include: overlay.include,
         current_client: overlay.current_client.or(base.current_client),
         current_project: overlay.current_project.or(base.current_project),
+        previous_client: overlay.previous_client.or(base.previous_client),
+        previous_project: overlay.previous_project.or(base.previous_project),
         default_browser: overlay.default_browser.or(base.default_browser),
         global: merge_command_lists(base.global, overlay.global),
+        env: merge_env(base.env, overlay.env),
         // shortcuts is machine-specific: local replaces entirely
         shortcuts: if overlay.shortcuts.is_some() {
             overlay.shortcuts
         } else {
             base.shortcuts
         },
+        // repos are machine-specific paths: local replaces entirely
+        repos: if overlay.repos.is_some() {
+            overlay.repos
+        } else {
+            base.repos
+        },
+        // providers run local executables: local replaces entirely
+        providers: if overlay.providers.is_some() {
+            overlay.providers
+        } else {
+            base.providers
+        },
         // webserver is machine-specific: local replaces entirely
         webserver: if overlay.webserver.is_some() {
             overlay.webserver
@@ -210,6 +531,8 @@ fn merge_configs(base: Config, overlay: Config) -> Config {
             base.webserver
         },
         monitor: overlay.monitor.or(base.monitor),
+        hook_timeout: overlay.hook_timeout.or(base.hook_timeout),
+        history_size: overlay.history_size.or(base.history_size),
         clients: merge_client_lists(base.clients, overlay.clients),
     }
 }
@@ -264,6 +587,17 @@ fn merge_optional_lists<T: Clone>(
     }
 }
 
+/// Variables set in both maps take the overlay's value.
+fn merge_env(base: Option<EnvMap>, overlay: Option<EnvMap>) -> Option<EnvMap> {
+    match (base, overlay) {
+        (Some(mut b), Some(o)) => {
+            b.extend(o);
+            Some(b)
+        }
+        (b, o) => o.or(b),
+    }
+}
+
 fn merge_client_lists(base: Vec<Client>, overlay: Vec<Client>) -> Vec<Client> {
     merge_keyed_lists(base, overlay, |c| c.name.as_str(), merge_clients, true)
 }
@@ -277,6 +611,9 @@ macro_rules! merge_shared_fields {
             $overlay.path.or($base.path),
             $overlay.description.or($base.description),
             $overlay.browser.or($base.browser),
+            merge_env($base.env, $overlay.env),
+            $overlay.on_enter.or($base.on_enter),
+            $overlay.on_leave.or($base.on_leave),
             merge_command_lists($base.commands, $overlay.commands),
         )
     };
@@ -284,12 +621,16 @@ macro_rules! merge_shared_fields {
 
 fn merge_clients(mut base: Client, mut overlay: Client) -> Client {
     let projects = merge_project_lists(base.projects.take(), overlay.projects.take());
-    let (name, path, description, browser, commands) = merge_shared_fields!(base, overlay);
+    let (name, path, description, browser, env, on_enter, on_leave, commands) =
+        merge_shared_fields!(base, overlay);
     Client {
         name,
         path,
         description,
         browser,
+        env,
+        on_enter,
+        on_leave,
         commands,
         projects,
     }
@@ -303,12 +644,16 @@ fn merge_project_lists(
 }
 
 fn merge_projects(base: Project, overlay: Project) -> Project {
-    let (name, path, description, browser, commands) = merge_shared_fields!(base, overlay);
+    let (name, path, description, browser, env, on_enter, on_leave, commands) =
+        merge_shared_fields!(base, overlay);
     Project {
         name,
         path,
         description,
         browser,
+        env,
+        on_enter,
+        on_leave,
         commands,
     }
 }
@@ -320,46 +665,94 @@ fn merge_command_lists(
     merge_optional_lists(base, overlay, |c| c.key.as_str(), merge_commands, false)
 }
 
-fn merge_commands(base: ProjectCommand, overlay: ProjectCommand) -> ProjectCommand {
+/// An override that gives a command a `url`/`command` replaces the base's
+/// `steps`, and one that gives it `steps` replaces the base's `url` and
+/// `command`: a command is one or the other. Either way it also replaces
+/// the base's `parallel` and `webview`, which only say how that body runs.
+fn merge_commands(mut base: ProjectCommand, overlay: ProjectCommand) -> ProjectCommand {
+    if overlay.url.is_some() || overlay.command.is_some() || overlay.steps.is_some() {
+        base.parallel = false;
+        base.webview = false;
+    }
+    if overlay.url.is_some() || overlay.command.is_some() {
+        base.steps = None;
+    } else if overlay.steps.is_some() {
+        base.url = None;
+        base.command = None;
+    }
     ProjectCommand {
         key: overlay.key,
+        aliases: overlay.aliases.or(base.aliases),
         url: overlay.url.or(base.url),
         command: overlay.command.or(base.command),
         browser: overlay.browser.or(base.browser),
         args: overlay.args.or(base.args),
+        cwd: overlay.cwd.or(base.cwd),
+        steps: overlay.steps.or(base.steps),
+        parallel: overlay.parallel || base.parallel,
         webview: overlay.webview || base.webview,
         pinned: overlay.pinned || base.pinned,
     }
 }
 
+/// Why a command's fields conflict, if they do — phrased to follow
+/// "Command 'key' in <context> ".
+pub(crate) fn command_conflict(
+    has_url: bool,
+    has_command: bool,
+    has_browser: bool,
+    webview: bool,
+    has_steps: bool,
+) -> Option<&'static str> {
+    if has_steps && (has_url || has_command) {
+        Some("has 'steps' and a 'url' or 'command' — a command with steps only runs its steps")
+    } else if has_url && has_command {
+        Some("has both 'url' and 'command' — use one or the other")
+    } else if has_command && has_browser {
+        Some("has both 'command' and 'browser' — 'command' runs directly, not in a browser")
+    } else if webview && has_command {
+        Some("has both 'webview: true' and 'command' — webview opens a URL, not a command")
+    } else {
+        None
+    }
+}
+
 fn validate_command_list(commands: &[ProjectCommand], context: &str) -> Result<()> {
     for cmd in commands {
-        if cmd.url.is_some() && cmd.command.is_some() {
-            anyhow::bail!(
-                "Command '{}' in {} has both 'url' and 'command' — use one or the other",
-                cmd.key,
-                context
-            );
+        if let Some(problem) = command_conflict(
+            cmd.url.is_some(),
+            cmd.command.is_some(),
+            cmd.browser.is_some(),
+            cmd.webview,
+            cmd.steps.is_some(),
+        ) {
+            anyhow::bail!("Command '{}' in {} {}", cmd.key, context, problem);
         }
-        if cmd.command.is_some() && cmd.browser.is_some() {
-            anyhow::bail!(
-                "Command '{}' in {} has both 'command' and 'browser' — 'command' runs directly, not in a browser",
-                cmd.key,
-                context
-            );
-        }
-        if cmd.webview && cmd.command.is_some() {
-            anyhow::bail!(
-                "Command '{}' in {} has both 'webview: true' and 'command' — webview opens a URL, not a command",
-                cmd.key,
-                context
-            );
+        for (i, step) in cmd.steps.iter().flatten().enumerate() {
+            let Step::Inline(step) = step else {
+                continue;
+            };
+            if let Some(problem) = command_conflict(
+                step.url.is_some(),
+                step.command.is_some(),
+                step.browser.is_some(),
+                step.webview,
+                false,
+            ) {
+                anyhow::bail!(
+                    "Step {} of command '{}' in {} {}",
+                    i + 1,
+                    cmd.key,
+                    context,
+                    problem
+                );
+            }
         }
     }
     Ok(())
 }
 
-fn validate_commands(config: &Config) -> Result<()> {
+pub(crate) fn validate_commands(config: &Config) -> Result<()> {
     if let Some(ref global) = config.global {
         validate_command_list(global, "global commands")?;
     }
@@ -389,30 +782,102 @@ fn validate_commands(config: &Config) -> Result<()> {
     Ok(())
 }
 
+/// Set a top-level string key in the raw YAML mapping, or remove it when unset.
+fn set_or_remove(map: &mut serde_yaml::Mapping, key: &str, value: Option<&String>) {
+    let key = Value::String(key.to_string());
+    match value {
+        Some(v) => {
+            map.insert(key, Value::String(v.clone()));
+        }
+        None => {
+            map.remove(&key);
+        }
+    }
+}
+
+/// The unmerged, editable entries of a single config file (the local file or
+/// its `include` target).
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLayer {
+    pub clients: Vec<Client>,
+    pub global: Option<Vec<ProjectCommand>>,
+}
+
+impl ConfigLayer {
+    fn from_config(config: &Config) -> Self {
+        Self {
+            clients: config.clients.clone(),
+            global: config.global.clone(),
+        }
+    }
+
+    pub fn client_mut(&mut self, name: &str) -> Option<&mut Client> {
+        self.clients.iter_mut().find(|c| c.name == name)
+    }
+
+    fn validate(&self) -> Result<()> {
+        validate_commands(&Config {
+            clients: self.clients.clone(),
+            global: self.global.clone(),
+            ..Config::default()
+        })
+    }
+
+    /// Write this layer's `clients` and `global` into a raw YAML mapping,
+    /// leaving every other key (and the key order) untouched.
+    fn write_into(&self, map: &mut serde_yaml::Mapping) -> Result<()> {
+        let clients_value =
+            serde_yaml::to_value(&self.clients).context("Failed to serialize clients")?;
+        map.insert(Value::String("clients".to_string()), clients_value);
+
+        let global_key = Value::String("global".to_string());
+        match self.global {
+            Some(ref global) => {
+                let global_value =
+                    serde_yaml::to_value(global).context("Failed to serialize global commands")?;
+                map.insert(global_key, global_value);
+            }
+            None => {
+                map.remove(&global_key);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Which file a config edit is written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditTarget<'a> {
+    /// `~/.project-switch.yml`.
+    Local,
+    /// An included file: the given one (which must be part of the include
+    /// chain), or the local config's only direct `include`.
+    Shared(Option<&'a Path>),
+}
+
+#[derive(Clone)]
 pub struct ConfigManager {
     config: Config,
     config_path: PathBuf,
     raw_yaml: Option<Value>,
-    local_clients: Vec<Client>,
+    local: ConfigLayer,
 }
 
 impl ConfigManager {
     pub fn new() -> Result<Self> {
-        let config_path = dirs::home_dir()
-            .context("Unable to determine home directory")?
-            .join(".project-switch.yml");
+        let config_path = local_config_path()?;
 
-        let (config, raw_yaml, local_clients) = Self::load_config(&config_path)?;
+        let (config, raw_yaml, local) = Self::load_config(&config_path)?;
 
         Ok(Self {
             config,
             config_path,
             raw_yaml,
-            local_clients,
+            local,
         })
     }
 
-    fn load_config(path: &PathBuf) -> Result<(Config, Option<Value>, Vec<Client>)> {
+    fn load_config(path: &PathBuf) -> Result<(Config, Option<Value>, ConfigLayer)> {
         if path.exists() {
             let contents = read_and_migrate(path)?;
 
@@ -424,27 +889,17 @@ impl ConfigManager {
 
             validate_commands(&local_config)?;
 
-            let local_clients = local_config.clients.clone();
-
-            // Handle include
-            let config = if let Some(ref include_path) = local_config.include {
-                let resolved = expand_tilde(include_path);
-                if resolved.exists() {
-                    let base_contents = read_and_migrate(&resolved)?;
-                    let base_config: Config =
-                        serde_yaml::from_str(&base_contents).with_context(|| {
-                            format!("Failed to parse included config: {}", resolved.display())
-                        })?;
-                    merge_configs(base_config, local_config)
-                } else {
-                    eprintln!("Warning: included config not found: {}", resolved.display());
-                    local_config
-                }
-            } else {
-                local_config
-            };
+            let local = ConfigLayer::from_config(&local_config);
+
+            let includes = read_includes(path, &local_config)?;
+            let config = merge_all(
+                includes
+                    .into_iter()
+                    .map(|s| s.config)
+                    .chain(std::iter::once(local_config)),
+            );
 
-            Ok((config, Some(raw_yaml), local_clients))
+            Ok((config, Some(raw_yaml), local))
         } else {
             let default_contents = "clients: []\n";
             fs::write(path, default_contents).with_context(|| {
@@ -454,7 +909,7 @@ impl ConfigManager {
                 serde_yaml::from_str(default_contents).context("Failed to parse default config")?;
             let raw_yaml: Value = serde_yaml::from_str(default_contents)
                 .context("Failed to parse default config as raw YAML")?;
-            Ok((config, Some(raw_yaml), Vec::new()))
+            Ok((config, Some(raw_yaml), ConfigLayer::default()))
         }
     }
 
@@ -462,27 +917,18 @@ impl ConfigManager {
         let yaml_value = if let Some(ref mut raw) = self.raw_yaml {
             // Update the raw YAML with current config values while preserving order
             if let Value::Mapping(ref mut map) = raw {
-                // Update currentClient
-                let current_client_key = Value::String("currentClient".to_string());
-                if let Some(ref current) = self.config.current_client {
-                    map.insert(current_client_key, Value::String(current.clone()));
-                } else {
-                    map.remove(&current_client_key);
-                }
-
-                // Update currentProject
-                let current_project_key = Value::String("currentProject".to_string());
-                if let Some(ref current) = self.config.current_project {
-                    map.insert(current_project_key, Value::String(current.clone()));
-                } else {
-                    map.remove(&current_project_key);
-                }
+                // Update the selection keys
+                set_or_remove(map, "currentClient", self.config.current_client.as_ref());
+                set_or_remove(map, "currentProject", self.config.current_project.as_ref());
+                set_or_remove(map, "previousClient", self.config.previous_client.as_ref());
+                set_or_remove(
+                    map,
+                    "previousProject",
+                    self.config.previous_project.as_ref(),
+                );
 
-                // Update clients array (only local clients, not merged)
-                let clients_key = Value::String("clients".to_string());
-                let clients_value = serde_yaml::to_value(&self.local_clients)
-                    .context("Failed to serialize clients")?;
-                map.insert(clients_key, clients_value);
+                // Update clients and global commands (only local entries, not merged)
+                self.local.write_into(map)?;
             }
             raw.clone()
         } else {
@@ -491,19 +937,26 @@ impl ConfigManager {
                 include: self.config.include.clone(),
                 current_client: self.config.current_client.clone(),
                 current_project: self.config.current_project.clone(),
+                previous_client: self.config.previous_client.clone(),
+                previous_project: self.config.previous_project.clone(),
                 default_browser: self.config.default_browser.clone(),
-                global: self.config.global.clone(),
+                global: self.local.global.clone(),
+                env: self.config.env.clone(),
                 shortcuts: self.config.shortcuts.clone(),
+                repos: self.config.repos.clone(),
+                providers: self.config.providers.clone(),
                 webserver: self.config.webserver.clone(),
                 monitor: self.config.monitor,
-                clients: self.local_clients.clone(),
+                hook_timeout: self.config.hook_timeout,
+                history_size: self.config.history_size,
+                clients: self.local.clients.clone(),
             };
             serde_yaml::to_value(&local_config).context("Failed to serialize config")?
         };
 
         let yaml = serde_yaml::to_string(&yaml_value).context("Failed to serialize config")?;
 
-        fs::write(&self.config_path, yaml).with_context(|| {
+        file::write_atomic(&self.config_path, &yaml).with_context(|| {
             format!(
                 "Failed to write config file: {}",
                 self.config_path.display()
@@ -513,6 +966,176 @@ impl ConfigManager {
         Ok(())
     }
 
+    /// Path of the file an edit to `target` is written to.
+    pub fn target_path(&self, target: EditTarget) -> Result<PathBuf> {
+        match target {
+            EditTarget::Local => Ok(self.config_path.clone()),
+            EditTarget::Shared(Some(file)) => {
+                let wanted = canonical_path(&expand_tilde(&file.to_string_lossy()));
+                self.included_paths()?
+                    .into_iter()
+                    .find(|p| canonical_path(p) == wanted)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("{} is not an included config file", file.display())
+                    })
+            }
+            EditTarget::Shared(None) => {
+                let direct: Vec<PathBuf> = self
+                    .config
+                    .include
+                    .iter()
+                    .flat_map(Include::paths)
+                    .map(|entry| resolve_include(entry, &self.config_path))
+                    .collect();
+                match direct.as_slice() {
+                    [] => anyhow::bail!(
+                        "No 'include' file is configured, so there is no shared config"
+                    ),
+                    [only] => Ok(only.clone()),
+                    several => anyhow::bail!(
+                        "'include' lists several files ({}) — pick one with --shared=<file>",
+                        several
+                            .iter()
+                            .map(|p| p.display().to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                }
+            }
+        }
+    }
+
+    /// Apply `edit` to the unmerged entries of `target`, validate the result,
+    /// write it back (preserving unrelated keys and their order), and reload
+    /// the merged config.
+    pub fn edit_layer(
+        &mut self,
+        target: EditTarget,
+        edit: impl FnOnce(&mut ConfigLayer) -> Result<()>,
+    ) -> Result<()> {
+        match target {
+            EditTarget::Local => self.update(|cm| {
+                let mut layer = cm.local.clone();
+                edit(&mut layer)?;
+                layer.validate()?;
+                cm.local = layer;
+                Ok(true)
+            })?,
+            EditTarget::Shared(_) => {
+                let path = self.target_path(target)?;
+                file::with_lock(&path, || {
+                    let (config, mut raw) = read_layer_file(&path)?;
+                    let mut layer = ConfigLayer::from_config(&config);
+                    edit(&mut layer)?;
+                    layer
+                        .validate()
+                        .with_context(|| format!("Invalid edit to {}", path.display()))?;
+                    if let Value::Mapping(ref mut map) = raw {
+                        layer.write_into(map)?;
+                    }
+                    let yaml = serde_yaml::to_string(&raw).context("Failed to serialize config")?;
+                    file::write_atomic(&path, &yaml)
+                        .with_context(|| format!("Failed to write config file: {}", path.display()))
+                })?;
+            }
+        }
+        self.reload()
+    }
+
+    /// The files behind the merged config, lowest priority first (the order
+    /// `merge_all` applies them); the local file is last.
+    pub fn sources(&self) -> Result<Vec<ConfigSource>> {
+        let (local, _) = read_layer_file(&self.config_path)?;
+        let mut sources = read_includes(&self.config_path, &local)?;
+        sources.push(ConfigSource {
+            path: self.config_path.clone(),
+            config: local,
+        });
+        Ok(sources)
+    }
+
+    /// Point the current/previous selection at a renamed client.
+    pub fn rename_selected_client(&mut self, old_name: &str, new_name: &str) -> Result<()> {
+        self.update(|cm| {
+            let mut changed = false;
+            for selected in [
+                &mut cm.config.current_client,
+                &mut cm.config.previous_client,
+            ] {
+                if selected.as_deref() == Some(old_name) {
+                    *selected = Some(new_name.to_string());
+                    changed = true;
+                }
+            }
+            Ok(changed)
+        })
+    }
+
+    /// Clear the current/previous selection of a removed client (`project`
+    /// `None`) or project. Nothing changes while another config file still
+    /// defines it.
+    pub fn deselect_removed(&mut self, client: &str, project: Option<&str>) -> Result<()> {
+        self.update(|cm| {
+            let removed = match project {
+                None => !cm.client_exists(client),
+                Some(project) => !cm.project_exists(client, project),
+            };
+            if !removed {
+                return Ok(false);
+            }
+            let mut changed = false;
+            for (selected_client, selected_project) in [
+                (
+                    &mut cm.config.current_client,
+                    &mut cm.config.current_project,
+                ),
+                (
+                    &mut cm.config.previous_client,
+                    &mut cm.config.previous_project,
+                ),
+            ] {
+                if selected_client.as_deref() != Some(client) {
+                    continue;
+                }
+                match project {
+                    None => {
+                        *selected_client = None;
+                        *selected_project = None;
+                        changed = true;
+                    }
+                    Some(project) if selected_project.as_deref() == Some(project) => {
+                        *selected_project = None;
+                        changed = true;
+                    }
+                    Some(_) => {}
+                }
+            }
+            Ok(changed)
+        })
+    }
+
+    /// Reload the local file, apply `change` and save it if `change`
+    /// reports a change, all under the config lock — so an edit made by
+    /// another process since this manager loaded isn't overwritten.
+    fn update(&mut self, change: impl FnOnce(&mut Self) -> Result<bool>) -> Result<()> {
+        let path = self.config_path.clone();
+        file::with_lock(&path, || {
+            self.reload()?;
+            if change(self)? {
+                self.save_config()?;
+            }
+            Ok(())
+        })
+    }
+
+    fn reload(&mut self) -> Result<()> {
+        let (config, raw_yaml, local) = Self::load_config(&self.config_path)?;
+        self.config = config;
+        self.raw_yaml = raw_yaml;
+        self.local = local;
+        Ok(())
+    }
+
     pub fn get_clients(&self) -> &Vec<Client> {
         &self.config.clients
     }
@@ -525,31 +1148,46 @@ impl ConfigManager {
         self.config.current_project.as_ref()
     }
 
+    /// The selection that was active before the last switch, if any.
+    pub fn get_previous_selection(&self) -> Option<(&String, Option<&String>)> {
+        let client = self.config.previous_client.as_ref()?;
+        Some((client, self.config.previous_project.as_ref()))
+    }
+
     /// Persist the active client and (optionally) nested project selection.
-    /// Passing `None` for `project_name` clears the current project.
+    /// Passing `None` for `project_name` clears the current project. The
+    /// outgoing selection is remembered as the previous one when it changes.
     pub fn set_current_selection(
         &mut self,
         client_name: &str,
         project_name: Option<&str>,
     ) -> Result<()> {
-        if !self.client_exists(client_name) {
-            anyhow::bail!("Client '{}' not found", client_name);
-        }
+        self.update(|cm| {
+            if !cm.client_exists(client_name) {
+                anyhow::bail!("Client '{}' not found", client_name);
+            }
 
-        if let Some(project) = project_name {
-            if !self.project_exists(client_name, project) {
-                anyhow::bail!(
-                    "Project '{}' not found in client '{}'",
-                    project,
-                    client_name
-                );
+            if let Some(project) = project_name {
+                if !cm.project_exists(client_name, project) {
+                    anyhow::bail!(
+                        "Project '{}' not found in client '{}'",
+                        project,
+                        client_name
+                    );
+                }
             }
-        }
 
-        self.config.current_client = Some(client_name.to_string());
-        self.config.current_project = project_name.map(|s| s.to_string());
-        self.save_config()?;
-        Ok(())
+            let is_same_selection = cm.config.current_client.as_deref() == Some(client_name)
+                && cm.config.current_project.as_deref() == project_name;
+            if !is_same_selection && cm.config.current_client.is_some() {
+                cm.config.previous_client = cm.config.current_client.take();
+                cm.config.previous_project = cm.config.current_project.take();
+            }
+
+            cm.config.current_client = Some(client_name.to_string());
+            cm.config.current_project = project_name.map(|s| s.to_string());
+            Ok(true)
+        })
     }
 
     pub fn client_exists(&self, name: &str) -> bool {
@@ -621,6 +1259,62 @@ impl ConfigManager {
         self.config.default_browser.as_deref().unwrap_or("firefox")
     }
 
+    /// Browser set on the active selection itself. Precedence: project > client.
+    pub fn get_scope_browser(&self) -> Option<&str> {
+        let (_, client, project) = self.resolve_current()?;
+        project
+            .and_then(|(_, p)| p.browser.as_deref())
+            .or(client.browser.as_deref())
+    }
+
+    /// Directory of the active selection. Precedence: project > client.
+    pub fn get_scope_path(&self) -> Option<&str> {
+        let (_, client, project) = self.resolve_current()?;
+        project
+            .and_then(|(_, p)| p.path.as_deref())
+            .or(client.path.as_deref())
+    }
+
+    /// Environment for `command:` entries: global, then the client's, then the
+    /// project's variables, later ones overriding earlier ones.
+    pub fn get_effective_env(&self) -> EnvMap {
+        let mut env = self.config.env.clone().unwrap_or_default();
+        if let Some((_, client, project)) = self.resolve_current() {
+            env.extend(client.env.clone().unwrap_or_default());
+            if let Some((_, project)) = project {
+                env.extend(project.env.clone().unwrap_or_default());
+            }
+        }
+        env
+    }
+
+    /// Browser used for the active selection when a command doesn't name one.
+    /// Precedence: project > client > global default.
+    pub fn get_effective_browser(&self) -> &str {
+        self.get_scope_browser()
+            .unwrap_or_else(|| self.get_default_browser())
+    }
+
+    /// How long one hook command may run (`hookTimeout`, default 30 seconds).
+    pub fn get_hook_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.config.hook_timeout.unwrap_or(30))
+    }
+
+    /// How many history entries to keep (`historySize`, default 10).
+    pub fn get_history_size(&self) -> usize {
+        self.config
+            .history_size
+            .unwrap_or(crate::history::DEFAULT_MAX_ENTRIES)
+    }
+
+    pub fn get_repos_config(&self) -> Option<&ReposConfig> {
+        self.config.repos.as_ref()
+    }
+
+    pub fn get_providers(&self) -> &[ProviderConfig] {
+        self.config.providers.as_deref().unwrap_or_default()
+    }
+
     pub fn get_monitor(&self) -> Option<u32> {
         self.config.monitor
     }
@@ -633,16 +1327,19 @@ impl ConfigManager {
         self.config.shortcuts.clone().unwrap_or_default()
     }
 
-    pub fn get_include_path(&self) -> Option<&str> {
-        self.config.include.as_deref()
+    /// Every included file, directly or through nested includes.
+    pub fn included_paths(&self) -> Result<Vec<PathBuf>> {
+        let mut sources = self.sources()?;
+        sources.pop();
+        Ok(sources.into_iter().map(|s| s.path).collect())
     }
 }
 
 #[cfg(test)]
-mod tests {
-    use super::*;
-
-    fn make_manager(contents: &str) -> ConfigManager {
+impl ConfigManager {
+    /// Build a manager from YAML written to a unique temp file, so tests
+    /// anywhere in the crate can exercise loading and saving.
+    pub(crate) fn from_test_yaml(contents: &str) -> Self {
         use std::sync::atomic::{AtomicU64, Ordering};
         static N: AtomicU64 = AtomicU64::new(0);
         let tmp = std::env::temp_dir().join(format!(
@@ -651,14 +1348,23 @@ mod tests {
             N.fetch_add(1, Ordering::Relaxed)
         ));
         fs::write(&tmp, contents).unwrap();
-        let (config, raw_yaml, local_clients) = ConfigManager::load_config(&tmp).unwrap();
+        let (config, raw_yaml, local) = ConfigManager::load_config(&tmp).unwrap();
         ConfigManager {
             config,
             config_path: tmp,
             raw_yaml,
-            local_clients,
+            local,
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_manager(contents: &str) -> ConfigManager {
+        ConfigManager::from_test_yaml(contents)
+    }
 
     #[test]
     fn old_schema_migrates_then_switch_persists_both_keys() {
@@ -745,10 +1451,14 @@ clients:
     fn pinned_defaults_false_and_omitted_when_false() {
         let cmd = ProjectCommand {
             key: "git".to_string(),
+            aliases: None,
             url: Some("https://x".to_string()),
             command: None,
             browser: None,
             args: None,
+            cwd: None,
+            steps: None,
+            parallel: false,
             webview: false,
             pinned: false,
         };
@@ -769,6 +1479,54 @@ clients:
         assert!(yaml.contains("pinned: true"), "got: {}", yaml);
     }
 
+    #[test]
+    fn switching_remembers_previous_selection() {
+        let initial = "\
+currentClient: acme
+clients:
+- name: acme
+- name: globex
+  projects:
+  - name: web
+";
+        let mut cm = make_manager(initial);
+        assert!(cm.get_previous_selection().is_none());
+
+        cm.set_current_selection("globex", Some("web")).unwrap();
+        let (client, project) = cm.get_previous_selection().unwrap();
+        assert_eq!(client, "acme");
+        assert_eq!(project, None);
+
+        // Switching back swaps current and previous, so `switch -` toggles.
+        cm.set_current_selection("acme", None).unwrap();
+        let (client, project) = cm.get_previous_selection().unwrap();
+        assert_eq!(client, "globex");
+        assert_eq!(project.map(String::as_str), Some("web"));
+
+        let written = fs::read_to_string(&cm.config_path).unwrap();
+        assert!(
+            written.contains("previousClient: globex"),
+            "got: {}",
+            written
+        );
+        assert!(written.contains("previousProject: web"), "got: {}", written);
+    }
+
+    #[test]
+    fn reselecting_current_keeps_previous_selection() {
+        let initial = "\
+currentClient: acme
+previousClient: globex
+clients:
+- name: acme
+- name: globex
+";
+        let mut cm = make_manager(initial);
+        cm.set_current_selection("acme", None).unwrap();
+        let (client, _) = cm.get_previous_selection().unwrap();
+        assert_eq!(client, "globex");
+    }
+
     #[test]
     fn switch_from_migrated_stale_current_to_client_with_project() {
         let initial = "\
@@ -798,4 +1556,352 @@ clients:
             written
         );
     }
+
+    #[test]
+    fn local_edit_keeps_included_clients_out_of_local_file() {
+        let shared = std::env::temp_dir().join(format!("ps-shared-{}.yml", std::process::id()));
+        fs::write(&shared, "clients:\n- name: team\n").unwrap();
+        let initial = format!(
+            "include: {}\ndefaultBrowser: firefox\nclients:\n- name: acme\n",
+            shared.display()
+        );
+        let mut cm = make_manager(&initial);
+        cm.edit_layer(EditTarget::Local, |layer| {
+            layer.client_mut("acme").unwrap().path = Some("/src/acme".to_string());
+            Ok(())
+        })
+        .unwrap();
+
+        let written = fs::read_to_string(&cm.config_path).unwrap();
+        assert!(written.contains("path: /src/acme"), "got: {}", written);
+        assert!(
+            written.contains("defaultBrowser: firefox"),
+            "got: {}",
+            written
+        );
+        assert!(!written.contains("team"), "got: {}", written);
+        assert!(cm.client_exists("team"));
+        fs::remove_file(&shared).ok();
+    }
+
+    #[test]
+    fn shared_edit_writes_included_file() {
+        let shared = std::env::temp_dir().join(format!("ps-shared-w-{}.yml", std::process::id()));
+        fs::write(&shared, "defaultBrowser: chrome\nclients:\n- name: team\n").unwrap();
+        let mut cm = make_manager(&format!("include: {}\nclients: []\n", shared.display()));
+        cm.edit_layer(EditTarget::Shared(None), |layer| {
+            layer.clients.push(Client {
+                name: "ops".to_string(),
+                path: None,
+                description: None,
+                browser: None,
+                env: None,
+                on_enter: None,
+                on_leave: None,
+                commands: None,
+                projects: None,
+            });
+            Ok(())
+        })
+        .unwrap();
+
+        let written = fs::read_to_string(&shared).unwrap();
+        assert!(written.contains("name: ops"), "got: {}", written);
+        assert!(
+            written.contains("defaultBrowser: chrome"),
+            "got: {}",
+            written
+        );
+        assert!(!fs::read_to_string(&cm.config_path).unwrap().contains("ops"));
+        assert!(cm.client_exists("ops"));
+        fs::remove_file(&shared).ok();
+    }
+
+    #[test]
+    fn invalid_edit_is_rejected_and_not_written() {
+        let mut cm = make_manager("clients:\n- name: acme\n");
+        let err = cm
+            .edit_layer(EditTarget::Local, |layer| {
+                let client = layer.client_mut("acme").unwrap();
+                client.commands = Some(vec![ProjectCommand {
+                    key: "bad".to_string(),
+                    aliases: None,
+                    url: Some("https://x".to_string()),
+                    command: Some("make".to_string()),
+                    browser: None,
+                    args: None,
+                    cwd: None,
+                    steps: None,
+                    parallel: false,
+                    webview: false,
+                    pinned: false,
+                }]);
+                Ok(())
+            })
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("bad"), "got: {err:#}");
+        assert!(!fs::read_to_string(&cm.config_path).unwrap().contains("bad"));
+        assert!(cm.local.client_mut("acme").unwrap().commands.is_none());
+    }
+
+    #[test]
+    fn shared_edit_without_include_fails() {
+        let mut cm = make_manager("clients: []\n");
+        assert!(cm.edit_layer(EditTarget::Shared(None), |_| Ok(())).is_err());
+    }
+
+    #[test]
+    fn renaming_selected_client_follows_selection() {
+        let mut cm = make_manager("currentClient: acme\nclients:\n- name: acme\n");
+        cm.edit_layer(EditTarget::Local, |layer| {
+            layer.client_mut("acme").unwrap().name = "acme-corp".to_string();
+            Ok(())
+        })
+        .unwrap();
+        cm.rename_selected_client("acme", "acme-corp").unwrap();
+        assert_eq!(
+            cm.get_current_client().map(String::as_str),
+            Some("acme-corp")
+        );
+        let written = fs::read_to_string(&cm.config_path).unwrap();
+        assert!(
+            written.contains("currentClient: acme-corp"),
+            "got: {}",
+            written
+        );
+    }
+
+    #[test]
+    fn removing_selected_client_or_project_clears_selection() {
+        let mut cm = make_manager(
+            "currentClient: acme\ncurrentProject: web\npreviousClient: acme\npreviousProject: api\nclients:\n- name: acme\n  projects:\n  - name: web\n  - name: api\n- name: umbrella\n",
+        );
+        // Still defined: nothing to clear
+        cm.deselect_removed("acme", Some("web")).unwrap();
+        assert_eq!(cm.config.current_project.as_deref(), Some("web"));
+
+        cm.edit_layer(EditTarget::Local, |layer| {
+            let acme = layer.client_mut("acme").unwrap();
+            acme.projects.as_mut().unwrap().retain(|p| p.name != "api");
+            Ok(())
+        })
+        .unwrap();
+        cm.deselect_removed("acme", Some("api")).unwrap();
+        assert_eq!(cm.config.current_project.as_deref(), Some("web"));
+        assert_eq!(cm.config.previous_client.as_deref(), Some("acme"));
+        assert!(cm.config.previous_project.is_none());
+
+        cm.edit_layer(EditTarget::Local, |layer| {
+            layer.clients.retain(|c| c.name != "acme");
+            Ok(())
+        })
+        .unwrap();
+        cm.deselect_removed("acme", None).unwrap();
+        assert!(cm.config.current_client.is_none() && cm.config.current_project.is_none());
+        assert!(cm.config.previous_client.is_none());
+        let written = fs::read_to_string(&cm.config_path).unwrap();
+        assert!(!written.contains("acme"), "got: {}", written);
+    }
+
+    /// A fresh temp dir for include-chain tests.
+    fn include_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ps-inc-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn nested_and_listed_includes_merge_lowest_priority_first() {
+        let dir = include_dir("chain");
+        fs::write(
+            dir.join("company.yml"),
+            "defaultBrowser: chrome\nclients:\n- name: acme\n  path: /company\n  description: Acme\n",
+        )
+        .unwrap();
+        // Relative entries resolve against the including file.
+        fs::write(
+            dir.join("team.yml"),
+            "include: company.yml\nclients:\n- name: acme\n  path: /team\n",
+        )
+        .unwrap();
+        fs::write(dir.join("extra.yml"), "defaultBrowser: edge\n").unwrap();
+        let cm = make_manager(&format!(
+            "include:\n- {}\n- {}\nclients:\n- name: acme\n  browser: firefox\n",
+            dir.join("team.yml").display(),
+            dir.join("extra.yml").display()
+        ));
+
+        let client = cm.get_client("acme").unwrap();
+        assert_eq!(client.path.as_deref(), Some("/team"));
+        assert_eq!(client.description.as_deref(), Some("Acme"));
+        assert_eq!(client.browser.as_deref(), Some("firefox"));
+        // extra.yml comes after team.yml (and company.yml) in the list.
+        assert_eq!(cm.get_default_browser(), "edge");
+
+        let names: Vec<String> = cm
+            .sources()
+            .unwrap()
+            .iter()
+            .map(|s| s.path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names[..3], ["company.yml", "team.yml", "extra.yml"]);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn command_overrides_replace_steps_and_vice_versa() {
+        let cmd = |yaml: &str| serde_yaml::from_str::<ProjectCommand>(yaml).unwrap();
+        let chain = cmd("key: ci\nsteps: [build, test]\nparallel: true\nbrowser: edge\n");
+        let merged = merge_commands(chain.clone(), cmd("key: ci\nurl: https://ci\n"));
+        assert_eq!(merged.url.as_deref(), Some("https://ci"));
+        assert!(merged.steps.is_none() && !merged.parallel);
+        assert_eq!(merged.browser.as_deref(), Some("edge"));
+
+        let merged = merge_commands(cmd("key: ci\nurl: https://ci\n"), chain);
+        assert!(merged.url.is_none());
+        assert_eq!(merged.steps.map(|s| s.len()), Some(2));
+    }
+
+    #[test]
+    fn command_overrides_redefining_the_body_drop_parallel_and_webview() {
+        let cmd = |yaml: &str| serde_yaml::from_str::<ProjectCommand>(yaml).unwrap();
+        let chain = cmd("key: ci\nsteps: [build, test]\nparallel: true\n");
+        let merged = merge_commands(chain.clone(), cmd("key: ci\nsteps: [build, test]\n"));
+        assert!(!merged.parallel);
+        let merged = merge_commands(chain, cmd("key: ci\nparallel: false\npinned: true\n"));
+        assert!(merged.parallel && merged.pinned);
+
+        let chat = cmd("key: chat\nurl: https://chat\nwebview: true\n");
+        let merged = merge_commands(chat.clone(), cmd("key: chat\nurl: https://chat2\n"));
+        assert!(!merged.webview);
+        let merged = merge_commands(chat, cmd("key: chat\nsteps: [ci]\n"));
+        assert!(!merged.webview && merged.url.is_none());
+        let merged = merge_commands(
+            cmd("key: chat\nurl: https://chat\n"),
+            cmd("key: chat\nwebview: true\n"),
+        );
+        assert!(merged.webview);
+    }
+
+    #[test]
+    fn env_maps_merge_per_variable() {
+        let dir = include_dir("env");
+        fs::write(
+            dir.join("shared.yml"),
+            "env:\n  REGISTRY: shared\n  AWS_PROFILE: shared\nclients:\n- name: acme\n  env:\n    AWS_PROFILE: acme\n",
+        )
+        .unwrap();
+        let cm = make_manager(&format!(
+            "include: {}\ncurrentClient: acme\nenv:\n  REGISTRY: local\nclients:\n- name: acme\n  env:\n    KUBECONFIG: /k\n",
+            dir.join("shared.yml").display()
+        ));
+        let env = cm.get_effective_env();
+        assert_eq!(env["REGISTRY"], "local");
+        assert_eq!(env["AWS_PROFILE"], "acme");
+        assert_eq!(env["KUBECONFIG"], "/k");
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn include_cycle_is_an_error_naming_the_chain() {
+        let dir = include_dir("cycle");
+        fs::write(dir.join("a.yml"), "include: b.yml\n").unwrap();
+        fs::write(dir.join("b.yml"), "include: a.yml\n").unwrap();
+        let local = dir.join("local.yml");
+        fs::write(
+            &local,
+            format!("include: {}\n", dir.join("a.yml").display()),
+        )
+        .unwrap();
+
+        let err = ConfigManager::load_config(&local).expect_err("cycle error");
+        let msg = format!("{err:#}");
+        assert!(msg.starts_with("Include cycle: "), "got: {}", msg);
+        assert!(msg.contains("a.yml -> "), "got: {}", msg);
+        assert!(msg.ends_with("a.yml"), "got: {}", msg);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn file_included_twice_is_read_once() {
+        let dir = include_dir("diamond");
+        fs::write(dir.join("base.yml"), "clients:\n- name: acme\n").unwrap();
+        fs::write(dir.join("a.yml"), "include: base.yml\n").unwrap();
+        fs::write(dir.join("b.yml"), "include: base.yml\n").unwrap();
+        let cm = make_manager(&format!(
+            "include: [{}, {}]\n",
+            dir.join("a.yml").display(),
+            dir.join("b.yml").display()
+        ));
+        assert_eq!(cm.sources().unwrap().len(), 4);
+        assert_eq!(cm.get_clients().len(), 1);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn shared_edit_needs_a_file_when_several_are_included() {
+        let dir = include_dir("pick");
+        fs::write(dir.join("a.yml"), "clients: []\n").unwrap();
+        fs::write(dir.join("b.yml"), "clients: []\n").unwrap();
+        let mut cm = make_manager(&format!(
+            "include: [{}, {}]\n",
+            dir.join("a.yml").display(),
+            dir.join("b.yml").display()
+        ));
+        assert!(cm.edit_layer(EditTarget::Shared(None), |_| Ok(())).is_err());
+
+        let b = dir.join("b.yml");
+        cm.edit_layer(EditTarget::Shared(Some(&b)), |layer| {
+            layer.global = Some(Vec::new());
+            Ok(())
+        })
+        .unwrap();
+        assert!(fs::read_to_string(&b).unwrap().contains("global"));
+        assert!(!fs::read_to_string(dir.join("a.yml"))
+            .unwrap()
+            .contains("global"));
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn concurrent_edits_from_stale_managers_are_all_kept() {
+        let path = make_manager("currentClient: c0\nclients:\n- name: c0\n").config_path;
+        // Every manager loads before any edit, as separate processes would.
+        let managers: Vec<ConfigManager> = (0..8)
+            .map(|_| {
+                let (config, raw_yaml, local) = ConfigManager::load_config(&path).unwrap();
+                ConfigManager {
+                    config,
+                    config_path: path.clone(),
+                    raw_yaml,
+                    local,
+                }
+            })
+            .collect();
+        let threads: Vec<_> = managers
+            .into_iter()
+            .enumerate()
+            .map(|(i, mut cm)| {
+                std::thread::spawn(move || {
+                    let name = format!("c{}", i + 1);
+                    cm.edit_layer(EditTarget::Local, |layer| {
+                        layer
+                            .clients
+                            .push(serde_yaml::from_str(&format!("name: {name}"))?);
+                        Ok(())
+                    })
+                    .unwrap();
+                    cm.set_current_selection(&name, None).unwrap();
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+
+        let cm = make_manager(&fs::read_to_string(&path).unwrap());
+        assert_eq!(cm.get_clients().len(), 9);
+        assert!(cm.get_current_client().is_some());
+        fs::remove_file(&path).ok();
+    }
 }
@@ -1,8 +1,120 @@
+//! Launcher history in `~/.project-switch-history.yml`.
+//!
+//! Each entry records how often a key was used, when it was last used and
+//! the client/project that was active at the time — its scope. Entries are
+//! kept per scope, so the same key used in two clients is two entries.
+//! Calculator expressions and file paths don't depend on the selection and
+//! are recorded unscoped (global). Keys are ranked by frecency: the use
+//! count weighted by how recently the key was last used.
+//!
+//! Older files held a bare list of keys; those are read as global entries
+//! used once, in their original order, and rewritten in the new format on
+//! the next write.
+
+use crate::config::ConfigManager;
+use crate::utils::file;
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How many entries are kept per scope when `historySize` isn't set.
+pub const DEFAULT_MAX_ENTRIES: usize = 10;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+/// The selection an entry was recorded in. No client means global.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Scope {
+    pub client: Option<String>,
+    pub project: Option<String>,
+}
+
+impl Scope {
+    /// The current selection of `config_manager`.
+    pub fn current(config_manager: &ConfigManager) -> Self {
+        Self {
+            client: config_manager.get_current_client().cloned(),
+            project: config_manager
+                .get_current_client()
+                .and(config_manager.get_current_project().cloned()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub key: String,
+    pub count: u32,
+    /// Unix timestamp in seconds.
+    #[serde(rename = "lastUsed")]
+    pub last_used: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+}
+
+impl Entry {
+    /// Use count weighted by the age of the last use.
+    pub fn frecency(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last_used);
+        let weight = if age <= HOUR {
+            4.0
+        } else if age <= DAY {
+            2.0
+        } else if age <= WEEK {
+            0.5
+        } else {
+            0.25
+        };
+        self.count as f64 * weight
+    }
+
+    pub fn in_scope(&self, scope: &Scope) -> bool {
+        self.client == scope.client && self.project == scope.project
+    }
 
-const MAX_ENTRIES: usize = 10;
+    /// How well the entry fits `scope`: 0 recorded in it, 1 in another
+    /// project of the same client, 2 global; `None` for other clients.
+    fn tier(&self, scope: &Scope) -> Option<u8> {
+        if self.in_scope(scope) {
+            Some(0)
+        } else if self.client.is_some() && self.client == scope.client {
+            Some(1)
+        } else if self.client.is_none() {
+            Some(2)
+        } else {
+            None
+        }
+    }
+}
+
+/// An entry as stored: the current format, or a key from the old plain list.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Stored {
+    Entry(Entry),
+    Key(String),
+}
+
+impl From<Stored> for Entry {
+    fn from(stored: Stored) -> Self {
+        match stored {
+            Stored::Entry(entry) => entry,
+            Stored::Key(key) => Entry {
+                key,
+                count: 1,
+                last_used: 0,
+                client: None,
+                project: None,
+            },
+        }
+    }
+}
 
 fn history_path() -> Result<PathBuf> {
     let home =
@@ -10,30 +122,174 @@ fn history_path() -> Result<PathBuf> {
     Ok(home.join(".project-switch-history.yml"))
 }
 
-/// Load recent action keys from the history file.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Sort entries best first: highest frecency, then most recently used.
+/// The sort is stable, so migrated entries keep their old order.
+fn rank(entries: &mut [Entry], now: u64) {
+    entries.sort_by(|a, b| {
+        b.frecency(now)
+            .total_cmp(&a.frecency(now))
+            .then(b.last_used.cmp(&a.last_used))
+    });
+}
+
+fn load_entries_from(path: &Path) -> Vec<Entry> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    serde_yaml::from_str::<Vec<Stored>>(&contents)
+        .map(|stored| stored.into_iter().map(Entry::from).collect())
+        .unwrap_or_default()
+}
+
+fn write_entries_to(path: &Path, entries: &[Entry]) -> Result<()> {
+    file::write_atomic(path, &serde_yaml::to_string(entries)?)
+}
+
+/// Load, change and write back the entries while holding the history lock,
+/// so concurrent launchers and CLI runs don't lose each other's updates.
+/// `change` returns whether anything changed and needs writing.
+fn update_at<T>(path: &Path, change: impl FnOnce(&mut Vec<Entry>) -> (T, bool)) -> Result<T> {
+    file::with_lock(path, || {
+        let mut entries = load_entries_from(path);
+        let (result, changed) = change(&mut entries);
+        if changed {
+            write_entries_to(path, &entries)?;
+        }
+        Ok(result)
+    })
+}
+
+/// Keys for `scope`: its own entries first, then the client's other
+/// projects, then global entries, each by frecency. Other clients' entries
+/// are left out.
+fn load_from(path: &Path, scope: &Scope, now: u64) -> Vec<String> {
+    let mut entries: Vec<(u8, Entry)> = load_entries_from(path)
+        .into_iter()
+        .filter_map(|e| Some((e.tier(scope)?, e)))
+        .collect();
+    entries.sort_by(|(ta, a), (tb, b)| {
+        ta.cmp(tb)
+            .then(b.frecency(now).total_cmp(&a.frecency(now)))
+            .then(b.last_used.cmp(&a.last_used))
+    });
+    let mut keys: Vec<String> = Vec::new();
+    for (_, entry) in entries {
+        if !keys.contains(&entry.key) {
+            keys.push(entry.key);
+        }
+    }
+    keys
+}
+
+/// Count a use of `key` in `scope` and keep the scope's best `max_entries`
+/// entries. The entry just used is always kept, even when its frecency is
+/// still low. Other scopes are left alone.
+fn record_entry(entries: &mut Vec<Entry>, key: &str, scope: Scope, max_entries: usize, now: u64) {
+    let count = match entries
+        .iter()
+        .position(|e| e.key == key && e.in_scope(&scope))
+    {
+        Some(i) => entries.remove(i).count.saturating_add(1),
+        None => 1,
+    };
+    rank(entries, now);
+    let mut kept = 0;
+    entries.retain(|e| {
+        if !e.in_scope(&scope) {
+            return true;
+        }
+        kept += 1;
+        kept < max_entries
+    });
+    entries.push(Entry {
+        key: key.to_string(),
+        count,
+        last_used: now,
+        client: scope.client,
+        project: scope.project,
+    });
+    rank(entries, now);
+}
+
+fn record_at(path: &Path, key: &str, scope: Scope, max_entries: usize, now: u64) -> Result<()> {
+    update_at(path, |entries| {
+        record_entry(entries, key, scope, max_entries, now);
+        ((), true)
+    })
+}
+
+/// Calculator expressions and file paths mean the same in every client.
+fn is_global_key(key: &str) -> bool {
+    key.starts_with('=') || crate::launcher::is_file_path(key)
+}
+
+/// Load recent action keys for the current selection: its own entries
+/// first, falling back to the client's other projects and global entries.
 /// Returns an empty list if the file doesn't exist or can't be parsed.
 pub fn load() -> Vec<String> {
-    let path = match history_path() {
-        Ok(p) => p,
-        Err(_) => return Vec::new(),
-    };
-    let contents = match fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => return Vec::new(),
+    let scope = ConfigManager::new()
+        .map(|cm| Scope::current(&cm))
+        .unwrap_or_default();
+    match history_path() {
+        Ok(path) => load_from(&path, &scope, now()),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Every entry in every scope, best frecency first.
+pub fn entries() -> Vec<Entry> {
+    let mut entries = match history_path() {
+        Ok(path) => load_entries_from(&path),
+        Err(_) => Vec::new(),
     };
-    serde_yaml::from_str::<Vec<String>>(&contents).unwrap_or_default()
+    rank(&mut entries, now());
+    entries
+}
+
+/// Keep only the entries `keep` accepts. Returns how many were removed.
+pub fn retain(mut keep: impl FnMut(&Entry) -> bool) -> Result<usize> {
+    update_at(&history_path()?, |entries| {
+        let before = entries.len();
+        entries.retain(|e| keep(e));
+        let removed = before - entries.len();
+        (removed, removed > 0)
+    })
+}
+
+/// Move a renamed client's entries to its new name.
+pub fn rename_client(old_name: &str, new_name: &str) -> Result<()> {
+    update_at(&history_path()?, |entries| {
+        let mut renamed = false;
+        for entry in entries.iter_mut() {
+            if entry.client.as_deref() == Some(old_name) {
+                entry.client = Some(new_name.to_string());
+                renamed = true;
+            }
+        }
+        ((), renamed)
+    })
 }
 
-/// Record an item key as the most recent action.
-/// Deduplicates (moves existing entry to top) and caps at 10.
+/// Record a use of an item key in the current client/project (globally for
+/// expressions and file paths). Keeps at most `historySize` entries per
+/// scope (default 10), dropping the lowest frecency first.
 pub fn record(key: &str) -> Result<()> {
-    let mut entries = load();
-    entries.retain(|k| k != key);
-    entries.insert(0, key.to_string());
-    entries.truncate(MAX_ENTRIES);
-    let yaml = serde_yaml::to_string(&entries)?;
-    fs::write(history_path()?, yaml)?;
-    Ok(())
+    let config_manager = ConfigManager::new().ok();
+    let scope = match &config_manager {
+        Some(cm) if !is_global_key(key) => Scope::current(cm),
+        _ => Scope::default(),
+    };
+    let max_entries = config_manager
+        .as_ref()
+        .map_or(DEFAULT_MAX_ENTRIES, |cm| cm.get_history_size());
+    record_at(&history_path()?, key, scope, max_entries, now())
 }
 
 #[cfg(test)]
@@ -41,6 +297,8 @@ mod tests {
     use super::*;
     use std::fs;
 
+    const MAX_ENTRIES: usize = DEFAULT_MAX_ENTRIES;
+
     /// Run a test with an isolated history file in a temp dir.
     fn with_temp_history(f: impl FnOnce(&PathBuf)) {
         let name = std::thread::current()
@@ -56,25 +314,43 @@ mod tests {
         let _ = fs::remove_dir_all(&dir);
     }
 
-    fn load_from(path: &PathBuf) -> Vec<String> {
-        let contents = fs::read_to_string(path).unwrap_or_default();
-        serde_yaml::from_str::<Vec<String>>(&contents).unwrap_or_default()
+    /// Clock for the tests: one second after the latest recorded use.
+    fn tick(path: &Path) -> u64 {
+        let latest = load_entries_from(path).iter().map(|e| e.last_used).max();
+        latest.unwrap_or(1_000_000) + 1
+    }
+
+    fn record_to(path: &Path, key: &str) {
+        let now = tick(path);
+        record_at(path, key, Scope::default(), MAX_ENTRIES, now).unwrap();
     }
 
-    fn record_to(path: &PathBuf, key: &str) {
-        let mut entries = load_from(path);
-        entries.retain(|k| k != key);
-        entries.insert(0, key.to_string());
-        entries.truncate(MAX_ENTRIES);
-        let yaml = serde_yaml::to_string(&entries).unwrap();
-        fs::write(path, yaml).unwrap();
+    fn load_now(path: &Path) -> Vec<String> {
+        load_from(path, &Scope::default(), tick(path))
+    }
+
+    fn scope(client: &str, project: Option<&str>) -> Scope {
+        Scope {
+            client: Some(client.to_string()),
+            project: project.map(str::to_string),
+        }
+    }
+
+    fn entry(key: &str, count: u32, last_used: u64) -> Entry {
+        Entry {
+            key: key.to_string(),
+            count,
+            last_used,
+            client: None,
+            project: None,
+        }
     }
 
     #[test]
     fn record_adds_entry() {
         with_temp_history(|path| {
             record_to(path, "github");
-            assert_eq!(load_from(path), vec!["github"]);
+            assert_eq!(load_now(path), vec!["github"]);
         });
     }
 
@@ -84,7 +360,7 @@ mod tests {
             record_to(path, "github");
             record_to(path, "jira");
             record_to(path, "github");
-            assert_eq!(load_from(path), vec!["github", "jira"]);
+            assert_eq!(load_now(path), vec!["github", "jira"]);
         });
     }
 
@@ -94,7 +370,7 @@ mod tests {
             for i in 0..15 {
                 record_to(path, &format!("item-{}", i));
             }
-            let entries = load_from(path);
+            let entries = load_now(path);
             assert_eq!(entries.len(), MAX_ENTRIES);
             assert_eq!(entries[0], "item-14");
             assert_eq!(entries[9], "item-5");
@@ -109,7 +385,7 @@ mod tests {
             }
             // Re-execute item-0 (currently last) — should move to top, still 10 entries
             record_to(path, "item-0");
-            let entries = load_from(path);
+            let entries = load_now(path);
             assert_eq!(entries.len(), MAX_ENTRIES);
             assert_eq!(entries[0], "item-0");
             assert_eq!(entries[1], "item-9");
@@ -119,7 +395,7 @@ mod tests {
     #[test]
     fn load_returns_empty_when_no_file() {
         with_temp_history(|path| {
-            assert!(load_from(path).is_empty());
+            assert!(load_now(path).is_empty());
         });
     }
 
@@ -127,7 +403,7 @@ mod tests {
     fn record_calc_expression() {
         with_temp_history(|path| {
             record_to(path, "=5+3");
-            let entries = load_from(path);
+            let entries = load_now(path);
             assert_eq!(entries, vec!["=5+3"]);
         });
     }
@@ -139,7 +415,7 @@ mod tests {
             record_to(path, "=5+3");
             record_to(path, "jira");
             record_to(path, "=5+3");
-            let entries = load_from(path);
+            let entries = load_now(path);
             assert_eq!(entries, vec!["=5+3", "jira", "github"]);
         });
     }
@@ -148,7 +424,7 @@ mod tests {
     fn record_file_path() {
         with_temp_history(|path| {
             record_to(path, "C:\\Users\\test\\file.txt");
-            let entries = load_from(path);
+            let entries = load_now(path);
             assert_eq!(entries, vec!["C:\\Users\\test\\file.txt"]);
         });
     }
@@ -160,7 +436,7 @@ mod tests {
             record_to(path, "C:\\Users\\test\\file.txt");
             record_to(path, "jira");
             record_to(path, "C:\\Users\\test\\file.txt");
-            let entries = load_from(path);
+            let entries = load_now(path);
             assert_eq!(entries, vec!["C:\\Users\\test\\file.txt", "jira", "github"]);
         });
     }
@@ -172,7 +448,7 @@ mod tests {
             record_to(path, "=10*2");
             record_to(path, "C:\\temp\\notes.md");
             record_to(path, "jira");
-            let entries = load_from(path);
+            let entries = load_now(path);
             assert_eq!(
                 entries,
                 vec!["jira", "C:\\temp\\notes.md", "=10*2", "github"]
@@ -192,11 +468,179 @@ mod tests {
             record_to(path, "C:\\path1");
             record_to(path, "C:\\path2");
             record_to(path, "C:\\path3");
-            let entries = load_from(path);
+            let entries = load_now(path);
             assert_eq!(entries.len(), MAX_ENTRIES);
             assert_eq!(entries[0], "C:\\path3");
             assert_eq!(entries[1], "C:\\path2");
             assert_eq!(entries[2], "C:\\path1");
         });
     }
+
+    #[test]
+    fn frequent_beats_recent_until_it_ages() {
+        let now = 10 * WEEK;
+        let mut entries = vec![
+            entry("once", 1, now - 60),
+            entry("often", 5, now - 2 * HOUR),
+        ];
+        rank(&mut entries, now);
+        assert_eq!(entries[0].key, "often");
+        // A month later the frequent key has aged out of its bonus.
+        let mut entries = vec![
+            entry("once", 1, now - 60),
+            entry("often", 5, now - 4 * WEEK),
+        ];
+        rank(&mut entries, now);
+        assert_eq!(entries[0].key, "once");
+    }
+
+    #[test]
+    fn record_counts_uses_and_tags_selection() {
+        let mut entries = Vec::new();
+        record_entry(&mut entries, "github", scope("acme", Some("web")), 10, 100);
+        record_entry(&mut entries, "github", scope("acme", Some("web")), 10, 200);
+        assert_eq!(
+            entries,
+            [Entry {
+                key: "github".to_string(),
+                count: 2,
+                last_used: 200,
+                client: Some("acme".to_string()),
+                project: Some("web".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn cap_drops_lowest_frecency_but_keeps_new_entry() {
+        let now = 10 * WEEK;
+        let mut entries = vec![
+            entry("often", 9, now - 60),
+            entry("stale", 1, now - 3 * WEEK),
+        ];
+        record_entry(&mut entries, "new", Scope::default(), 2, now);
+        let keys: Vec<&str> = entries.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, ["often", "new"]);
+    }
+
+    #[test]
+    fn migrates_plain_key_list() {
+        with_temp_history(|path| {
+            fs::write(path, "- jira\n- github\n- =5+3\n").unwrap();
+            assert_eq!(load_now(path), vec!["jira", "github", "=5+3"]);
+            record_to(path, "github");
+            assert_eq!(load_now(path), vec!["github", "jira", "=5+3"]);
+            let stored = load_entries_from(path);
+            assert_eq!(stored[0].count, 2);
+            assert_eq!(stored[1], entry("jira", 1, 0));
+        });
+    }
+
+    #[test]
+    fn same_key_is_counted_per_scope() {
+        let mut entries = Vec::new();
+        record_entry(&mut entries, "github", scope("acme", None), 10, 100);
+        record_entry(&mut entries, "github", scope("globex", None), 10, 200);
+        record_entry(&mut entries, "github", scope("acme", None), 10, 300);
+        let counts: Vec<(Option<&str>, u32)> = entries
+            .iter()
+            .map(|e| (e.client.as_deref(), e.count))
+            .collect();
+        assert_eq!(counts, [(Some("acme"), 2), (Some("globex"), 1)]);
+    }
+
+    #[test]
+    fn cap_applies_per_scope() {
+        let mut entries = Vec::new();
+        for (i, key) in ["a", "b", "c"].iter().enumerate() {
+            record_entry(&mut entries, key, scope("acme", None), 2, i as u64);
+        }
+        record_entry(&mut entries, "x", Scope::default(), 2, 10);
+        let keys: Vec<&str> = entries.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, ["x", "c", "b"]);
+    }
+
+    #[test]
+    fn load_puts_active_scope_first_then_client_then_global() {
+        with_temp_history(|path| {
+            let now = 1_000_000;
+            let mut entries = Vec::new();
+            record_entry(&mut entries, "=1+1", Scope::default(), 10, now - 1);
+            record_entry(&mut entries, "other", scope("globex", None), 10, now);
+            record_entry(&mut entries, "api", scope("acme", Some("api")), 10, now);
+            record_entry(&mut entries, "web", scope("acme", Some("web")), 10, now - 5);
+            record_entry(
+                &mut entries,
+                "shared",
+                scope("acme", Some("web")),
+                10,
+                now - 9,
+            );
+            record_entry(&mut entries, "shared", scope("acme", Some("api")), 10, now);
+            write_entries_to(path, &entries).unwrap();
+
+            let keys = load_from(path, &scope("acme", Some("web")), now);
+            assert_eq!(keys, ["web", "shared", "api", "=1+1"]);
+            let keys = load_from(path, &Scope::default(), now);
+            assert_eq!(keys, ["=1+1"]);
+        });
+    }
+
+    #[test]
+    fn concurrent_records_lose_no_updates() {
+        with_temp_history(|path| {
+            let threads: Vec<_> = (0..8)
+                .map(|t| {
+                    let path = path.clone();
+                    std::thread::spawn(move || {
+                        for i in 0..25 {
+                            let key = format!("key-{}", (t + i) % 5);
+                            record_at(&path, &key, Scope::default(), MAX_ENTRIES, 1_000).unwrap();
+                        }
+                    })
+                })
+                .collect();
+            for t in threads {
+                t.join().unwrap();
+            }
+            let entries = load_entries_from(path);
+            assert_eq!(entries.len(), 5);
+            assert_eq!(entries.iter().map(|e| e.count).sum::<u32>(), 8 * 25);
+        });
+    }
+
+    /// Worker for [`concurrent_records_from_processes`]; a no-op unless run
+    /// by it.
+    #[test]
+    fn record_worker() {
+        let Ok(path) = std::env::var("PS_HISTORY_WORKER") else {
+            return;
+        };
+        for i in 0..25 {
+            let key = format!("key-{}", i % 5);
+            record_at(Path::new(&path), &key, Scope::default(), MAX_ENTRIES, 1_000).unwrap();
+        }
+    }
+
+    #[test]
+    fn concurrent_records_from_processes() {
+        with_temp_history(|path| {
+            let exe = std::env::current_exe().unwrap();
+            let children: Vec<_> = (0..4)
+                .map(|_| {
+                    std::process::Command::new(&exe)
+                        .args(["--exact", "history::tests::record_worker", "--quiet"])
+                        .env("PS_HISTORY_WORKER", path)
+                        .stdout(std::process::Stdio::null())
+                        .spawn()
+                        .unwrap()
+                })
+                .collect();
+            for mut child in children {
+                assert!(child.wait().unwrap().success());
+            }
+            let entries = load_entries_from(path);
+            assert_eq!(entries.iter().map(|e| e.count).sum::<u32>(), 4 * 25);
+        });
+    }
 }
@@ -0,0 +1,203 @@
+//! `onEnter` / `onLeave` hooks: shell commands run when the selection changes.
+//!
+//! Leaving runs the old project's `onLeave`, then the old client's; entering
+//! runs the new client's `onEnter`, then the new project's. A client's hooks
+//! only fire when the client itself changes, so moving between projects of
+//! one client runs just the project hooks. Each command runs in its entry's
+//! `path` with the selection's `env`, its output goes to the log, and it is
+//! killed after `hookTimeout` seconds. A failing hook is reported but never
+//! stops the switch.
+
+use crate::config::{self, ConfigManager};
+use crate::utils::interpolate::{self, Vars};
+use crate::utils::log;
+use crate::utils::shell::{self, Outcome};
+use colored::*;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Enter,
+    Leave,
+}
+
+impl Event {
+    fn key(self) -> &'static str {
+        match self {
+            Event::Enter => "onEnter",
+            Event::Leave => "onLeave",
+        }
+    }
+}
+
+/// One entry's hook commands for an event.
+#[derive(Debug, PartialEq)]
+struct Hook {
+    /// `acme` or `acme / web`.
+    owner: String,
+    commands: Vec<String>,
+    cwd: Option<PathBuf>,
+}
+
+/// The hooks that fire for `event` on the active selection of
+/// `config_manager`, given the selection on the other side of the switch.
+fn plan(
+    config_manager: &ConfigManager,
+    event: Event,
+    other_client: Option<&str>,
+    other_project: Option<&str>,
+) -> Vec<Hook> {
+    let Some((client_name, client, project)) = config_manager.resolve_current() else {
+        return Vec::new();
+    };
+    let client_changed = other_client != Some(client_name.as_str());
+    let project_changed = client_changed || other_project != project.map(|(name, _)| name.as_str());
+    let pick = |enter: &Option<Vec<String>>, leave: &Option<Vec<String>>| match event {
+        Event::Enter => enter.clone(),
+        Event::Leave => leave.clone(),
+    };
+
+    let mut hooks = Vec::new();
+    if client_changed {
+        if let Some(commands) = pick(&client.on_enter, &client.on_leave) {
+            hooks.push(Hook {
+                owner: client_name.clone(),
+                commands,
+                cwd: client.path.as_deref().map(config::expand_tilde),
+            });
+        }
+    }
+    if let Some((project_name, project)) = project.filter(|_| project_changed) {
+        if let Some(commands) = pick(&project.on_enter, &project.on_leave) {
+            hooks.push(Hook {
+                owner: format!("{} / {}", client_name, project_name),
+                commands,
+                cwd: project
+                    .path
+                    .as_deref()
+                    .or(client.path.as_deref())
+                    .map(config::expand_tilde),
+            });
+        }
+    }
+    // Leaving unwinds the project before its client.
+    if event == Event::Leave {
+        hooks.reverse();
+    }
+    hooks
+}
+
+/// Run the `event` hooks for the active selection of `config_manager`, where
+/// `other_*` is the selection on the other side of the switch: the new one
+/// when leaving, the old one when entering. Progress is printed unless
+/// `quiet`; failures always go to stderr.
+pub fn run(
+    config_manager: &ConfigManager,
+    event: Event,
+    other_client: Option<&str>,
+    other_project: Option<&str>,
+    quiet: bool,
+) {
+    let hooks = plan(config_manager, event, other_client, other_project);
+    if hooks.is_empty() {
+        return;
+    }
+    let vars = Vars::from_selection(config_manager);
+    let timeout = config_manager.get_hook_timeout();
+    let env = interpolate::expand_env(&config_manager.get_effective_env(), &vars);
+
+    for hook in hooks {
+        for command in &hook.commands {
+            let label = format!("{} {}", hook.owner, event.key());
+            let expanded = env
+                .as_ref()
+                .map_err(|e| format!("{:#}", e))
+                .and_then(|env| {
+                    interpolate::expand(command, &vars)
+                        .map(|command| (env, command))
+                        .map_err(|e| format!("{:#}", e))
+                });
+            let (output, outcome) = match expanded {
+                Ok((env, command)) => {
+                    if !quiet {
+                        println!("{}", format!("{}: {}", label, command).dimmed());
+                    }
+                    let (output, outcome) = shell::run(&command, hook.cwd.as_deref(), env, timeout);
+                    (output.stdout + &output.stderr, outcome)
+                }
+                Err(reason) => (String::new(), Outcome::Failed(reason)),
+            };
+
+            let mut entry = format!("hook {}: {}", label, command);
+            if !output.trim().is_empty() {
+                entry.push('\n');
+                entry.push_str(output.trim_end());
+            }
+            if let Outcome::Failed(reason) = &outcome {
+                entry.push_str(&format!("\nhook failed: {}", reason));
+                eprintln!(
+                    "{}",
+                    format!("{} hook '{}' failed: {}", label, command, reason).yellow()
+                );
+            }
+            log::append(&entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "\
+currentClient: acme
+currentProject: web
+clients:
+- name: acme
+  path: /src/acme
+  onEnter: [aws sso login]
+  onLeave: [docker compose down]
+  projects:
+  - name: web
+    path: /src/acme-web
+    onEnter: [nvm use]
+    onLeave: [echo bye]
+  - name: api
+- name: beta
+";
+
+    fn owners(hooks: &[Hook]) -> Vec<&str> {
+        hooks.iter().map(|h| h.owner.as_str()).collect()
+    }
+
+    #[test]
+    fn leaving_for_another_client_unwinds_project_then_client() {
+        let cm = ConfigManager::from_test_yaml(CONFIG);
+        let hooks = plan(&cm, Event::Leave, Some("beta"), None);
+        assert_eq!(owners(&hooks), ["acme / web", "acme"]);
+        assert_eq!(hooks[0].commands, ["echo bye"]);
+        assert_eq!(hooks[0].cwd, Some(PathBuf::from("/src/acme-web")));
+    }
+
+    #[test]
+    fn moving_between_projects_of_one_client_skips_client_hooks() {
+        let cm = ConfigManager::from_test_yaml(CONFIG);
+        assert_eq!(
+            owners(&plan(&cm, Event::Leave, Some("acme"), Some("api"))),
+            ["acme / web"]
+        );
+        assert_eq!(
+            owners(&plan(&cm, Event::Enter, Some("acme"), None)),
+            ["acme / web"]
+        );
+        assert!(plan(&cm, Event::Enter, Some("acme"), Some("web")).is_empty());
+    }
+
+    #[test]
+    fn entering_from_nothing_runs_client_then_project() {
+        let cm = ConfigManager::from_test_yaml(CONFIG);
+        let hooks = plan(&cm, Event::Enter, None, None);
+        assert_eq!(owners(&hooks), ["acme", "acme / web"]);
+        assert_eq!(hooks[0].commands, ["aws sso login"]);
+    }
+}
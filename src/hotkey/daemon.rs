@@ -8,12 +8,12 @@ use muda::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem};
 use tray_icon::{TrayIcon, TrayIconBuilder};
 
 use crate::config::ConfigManager;
-use crate::hotkey::sync;
-use crate::launcher::ListItem;
+use crate::hotkey::{sync, watch};
 use crate::ui::state::Visibility;
 use crate::ui::WindowState;
 use anyhow::Result;
 use eframe::egui;
+use std::sync::mpsc::Receiver;
 
 #[cfg(any(windows, target_os = "macos"))]
 fn register_hotkey() -> Result<GlobalHotKeyManager> {
@@ -77,22 +77,14 @@ fn create_tray(shortcuts_enabled: bool) -> Result<(TrayIcon, MenuIds)> {
     ))
 }
 
-fn load_items() -> (Vec<ListItem>, String) {
-    let config_manager = match ConfigManager::new() {
-        Ok(cm) => cm,
-        Err(_) => return (Vec::new(), "global".to_string()),
-    };
-
-    let display_name = crate::commands::list::selection_display_name(&config_manager);
-
-    let (_, items) = crate::commands::list::load_items(&config_manager);
-    (items, display_name)
-}
-
 struct DaemonApp {
     state: WindowState,
     client_name: String,
     prev_input: String,
+    /// Config reloads from the [`watch`] thread.
+    reloads: Receiver<Result<watch::Loaded, String>>,
+    /// The last config that loaded; `None` until one does.
+    config_manager: Option<ConfigManager>,
     #[cfg(any(windows, target_os = "macos"))]
     _hotkey_manager: GlobalHotKeyManager,
     #[cfg(any(windows, target_os = "macos"))]
@@ -101,17 +93,59 @@ struct DaemonApp {
     menu_ids: MenuIds,
 }
 
+impl DaemonApp {
+    /// Replace the command items and merge in the provider items as they
+    /// come, keeping what providers listed until they send anew.
+    fn apply_loaded(&mut self, loaded: watch::Loaded) {
+        self.state.set_command_items(loaded.items);
+        for batch in loaded.provided {
+            self.state.append_items(batch);
+        }
+        if let Some(rx) = loaded.pending {
+            self.state.receive_items(rx);
+        }
+        self.state.set_targets(loaded.targets);
+        self.state.config_error = None;
+        self.client_name = loaded.display_name;
+        self.config_manager = Some(loaded.config_manager);
+    }
+
+    /// Apply config reloads: new items replace the old, a broken config
+    /// keeps the last good items and shows its error until fixed.
+    fn apply_reloads(&mut self) {
+        while let Ok(reloaded) = self.reloads.try_recv() {
+            match reloaded {
+                Ok(loaded) => self.apply_loaded(loaded),
+                Err(e) => self.state.config_error = Some(e),
+            }
+        }
+    }
+
+    /// Prepare the launcher as it's shown: current history, and provider
+    /// items collected again in the background (they may have changed since
+    /// the config was loaded). Uses the last good config rather than reading
+    /// it again.
+    #[cfg(any(windows, target_os = "macos"))]
+    fn refresh_on_show(&mut self) {
+        self.state.set_recent_keys(crate::history::load());
+        if let Some(cm) = &self.config_manager {
+            self.state
+                .receive_items(crate::providers::refresh(crate::providers::registered(cm)));
+        }
+    }
+}
+
 impl eframe::App for DaemonApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.apply_reloads();
+        self.state.poll_items();
+
         // Poll hotkey events
         #[cfg(any(windows, target_os = "macos"))]
         if let Ok(_event) = GlobalHotKeyEvent::receiver().try_recv() {
             self.state.toggle();
             if self.state.visibility == Visibility::Visible {
-                let (items, name) = load_items();
-                self.state.set_items(items);
-                self.state.set_recent_keys(crate::history::load());
-                self.client_name = name;
+                self.refresh_on_show();
             }
         }
 
@@ -120,10 +154,7 @@ impl eframe::App for DaemonApp {
         if let Ok(event) = MenuEvent::receiver().try_recv() {
             if event.id() == self.menu_ids.open.id() {
                 self.state.show();
-                let (items, name) = load_items();
-                self.state.set_items(items);
-                self.state.set_recent_keys(crate::history::load());
-                self.client_name = name;
+                self.refresh_on_show();
             } else if event.id() == self.menu_ids.exit.id() {
                 std::process::exit(0);
             } else if event.id() == self.menu_ids.shortcuts.id() {
@@ -154,7 +185,7 @@ impl eframe::App for DaemonApp {
         crate::ui::window::render_launcher(
             ctx,
             &mut self.state,
-            &self.client_name,
+            &mut self.client_name,
             &mut self.prev_input,
         );
     }
@@ -162,15 +193,21 @@ impl eframe::App for DaemonApp {
 
 /// Run the daemon: hotkey listener + system tray + GUI launcher.
 pub fn run() -> Result<()> {
-    // Start config sync
-    if let Ok(cm) = ConfigManager::new() {
-        sync::start(cm.get_include_path().map(|s| s.to_string()));
-    }
+    // A broken config at startup still starts the daemon: the launcher shows
+    // the error, and the watcher picks up the fix (of the local file; the
+    // includes are unknown until it parses).
+    let (loaded, included, config_error) = match watch::load() {
+        Ok((loaded, included)) => (Some(loaded), included, None),
+        Err(e) => (None, Vec::new(), Some(e)),
+    };
 
-    let (items, display_name) = load_items();
+    // Start config sync
+    sync::start(included.clone());
+    let reloads = watch::start(included);
     #[cfg(any(windows, target_os = "macos"))]
-    let shortcuts_enabled = ConfigManager::new()
-        .map(|cm| cm.get_shortcuts_config().enabled)
+    let shortcuts_enabled = loaded
+        .as_ref()
+        .map(|l| l.config_manager.get_shortcuts_config().enabled)
         .unwrap_or(true);
 
     #[cfg(any(windows, target_os = "macos"))]
@@ -178,26 +215,30 @@ pub fn run() -> Result<()> {
     #[cfg(any(windows, target_os = "macos"))]
     let (tray, menu_ids) = create_tray(shortcuts_enabled)?;
 
-    let recent_keys = crate::history::load();
-    let state = WindowState::new(items, recent_keys);
+    let mut app = DaemonApp {
+        state: WindowState::new(Vec::new(), crate::history::load()),
+        client_name: "project-switch".to_string(),
+        prev_input: String::new(),
+        reloads,
+        config_manager: None,
+        #[cfg(any(windows, target_os = "macos"))]
+        _hotkey_manager: hotkey_manager,
+        #[cfg(any(windows, target_os = "macos"))]
+        _tray: tray,
+        #[cfg(any(windows, target_os = "macos"))]
+        menu_ids,
+    };
+    match loaded {
+        Some(loaded) => app.apply_loaded(loaded),
+        None => app.state.config_error = config_error,
+    }
 
     eframe::run_native(
         "project-switch",
         crate::ui::launcher_options(false, None),
         Box::new(move |cc| {
             crate::ui::apply_launcher_style(&cc.egui_ctx);
-
-            Ok(Box::new(DaemonApp {
-                state,
-                client_name: display_name,
-                prev_input: String::new(),
-                #[cfg(any(windows, target_os = "macos"))]
-                _hotkey_manager: hotkey_manager,
-                #[cfg(any(windows, target_os = "macos"))]
-                _tray: tray,
-                #[cfg(any(windows, target_os = "macos"))]
-                menu_ids,
-            }))
+            Ok(Box::new(app))
         }),
     )
     .map_err(|e| anyhow::anyhow!("Daemon error: {}", e))
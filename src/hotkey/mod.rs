@@ -2,3 +2,4 @@
 
 pub mod daemon;
 mod sync;
+mod watch;
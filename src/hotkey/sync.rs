@@ -1,6 +1,6 @@
 //! Background git sync for included config files.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::thread;
 use std::time::Duration;
@@ -49,25 +49,26 @@ fn git_sync(dir: &Path) {
     }
 }
 
-/// Start the background sync thread for the given config directory.
-/// Returns immediately; the thread runs until the process exits.
-pub fn start(include_path: Option<String>) {
-    let dir = match include_path {
-        Some(p) => {
-            let path = crate::config::expand_include_path(&p);
-            match path.parent() {
-                Some(d) if d.join(".git").exists() => d.to_path_buf(),
-                _ => return,
+/// Start a background sync thread for each git checkout holding one of the
+/// given included config files. Returns immediately; the threads run until
+/// the process exits.
+pub fn start(include_paths: Vec<PathBuf>) {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for path in include_paths {
+        if let Some(dir) = path.parent() {
+            if dir.join(".git").exists() && !dirs.iter().any(|d| d == dir) {
+                dirs.push(dir.to_path_buf());
             }
         }
-        None => return,
-    };
+    }
 
-    thread::spawn(move || {
-        git_pull(&dir);
-        loop {
-            thread::sleep(Duration::from_secs(30));
-            git_sync(&dir);
-        }
-    });
+    for dir in dirs {
+        thread::spawn(move || {
+            git_pull(&dir);
+            loop {
+                thread::sleep(Duration::from_secs(30));
+                git_sync(&dir);
+            }
+        });
+    }
 }
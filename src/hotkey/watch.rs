@@ -0,0 +1,157 @@
+//! Background config watcher: reloads the config when `~/.project-switch.yml`
+//! or one of its included files changes, so the daemon's launcher is always
+//! current without re-reading everything on each hotkey press.
+//!
+//! Files are polled for a changed modification time or size (a git pull of
+//! an include, an editor save and our own atomic rename all show up that
+//! way). A change is reloaded once the files have been quiet for a poll, so
+//! an editor writing in several steps is read once, complete.
+
+use crate::config::{self, ConfigManager};
+use crate::launcher::ListItem;
+use crate::providers::{self, Batch};
+use crate::ui::state::SwitchTarget;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Everything the launcher shows that comes from the config, and the config
+/// itself for what the daemon does between reloads.
+pub struct Loaded {
+    pub config_manager: ConfigManager,
+    /// The command items.
+    pub items: Vec<ListItem>,
+    /// The sync providers' items.
+    pub provided: Vec<Batch>,
+    /// The background providers' items, sent as each finishes.
+    pub pending: Option<Receiver<Batch>>,
+    pub display_name: String,
+    pub targets: Vec<SwitchTarget>,
+}
+
+/// Load the config and the launcher items it defines; background providers
+/// keep collecting after this returns. The error is the full context chain,
+/// for display.
+pub fn load() -> Result<(Loaded, Vec<PathBuf>), String> {
+    let config_manager = ConfigManager::new().map_err(|e| format!("{e:#}"))?;
+    let display_name = crate::commands::list::selection_display_name(&config_manager);
+    let (_, items) = crate::commands::list::load_command_items(&config_manager);
+    let (provided, pending) = providers::start(providers::registered(&config_manager));
+    let targets = crate::commands::switch::targets(&config_manager);
+    let included = config_manager.included_paths().unwrap_or_default();
+    Ok((
+        Loaded {
+            config_manager,
+            items,
+            provided,
+            pending,
+            display_name,
+            targets,
+        },
+        included,
+    ))
+}
+
+type Stamp = Option<(SystemTime, u64)>;
+
+/// The watched files and their state when last looked at.
+struct Files {
+    paths: Vec<PathBuf>,
+    stamps: Vec<Stamp>,
+}
+
+impl Files {
+    fn new(paths: Vec<PathBuf>) -> Self {
+        let stamps = paths.iter().map(stamp).collect();
+        Self { paths, stamps }
+    }
+
+    /// Whether any file changed since the last call (or since `new`).
+    fn changed(&mut self) -> bool {
+        let stamps: Vec<Stamp> = self.paths.iter().map(stamp).collect();
+        let changed = stamps != self.stamps;
+        self.stamps = stamps;
+        changed
+    }
+}
+
+fn stamp(path: &PathBuf) -> Stamp {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+/// The local config file plus `included`.
+fn watched(included: Vec<PathBuf>) -> Vec<PathBuf> {
+    config::local_config_path()
+        .into_iter()
+        .chain(included)
+        .collect()
+}
+
+/// Start watching the config files; `included` are the include files of the
+/// config loaded at startup. Each reload is sent on the returned channel:
+/// the new items, or the error that kept the previous ones in place. The
+/// thread runs until the receiver is dropped.
+pub fn start(included: Vec<PathBuf>) -> Receiver<Result<Loaded, String>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut files = Files::new(watched(included));
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            if !files.changed() {
+                continue;
+            }
+            // Wait for a quiet poll: the writer is done.
+            loop {
+                thread::sleep(POLL_INTERVAL);
+                if !files.changed() {
+                    break;
+                }
+            }
+            let reloaded = match load() {
+                Ok((loaded, included)) => {
+                    // Includes may have been added or removed.
+                    files = Files::new(watched(included));
+                    Ok(loaded)
+                }
+                Err(e) => {
+                    crate::utils::log::append_error(&format!("Config reload failed: {e}"));
+                    Err(e)
+                }
+            };
+            if tx.send(reloaded).is_err() {
+                return;
+            }
+        }
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn detects_changed_added_and_removed_files() {
+        let dir = std::env::temp_dir().join(format!("ps-watch-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let (a, b) = (dir.join("a.yml"), dir.join("b.yml"));
+        fs::write(&a, "clients: []\n").unwrap();
+
+        let mut files = Files::new(vec![a.clone(), b.clone()]);
+        assert!(!files.changed());
+        fs::write(&a, "clients: [] # edited\n").unwrap();
+        assert!(files.changed());
+        assert!(!files.changed());
+        fs::write(&b, "").unwrap();
+        assert!(files.changed());
+        fs::remove_file(&a).unwrap();
+        assert!(files.changed());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
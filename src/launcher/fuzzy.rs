@@ -0,0 +1,167 @@
+//! Scored fuzzy matching for launcher items.
+//!
+//! The query's characters must appear in order in the candidate
+//! (case-insensitive). Among all such alignments the best-scoring one wins:
+//! matches at the start of the candidate or of a word (after `-`, `_`, space,
+//! `.`, `/`, `\`, `:`, a lower→upper case change or a letter↔digit change)
+//! and runs of consecutive matches score higher, gaps between matches cost a
+//! little. So `ga` prefers `github-actions` over `gitlab`, and `git` prefers
+//! `github` over `digit`.
+
+use std::ops::Range;
+
+const SCORE_MATCH: i32 = 16;
+const BONUS_BOUNDARY: i32 = 24;
+/// On top of [`BONUS_BOUNDARY`] for the candidate's first character.
+const BONUS_PREFIX: i32 = 16;
+const BONUS_CONSECUTIVE: i32 = 16;
+/// Whole candidate typed (ignoring case).
+const BONUS_EXACT: i32 = 64;
+/// Per skipped character between two matched ones.
+const PENALTY_GAP: i32 = 2;
+
+/// A successful match: its score and the byte ranges of `candidate` that
+/// matched, for highlighting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    pub score: i32,
+    pub ranges: Vec<Range<usize>>,
+}
+
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Bonus for a match at `chars[j]` for being at a word start.
+fn boundary_bonus(chars: &[(usize, char)], j: usize) -> i32 {
+    let Some(j_prev) = j.checked_sub(1) else {
+        return BONUS_BOUNDARY + BONUS_PREFIX;
+    };
+    let (prev, cur) = (chars[j_prev].1, chars[j].1);
+    let word_start = matches!(prev, '-' | '_' | ' ' | '.' | '/' | '\\' | ':')
+        || (prev.is_lowercase() && cur.is_uppercase())
+        || (prev.is_alphabetic() && cur.is_ascii_digit())
+        || (prev.is_ascii_digit() && cur.is_alphabetic());
+    if word_start {
+        BONUS_BOUNDARY
+    } else {
+        0
+    }
+}
+
+/// Score `query` against `candidate`, or `None` when the query's characters
+/// don't all appear in order. An empty query matches everything with score 0.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<Match> {
+    let query: Vec<char> = query.chars().map(fold).collect();
+    if query.is_empty() {
+        return Some(Match {
+            score: 0,
+            ranges: Vec::new(),
+        });
+    }
+    let chars: Vec<(usize, char)> = candidate.char_indices().collect();
+    let (m, n) = (query.len(), chars.len());
+    if m > n {
+        return None;
+    }
+
+    // best[i][j]: best score with query[..=i] matched and query[i] at chars[j];
+    // from[i][j]: where query[i - 1] matched on that best path.
+    let mut best = vec![vec![None::<i32>; n]; m];
+    let mut from = vec![vec![0usize; n]; m];
+    for (i, &q) in query.iter().enumerate() {
+        // Best predecessor score so far, already charged for the gap up to j.
+        let mut carry: Option<(i32, usize)> = None;
+        for j in 0..n {
+            if i > 0 && j > 0 {
+                carry = match (carry, best[i - 1][j - 1]) {
+                    (Some((c, k)), Some(p)) if c - PENALTY_GAP >= p => Some((c - PENALTY_GAP, k)),
+                    (_, Some(p)) => Some((p, j - 1)),
+                    (Some((c, k)), None) => Some((c - PENALTY_GAP, k)),
+                    (None, None) => None,
+                };
+            }
+            if fold(chars[j].1) != q {
+                continue;
+            }
+            let gain = SCORE_MATCH + boundary_bonus(&chars, j);
+            if i == 0 {
+                best[i][j] = Some(gain);
+                continue;
+            }
+            let consecutive = j
+                .checked_sub(1)
+                .and_then(|k| best[i - 1][k].map(|p| (p + BONUS_CONSECUTIVE, k)));
+            let candidates = [carry, consecutive];
+            if let Some((score, k)) = candidates.into_iter().flatten().max_by_key(|(s, _)| *s) {
+                best[i][j] = Some(score + gain);
+                from[i][j] = k;
+            }
+        }
+    }
+
+    let (mut score, end) = (0..n)
+        .filter_map(|j| best[m - 1][j].map(|s| (s, j)))
+        .max()?;
+    if m == n {
+        score += BONUS_EXACT;
+    }
+
+    let mut positions = vec![end; m];
+    for i in (1..m).rev() {
+        positions[i - 1] = from[i][positions[i]];
+    }
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for j in positions {
+        let (start, c) = chars[j];
+        let end = start + c.len_utf8();
+        match ranges.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ => ranges.push(start..end),
+        }
+    }
+    Some(Match { score, ranges })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(query: &str, candidate: &str) -> i32 {
+        fuzzy_match(query, candidate)
+            .unwrap_or_else(|| panic!("'{}' should match '{}'", query, candidate))
+            .score
+    }
+
+    #[test]
+    fn requires_characters_in_order() {
+        assert!(fuzzy_match("gha", "github-actions").is_some());
+        assert!(fuzzy_match("GH", "github").is_some());
+        assert!(fuzzy_match("hg", "github").is_none());
+        assert!(fuzzy_match("githubs", "github").is_none());
+    }
+
+    #[test]
+    fn acronyms_beat_scattered_matches() {
+        assert!(score("ga", "github-actions") > score("ga", "gitlab"));
+        assert!(score("vsc", "Visual Studio Code") > score("vsc", "avast-scanner"));
+        assert!(score("gA", "gitActions") > score("ga", "gitlab"));
+    }
+
+    #[test]
+    fn prefix_and_exact_rank_first() {
+        assert!(score("git", "github") > score("git", "digit"));
+        assert!(score("jira", "jira") > score("jira", "jira-board"));
+    }
+
+    #[test]
+    fn ranges_cover_matched_bytes_merging_runs() {
+        let m = fuzzy_match("ga", "github-actions").unwrap();
+        assert_eq!(m.ranges, [0..1, 7..8]);
+        let m = fuzzy_match("hub", "github").unwrap();
+        assert_eq!(m.ranges, vec![3..6]);
+        // Byte offsets, not char indices.
+        let m = fuzzy_match("cf", "Café Finder").unwrap();
+        assert_eq!(m.ranges, [0..1, 6..7]);
+    }
+}
@@ -1,17 +1,42 @@
 //! Shared launcher data model — filtering, matching, item types.
 //! Used by both the CLI `list` command and the windowed GUI launcher.
 
+pub mod fuzzy;
+
+use std::collections::HashMap;
+
 /// The kind of item in the launcher list.
 #[derive(Debug, Clone, PartialEq)]
 pub enum ListItemKind {
     Command,
-    Shortcut { path: String },
+    Shortcut {
+        path: String,
+    },
+    /// An item from a [`crate::providers::ItemProvider`]; `data` is whatever
+    /// the provider needs to run it.
+    Provided {
+        provider: String,
+        data: String,
+    },
+}
+
+impl ListItemKind {
+    /// Name of the provider that made the item; `None` for config commands.
+    pub fn provider(&self) -> Option<&str> {
+        match self {
+            ListItemKind::Command => None,
+            ListItemKind::Shortcut { .. } => Some("shortcuts"),
+            ListItemKind::Provided { provider, .. } => Some(provider),
+        }
+    }
 }
 
 /// A single item in the launcher list.
 #[derive(Debug, Clone, PartialEq)]
 pub struct ListItem {
     pub key: String,
+    /// Other names that find and run this item (a command's `aliases`).
+    pub aliases: Vec<String>,
     pub display_detail: String,
     pub kind: ListItemKind,
     /// Whether this command is pinned to the top of the recent list.
@@ -19,19 +44,175 @@ pub struct ListItem {
 }
 
 impl ListItem {
-    pub fn matches(&self, query: &str) -> bool {
-        self.key.to_lowercase().contains(&query.to_lowercase())
+    /// Best [`fuzzy::fuzzy_match`] of `query` against the key or an alias.
+    /// The ranges index into the key, and are empty when an alias matched
+    /// better.
+    pub fn fuzzy_match(&self, query: &str) -> Option<fuzzy::Match> {
+        let key_match = fuzzy::fuzzy_match(query, &self.key);
+        let alias_score = self
+            .aliases
+            .iter()
+            .filter_map(|alias| fuzzy::fuzzy_match(query, alias))
+            .map(|m| m.score)
+            .max();
+        match (key_match, alias_score) {
+            (Some(m), Some(score)) if score > m.score => Some(fuzzy::Match {
+                score,
+                ranges: Vec::new(),
+            }),
+            (Some(m), _) => Some(m),
+            (None, score) => score.map(|score| fuzzy::Match {
+                score,
+                ranges: Vec::new(),
+            }),
+        }
+    }
+
+    /// Whether `name` is the key or an alias (case-insensitive).
+    pub fn is_named(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.names().any(|n| n.to_lowercase() == name)
+    }
+
+    fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.key.as_str()).chain(self.aliases.iter().map(String::as_str))
+    }
+}
+
+/// How much each key has been used, normalised to `0.0..=1.0`. Blended into
+/// match scores so a frequently used item wins a near-tie, but never beats a
+/// clearly better match.
+#[derive(Debug, Clone, Default)]
+pub struct Usage {
+    weights: HashMap<String, f64>,
+}
+
+/// Score bonus for the most used key; about one word-boundary match.
+const USAGE_BONUS: f64 = 24.0;
+
+impl Usage {
+    /// Weights from recent keys (most recent first): the most recent counts
+    /// fully, the oldest least.
+    pub fn from_recent(recent_keys: &[String]) -> Self {
+        let n = recent_keys.len() as f64;
+        let mut weights = HashMap::new();
+        for (i, key) in recent_keys.iter().enumerate() {
+            weights.entry(key.clone()).or_insert(1.0 - i as f64 / n);
+        }
+        Self { weights }
+    }
+
+    fn bonus(&self, key: &str) -> i32 {
+        let weight = self.weights.get(key).copied().unwrap_or(0.0);
+        (weight * USAGE_BONUS).round() as i32
     }
 }
 
 /// URL-encode user arguments while preserving path separators (slashes).
 pub fn encode_url_args(url: &str, user_args: &str) -> String {
-    let encoded: String = user_args
-        .split('/')
+    format!("{}{}", url, encode_segments(user_args))
+}
+
+fn encode_segments(text: &str) -> String {
+    text.split('/')
         .map(|segment| urlencoding::encode(segment).into_owned())
         .collect::<Vec<_>>()
-        .join("/");
-    format!("{}{}", url, encoded)
+        .join("/")
+}
+
+/// A `{0}`/`{1}`/… (one whitespace-separated user argument) or `{query}` (all
+/// of them) parameter in a command URL or command line, optionally with a
+/// `?default` used when the argument is missing.
+struct TemplateParam<'a> {
+    span: std::ops::Range<usize>,
+    index: Option<usize>,
+    default: Option<&'a str>,
+}
+
+fn template_params(template: &str) -> Vec<TemplateParam<'_>> {
+    let mut params = Vec::new();
+    let mut from = 0;
+    while let Some(open) = template[from..].find('{').map(|i| from + i) {
+        from = open + 1;
+        // `${...}` belongs to placeholder interpolation, not to parameters.
+        if template[..open].ends_with('$') {
+            continue;
+        }
+        let Some(close) = template[open..].find('}').map(|i| open + i) else {
+            break;
+        };
+        let inner = &template[open + 1..close];
+        let (name, default) = match inner.split_once('?') {
+            Some((name, default)) => (name, Some(default)),
+            None => (inner, None),
+        };
+        let index = match name {
+            "query" => None,
+            _ if !name.is_empty() && name.bytes().all(|b| b.is_ascii_digit()) => {
+                match name.parse() {
+                    Ok(i) => Some(i),
+                    Err(_) => continue,
+                }
+            }
+            _ => continue,
+        };
+        params.push(TemplateParam {
+            span: open..close + 1,
+            index,
+            default,
+        });
+        from = close + 1;
+    }
+    params
+}
+
+/// `text` cut to at most `max` characters, ending in `...` when cut. Counts
+/// chars rather than bytes, so a detail with `→` or accents can't be split
+/// inside a character.
+pub fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(max.saturating_sub(3)).collect();
+    format!("{}...", kept)
+}
+
+/// Fill the `{0}`, `{query}`, `{name?default}` parameters of `template` from
+/// the user arguments, URL-encoding each value per path segment when
+/// `encode` is set. Returns `Ok(None)` when the template has no parameters
+/// (args are then appended as before) and `Err` naming the first parameter
+/// with neither an argument nor a default.
+pub fn fill_template(
+    template: &str,
+    user_args: Option<&str>,
+    encode: bool,
+) -> Result<Option<String>, String> {
+    let params = template_params(template);
+    if params.is_empty() {
+        return Ok(None);
+    }
+    let query = user_args.unwrap_or("").trim();
+    let words: Vec<&str> = query.split_whitespace().collect();
+
+    let mut filled = String::with_capacity(template.len() + query.len());
+    let mut last = 0;
+    for param in params {
+        filled.push_str(&template[last..param.span.start]);
+        let value = match param.index {
+            Some(i) => words.get(i).copied(),
+            None => Some(query).filter(|q| !q.is_empty()),
+        }
+        .or(param.default)
+        .ok_or_else(|| template[param.span.clone()].to_string())?;
+        if encode {
+            filled.push_str(&encode_segments(value));
+        } else {
+            filled.push_str(value);
+        }
+        last = param.span.end;
+    }
+    filled.push_str(&template[last..]);
+    Ok(Some(filled))
 }
 
 /// Merge command-level args with user-supplied args.
@@ -216,8 +397,8 @@ pub fn get_path_entries(input: &str) -> Vec<PathEntry> {
             }
         }
 
-        dirs.sort_by(|a, b| a.1.to_lowercase().cmp(&b.1.to_lowercase()));
-        files.sort_by(|a, b| a.1.to_lowercase().cmp(&b.1.to_lowercase()));
+        dirs.sort_by_key(|a| a.1.to_lowercase());
+        files.sort_by_key(|a| a.1.to_lowercase());
 
         for (full, _) in &dirs {
             // Without trailing \ = open the directory
@@ -253,6 +434,22 @@ pub fn get_path_entries(input: &str) -> Vec<PathEntry> {
     result
 }
 
+/// Map recent keys recorded under a command's alias (before it became one)
+/// to the command's key, keeping only the most recent entry per command.
+fn canonical_recent_keys(recent_keys: &[String], items: &[ListItem]) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::with_capacity(recent_keys.len());
+    for key in recent_keys {
+        let key = items
+            .iter()
+            .find(|item| item.key != *key && item.aliases.iter().any(|a| a == key))
+            .map_or(key, |item| &item.key);
+        if !keys.contains(key) {
+            keys.push(key.clone());
+        }
+    }
+    keys
+}
+
 /// Reorder recent keys for empty-input display. The result is, in order:
 /// accessed pinned commands (by recency), pinned commands never accessed
 /// (by config order), then the remaining recents (by recency).
@@ -263,6 +460,7 @@ pub fn get_path_entries(input: &str) -> Vec<PathEntry> {
 /// with no history entry. Used by both the CLI list view and the GUI launcher
 /// so their empty-input ordering stays identical.
 pub fn order_recent_keys(recent_keys: &[String], items: &[ListItem]) -> Vec<String> {
+    let recent_keys = &canonical_recent_keys(recent_keys, items);
     let is_pinned = |key: &String| items.iter().any(|item| &item.key == key && item.pinned);
 
     let mut accessed_pinned: Vec<String> = Vec::new();
@@ -287,39 +485,55 @@ pub fn order_recent_keys(recent_keys: &[String], items: &[ListItem]) -> Vec<Stri
     ordered
 }
 
-/// Filter a list of items by query string, returning matching items in order.
-/// When the query contains a space (i.e. keyword + args), use exact key match
-/// so that "g some text" only matches a "g" key, not everything containing "g".
-pub fn filter_items<'a>(items: &'a [ListItem], query: &str) -> Vec<&'a ListItem> {
+/// Filter a list of items by query string. An empty query keeps every item
+/// in order; otherwise items are fuzzy matched on key and aliases and ranked
+/// by match score plus [`Usage`], ties keeping the shorter key, then list
+/// order. When the query contains a space (i.e. keyword + args), use exact
+/// key match so that "g some text" only matches a "g" key, not everything
+/// containing "g".
+pub fn filter_items<'a>(items: &'a [ListItem], query: &str, usage: &Usage) -> Vec<&'a ListItem> {
     if query.is_empty() {
-        items.iter().collect()
-    } else {
-        let keyword = query.split_whitespace().next().unwrap_or(query);
-        let has_args = query.contains(' ');
-        items
-            .iter()
-            .filter(|item| {
-                if has_args {
-                    item.key.to_lowercase() == keyword.to_lowercase()
-                } else {
-                    item.matches(keyword)
-                }
-            })
-            .collect()
+        return items.iter().collect();
+    }
+    let keyword = query.split_whitespace().next().unwrap_or(query);
+    if query.contains(' ') {
+        return items.iter().filter(|item| item.is_named(keyword)).collect();
     }
+    let mut ranked: Vec<(i32, &ListItem)> = items
+        .iter()
+        .filter_map(|item| {
+            let m = item.fuzzy_match(keyword)?;
+            Some((m.score + usage.bonus(&item.key), item))
+        })
+        .collect();
+    ranked.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .cmp(a_score)
+            .then_with(|| a.key.len().cmp(&b.key.len()))
+    });
+    ranked.into_iter().map(|(_, item)| item).collect()
+}
+
+/// The item whose key is `name`, else the first with `name` as an alias
+/// (case-insensitive), so a key is never shadowed by another item's alias.
+pub fn find_named<'a>(items: &'a [ListItem], name: &str) -> Option<&'a ListItem> {
+    let lower = name.to_lowercase();
+    items
+        .iter()
+        .find(|item| item.key.to_lowercase() == lower)
+        .or_else(|| items.iter().find(|item| item.is_named(name)))
 }
 
-/// Find the best matching item for a given input.
+/// Find the best matching item for a given input: an exact key or alias,
+/// else the top of [`filter_items`].
 /// Returns the matched item and any remaining arguments.
 pub fn resolve_item<'a>(
     items: &'a [ListItem],
     input: &str,
+    usage: &Usage,
 ) -> Option<(&'a ListItem, Option<String>)> {
     // Try exact match on full input (handles multi-word keys like shortcuts)
-    if let Some(item) = items
-        .iter()
-        .find(|item| item.key.to_lowercase() == input.to_lowercase())
-    {
+    if let Some(item) = find_named(items, input) {
         return Some((item, None));
     }
 
@@ -340,22 +554,14 @@ pub fn resolve_item<'a>(
     };
 
     // Try exact match on keyword
-    if let Some(item) = items
-        .iter()
-        .find(|item| item.key.to_lowercase() == keyword.to_lowercase())
-    {
+    if let Some(item) = find_named(items, &keyword) {
         return Some((item, args));
     }
 
-    // Partial match fallback
-    if let Some(item) = items
-        .iter()
-        .find(|item| item.key.to_lowercase().contains(&keyword.to_lowercase()))
-    {
-        return Some((item, args));
-    }
-
-    None
+    // Best fuzzy match, ranked like the launcher list
+    filter_items(items, &keyword, usage)
+        .first()
+        .map(|item| (*item, args))
 }
 
 #[cfg(test)]
@@ -366,24 +572,28 @@ mod tests {
         vec![
             ListItem {
                 key: "github".to_string(),
+                aliases: Vec::new(),
                 display_detail: "https://github.com/".to_string(),
                 kind: ListItemKind::Command,
                 pinned: false,
             },
             ListItem {
                 key: "jira".to_string(),
+                aliases: Vec::new(),
                 display_detail: "https://jira.example.com/".to_string(),
                 kind: ListItemKind::Command,
                 pinned: false,
             },
             ListItem {
                 key: "slack".to_string(),
+                aliases: Vec::new(),
                 display_detail: "https://slack.com/".to_string(),
                 kind: ListItemKind::Command,
                 pinned: false,
             },
             ListItem {
                 key: "Visual Studio Code".to_string(),
+                aliases: Vec::new(),
                 display_detail: "C:\\Program Files\\Code.exe".to_string(),
                 kind: ListItemKind::Shortcut {
                     path: "C:\\ProgramData\\Start Menu\\Visual Studio Code.lnk".to_string(),
@@ -393,6 +603,85 @@ mod tests {
         ]
     }
 
+    #[test]
+    fn truncate_counts_chars() {
+        assert_eq!(truncate("build → test", 12), "build → test");
+        assert_eq!(truncate("build → test", 9), "build ...");
+        assert_eq!(truncate("a → b", 4), "a...");
+    }
+
+    // --- fill_template ---
+
+    #[test]
+    fn fill_template_positional_params_mid_url() {
+        assert_eq!(
+            fill_template(
+                "https://jira.example.com/browse/{0}/comments",
+                Some("ABC-1"),
+                true
+            ),
+            Ok(Some(
+                "https://jira.example.com/browse/ABC-1/comments".to_string()
+            ))
+        );
+        assert_eq!(
+            fill_template("https://x.dev/{1}/{0}", Some("a b"), true),
+            Ok(Some("https://x.dev/b/a".to_string()))
+        );
+    }
+
+    #[test]
+    fn fill_template_query_and_defaults() {
+        assert_eq!(
+            fill_template(
+                "https://s.dev/?q={query}&p={1?1}",
+                Some("hello world"),
+                true
+            ),
+            Ok(Some("https://s.dev/?q=hello%20world&p=world".to_string()))
+        );
+        assert_eq!(
+            fill_template("https://s.dev/?q={query?rust}", None, true),
+            Ok(Some("https://s.dev/?q=rust".to_string()))
+        );
+    }
+
+    #[test]
+    fn fill_template_command_is_not_encoded() {
+        assert_eq!(
+            fill_template(
+                "git checkout {0} && git log {query}",
+                Some("main -3"),
+                false
+            ),
+            Ok(Some("git checkout main && git log main -3".to_string()))
+        );
+    }
+
+    #[test]
+    fn fill_template_missing_param_is_named() {
+        assert_eq!(
+            fill_template("https://x.dev/{0}/{1}", Some("a"), true),
+            Err("{1}".to_string())
+        );
+    }
+
+    #[test]
+    fn fill_template_ignores_other_braces() {
+        assert_eq!(
+            fill_template("https://x.dev/?q=", Some("a"), true),
+            Ok(None)
+        );
+        assert_eq!(
+            fill_template("echo ${env:HOME} {name} {}", None, false),
+            Ok(None)
+        );
+        assert_eq!(
+            fill_template("echo ${0} {0}", Some("a"), false),
+            Ok(Some("echo ${0} a".to_string()))
+        );
+    }
+
     // --- encode_url_args ---
 
     #[test]
@@ -611,14 +900,14 @@ mod tests {
     #[test]
     fn filter_items_empty_query_returns_all() {
         let items = sample_items();
-        let filtered = filter_items(&items, "");
+        let filtered = filter_items(&items, "", &Usage::default());
         assert_eq!(filtered.len(), 4);
     }
 
     #[test]
     fn filter_items_exact_match() {
         let items = sample_items();
-        let filtered = filter_items(&items, "github");
+        let filtered = filter_items(&items, "github", &Usage::default());
         assert_eq!(filtered.len(), 1);
         assert_eq!(filtered[0].key, "github");
     }
@@ -626,7 +915,7 @@ mod tests {
     #[test]
     fn filter_items_partial_match() {
         let items = sample_items();
-        let filtered = filter_items(&items, "git");
+        let filtered = filter_items(&items, "git", &Usage::default());
         assert_eq!(filtered.len(), 1);
         assert_eq!(filtered[0].key, "github");
     }
@@ -634,7 +923,7 @@ mod tests {
     #[test]
     fn filter_items_case_insensitive() {
         let items = sample_items();
-        let filtered = filter_items(&items, "JIRA");
+        let filtered = filter_items(&items, "JIRA", &Usage::default());
         assert_eq!(filtered.len(), 1);
         assert_eq!(filtered[0].key, "jira");
     }
@@ -642,14 +931,14 @@ mod tests {
     #[test]
     fn filter_items_no_match() {
         let items = sample_items();
-        let filtered = filter_items(&items, "nonexistent");
+        let filtered = filter_items(&items, "nonexistent", &Usage::default());
         assert_eq!(filtered.len(), 0);
     }
 
     #[test]
     fn filter_items_with_args_filters_by_keyword_only() {
         let items = sample_items();
-        let filtered = filter_items(&items, "github staff0rd/repo");
+        let filtered = filter_items(&items, "github staff0rd/repo", &Usage::default());
         assert_eq!(filtered.len(), 1);
         assert_eq!(filtered[0].key, "github");
     }
@@ -660,11 +949,12 @@ mod tests {
         let mut items = sample_items();
         items.push(ListItem {
             key: "g".to_string(),
+            aliases: Vec::new(),
             display_detail: "https://google.com/search?q=".to_string(),
             kind: ListItemKind::Command,
             pinned: false,
         });
-        let filtered = filter_items(&items, "g some text");
+        let filtered = filter_items(&items, "g some text", &Usage::default());
         assert_eq!(filtered.len(), 1);
         assert_eq!(filtered[0].key, "g");
     }
@@ -674,7 +964,7 @@ mod tests {
     #[test]
     fn resolve_item_exact_match() {
         let items = sample_items();
-        let (item, args) = resolve_item(&items, "github").unwrap();
+        let (item, args) = resolve_item(&items, "github", &Usage::default()).unwrap();
         assert_eq!(item.key, "github");
         assert_eq!(args, None);
     }
@@ -682,7 +972,7 @@ mod tests {
     #[test]
     fn resolve_item_with_args() {
         let items = sample_items();
-        let (item, args) = resolve_item(&items, "github staff0rd/repo").unwrap();
+        let (item, args) = resolve_item(&items, "github staff0rd/repo", &Usage::default()).unwrap();
         assert_eq!(item.key, "github");
         assert_eq!(args, Some("staff0rd/repo".to_string()));
     }
@@ -693,11 +983,12 @@ mod tests {
         let mut items = sample_items();
         items.push(ListItem {
             key: "g".to_string(),
+            aliases: Vec::new(),
             display_detail: "https://google.com/search?q=".to_string(),
             kind: ListItemKind::Command,
             pinned: false,
         });
-        let (item, args) = resolve_item(&items, "g some text").unwrap();
+        let (item, args) = resolve_item(&items, "g some text", &Usage::default()).unwrap();
         assert_eq!(item.key, "g");
         assert_eq!(args, Some("some text".to_string()));
     }
@@ -705,7 +996,7 @@ mod tests {
     #[test]
     fn resolve_item_multi_word_key() {
         let items = sample_items();
-        let (item, args) = resolve_item(&items, "Visual Studio Code").unwrap();
+        let (item, args) = resolve_item(&items, "Visual Studio Code", &Usage::default()).unwrap();
         assert_eq!(item.key, "Visual Studio Code");
         assert_eq!(args, None);
     }
@@ -713,21 +1004,56 @@ mod tests {
     #[test]
     fn resolve_item_partial_match() {
         let items = sample_items();
-        let (item, _) = resolve_item(&items, "git").unwrap();
+        let (item, _) = resolve_item(&items, "git", &Usage::default()).unwrap();
         assert_eq!(item.key, "github");
     }
 
     #[test]
     fn resolve_item_case_insensitive() {
         let items = sample_items();
-        let (item, _) = resolve_item(&items, "GITHUB").unwrap();
+        let (item, _) = resolve_item(&items, "GITHUB", &Usage::default()).unwrap();
         assert_eq!(item.key, "github");
     }
 
     #[test]
     fn resolve_item_no_match() {
         let items = sample_items();
-        assert!(resolve_item(&items, "nonexistent").is_none());
+        assert!(resolve_item(&items, "nonexistent", &Usage::default()).is_none());
+    }
+
+    fn aliased_items() -> Vec<ListItem> {
+        let mut github = cmd_item("github", false);
+        github.aliases = vec!["gh".to_string(), "repo".to_string()];
+        let mut jira = cmd_item("jira", false);
+        jira.aliases = vec!["github-issues".to_string()];
+        vec![github, jira, cmd_item("gh", false)]
+    }
+
+    #[test]
+    fn resolve_item_by_alias_with_args() {
+        let items = aliased_items();
+        let (item, args) = resolve_item(&items, "REPO rust-lang/rust", &Usage::default()).unwrap();
+        assert_eq!(item.key, "github");
+        assert_eq!(args.as_deref(), Some("rust-lang/rust"));
+        // A key is never shadowed by another item's alias.
+        assert_eq!(
+            resolve_item(&items, "gh", &Usage::default()).unwrap().0.key,
+            "gh"
+        );
+    }
+
+    #[test]
+    fn filter_items_matches_aliases() {
+        let items = aliased_items();
+        let keys = |query| -> Vec<String> {
+            filter_items(&items, query, &Usage::default())
+                .iter()
+                .map(|i| i.key.clone())
+                .collect()
+        };
+        assert_eq!(keys("rep"), ["github"]);
+        assert_eq!(keys("issues"), ["jira"]);
+        assert_eq!(keys("repo x"), ["github"]);
     }
 
     // --- order_recent_keys ---
@@ -735,12 +1061,22 @@ mod tests {
     fn cmd_item(key: &str, pinned: bool) -> ListItem {
         ListItem {
             key: key.to_string(),
+            aliases: Vec::new(),
             display_detail: format!("https://{}/", key),
             kind: ListItemKind::Command,
             pinned,
         }
     }
 
+    #[test]
+    fn order_recent_keys_folds_aliases_into_the_key() {
+        let mut github = cmd_item("github", false);
+        github.aliases = vec!["gh".to_string()];
+        let items = vec![github, cmd_item("jira", false)];
+        let recent = vec!["gh".to_string(), "jira".to_string(), "github".to_string()];
+        assert_eq!(order_recent_keys(&recent, &items), ["github", "jira"]);
+    }
+
     #[test]
     fn order_recent_keys_no_pins_preserves_order() {
         let items = vec![cmd_item("a", false), cmd_item("b", false)];
@@ -829,19 +1165,72 @@ mod tests {
         );
     }
 
-    // --- ListItem::matches ---
+    // --- ListItem::fuzzy_match ---
 
     #[test]
     fn list_item_matches_case_insensitive() {
         let item = ListItem {
             key: "GitHub".to_string(),
+            aliases: Vec::new(),
             display_detail: String::new(),
             kind: ListItemKind::Command,
             pinned: false,
         };
-        assert!(item.matches("github"));
-        assert!(item.matches("Git"));
-        assert!(item.matches("GITHUB"));
-        assert!(!item.matches("jira"));
+        assert!(item.fuzzy_match("github").is_some());
+        assert!(item.fuzzy_match("Git").is_some());
+        assert!(item.fuzzy_match("GITHUB").is_some());
+        assert!(item.fuzzy_match("jira").is_none());
+    }
+
+    #[test]
+    fn list_item_fuzzy_match_uses_best_of_key_and_aliases() {
+        let item = ListItem {
+            key: "github-actions".to_string(),
+            aliases: vec!["ci".to_string()],
+            display_detail: String::new(),
+            kind: ListItemKind::Command,
+            pinned: false,
+        };
+        assert_eq!(item.fuzzy_match("ga").unwrap().ranges, [0..1, 7..8]);
+        // An alias match has nothing to highlight in the key.
+        assert!(item.fuzzy_match("ci").unwrap().ranges.is_empty());
+    }
+
+    // --- ranking ---
+
+    fn keys(items: &[&ListItem]) -> Vec<String> {
+        items.iter().map(|i| i.key.clone()).collect()
+    }
+
+    #[test]
+    fn filter_items_ranks_by_score() {
+        let items = vec![cmd_item("gitlab", false), cmd_item("github-actions", false)];
+        let result = filter_items(&items, "ga", &Usage::default());
+        assert_eq!(keys(&result), ["github-actions", "gitlab"]);
+    }
+
+    #[test]
+    fn filter_items_usage_breaks_near_ties() {
+        let items = vec![cmd_item("grafana", false), cmd_item("gitlab", false)];
+        let result = filter_items(&items, "g", &Usage::default());
+        assert_eq!(keys(&result), ["gitlab", "grafana"]);
+        let usage = Usage::from_recent(&["grafana".to_string()]);
+        let result = filter_items(&items, "g", &usage);
+        assert_eq!(keys(&result), ["grafana", "gitlab"]);
+    }
+
+    #[test]
+    fn filter_items_usage_does_not_beat_a_clearly_better_match() {
+        let items = vec![cmd_item("digit", false), cmd_item("github", false)];
+        let usage = Usage::from_recent(&["digit".to_string()]);
+        let result = filter_items(&items, "git", &usage);
+        assert_eq!(keys(&result), ["github", "digit"]);
+    }
+
+    #[test]
+    fn resolve_item_prefers_acronym_match() {
+        let items = vec![cmd_item("gitlab", false), cmd_item("github-actions", false)];
+        let (item, _) = resolve_item(&items, "ga", &Usage::default()).unwrap();
+        assert_eq!(item.key, "github-actions");
     }
 }
@@ -1,13 +1,18 @@
 mod commands;
 mod config;
 mod history;
+mod hooks;
 mod hotkey;
 mod icon;
 mod launcher;
+mod providers;
 mod ui;
 mod utils;
 
 use clap::{Parser, Subcommand};
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use utils::output::OutputFormat;
 
 #[derive(Parser)]
 #[command(name = "project-switch")]
@@ -16,14 +21,52 @@ use clap::{Parser, Subcommand};
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+    /// Print machine-readable output (current, list, switch, run --dry-run, env, config,
+    /// history list)
+    #[arg(long, global = true, value_enum)]
+    format: Option<OutputFormat>,
+    /// Shorthand for --format json
+    #[arg(long, global = true, conflicts_with = "format")]
+    json: bool,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Switch between clients
-    Switch,
+    /// Switch between clients (interactive when no client is given)
+    Switch {
+        /// Client name (fuzzy matched), or '-' for the previous selection
+        client: Option<String>,
+        /// Nested project name within the client (fuzzy matched)
+        project: Option<String>,
+        /// Don't run the onLeave/onEnter hooks
+        #[arg(long)]
+        no_hooks: bool,
+    },
     /// Show the current client
     Current,
+    /// Print the path of the current client/project, or of the named one
+    Path {
+        /// Client name (fuzzy matched); defaults to the current selection
+        client: Option<String>,
+        /// Nested project name within the client (fuzzy matched)
+        project: Option<String>,
+    },
+    /// Print the environment variables commands get for the current selection
+    Env {
+        /// Print shell code that sets them (and unsets the previous
+        /// selection's), for the `init` function
+        #[arg(long, value_enum, value_name = "SHELL")]
+        export: Option<commands::init::Shell>,
+    },
+    /// Print a shell function that cds on switch; add
+    /// `eval "$(project-switch init bash)"` to your shell's startup file
+    Init {
+        #[arg(value_enum)]
+        shell: commands::init::Shell,
+        /// Name of the shell function
+        #[arg(long, default_value = "ps")]
+        cmd: String,
+    },
     #[command(hide = true)]
     /// (Deprecated) Open a URL associated with the current client - use 'list' instead
     Open {
@@ -42,6 +85,54 @@ enum Commands {
         #[arg(long)]
         title: Option<String>,
     },
+    /// Run a command, shortcut, URL, file path or =expression without any UI
+    Run {
+        /// Command key, shortcut name, URL, file path or =expression
+        key: String,
+        /// Arguments for the command (appended to its URL or command line);
+        /// put arguments starting with '-' after `--`
+        args: Vec<String>,
+        /// Print the resolved action instead of launching it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Add, remove, rename or import clients in the config
+    Client {
+        /// Edit an included (shared) config file instead of the local one;
+        /// use --shared=<file> when there are several
+        #[arg(long, global = true, num_args = 0..=1, require_equals = true, value_name = "FILE")]
+        shared: Option<Option<PathBuf>>,
+        #[command(subcommand)]
+        action: commands::edit::ClientAction,
+    },
+    /// Add or remove nested projects in the config
+    Project {
+        /// Edit an included (shared) config file instead of the local one;
+        /// use --shared=<file> when there are several
+        #[arg(long, global = true, num_args = 0..=1, require_equals = true, value_name = "FILE")]
+        shared: Option<Option<PathBuf>>,
+        #[command(subcommand)]
+        action: commands::edit::ProjectAction,
+    },
+    /// Add, remove or change commands in the config
+    Cmd {
+        /// Edit an included (shared) config file instead of the local one;
+        /// use --shared=<file> when there are several
+        #[arg(long, global = true, num_args = 0..=1, require_equals = true, value_name = "FILE")]
+        shared: Option<Option<PathBuf>>,
+        #[command(subcommand)]
+        action: commands::edit::CmdAction,
+    },
+    /// Check the config files
+    Config {
+        #[command(subcommand)]
+        action: commands::config::ConfigAction,
+    },
+    /// List, prune or clear launcher history (per client/project)
+    History {
+        #[command(subcommand)]
+        action: commands::history::HistoryAction,
+    },
     /// List all openable items from the current client (interactive)
     List {
         /// Print the full command and args before executing
@@ -59,12 +150,31 @@ enum Commands {
 fn main() {
     let cli = Cli::parse();
     let gui_mode = matches!(&cli.command, Some(Commands::List { gui: true, .. }));
+    // Run inside shell functions/command substitution: never wait for input.
+    let scripted = matches!(
+        &cli.command,
+        Some(Commands::Path { .. } | Commands::Env { .. } | Commands::Init { .. })
+    );
+    let format = if cli.json {
+        Some(OutputFormat::Json)
+    } else {
+        cli.format
+    };
 
     let result = match cli.command {
         // No subcommand: start the daemon (hotkey + tray + GUI)
         None => hotkey::daemon::run(),
-        Some(Commands::Switch) => commands::switch::execute(),
-        Some(Commands::Current) => commands::current::execute(),
+        Some(Commands::Switch {
+            client,
+            project,
+            no_hooks,
+        }) => commands::switch::execute(client.as_deref(), project.as_deref(), !no_hooks, format),
+        Some(Commands::Current) => commands::current::execute(format),
+        Some(Commands::Path { client, project }) => {
+            commands::path::execute(client.as_deref(), project.as_deref())
+        }
+        Some(Commands::Env { export }) => commands::env::execute(export, format),
+        Some(Commands::Init { shell, cmd }) => commands::init::execute(shell, &cmd),
         #[allow(deprecated)]
         Some(Commands::Open { key }) => commands::open::execute(&key),
         Some(Commands::Webview {
@@ -72,6 +182,16 @@ fn main() {
             monitor,
             title,
         }) => commands::webview::execute(&url, monitor, title.as_deref()),
+        Some(Commands::Run { key, args, dry_run }) => {
+            commands::run::execute(&key, &args, dry_run, format)
+        }
+        Some(Commands::Client { shared, action }) => commands::edit::execute_client(action, shared),
+        Some(Commands::Project { shared, action }) => {
+            commands::edit::execute_project(action, shared)
+        }
+        Some(Commands::Cmd { shared, action }) => commands::edit::execute_cmd(action, shared),
+        Some(Commands::Config { action }) => commands::config::execute(action, format),
+        Some(Commands::History { action }) => commands::history::execute(action, format),
         Some(Commands::List {
             debug,
             gui,
@@ -80,7 +200,7 @@ fn main() {
             if gui {
                 commands::list::execute_gui(monitor)
             } else {
-                commands::list::execute(debug)
+                commands::list::execute(debug, format)
             }
         }
     };
@@ -91,6 +211,9 @@ fn main() {
 
         if gui_mode {
             show_error_dialog(&msg);
+        } else if scripted || format.is_some() || !std::io::stdin().is_terminal() {
+            // Scripts read the exit code; never block waiting for input.
+            eprintln!("Error: {msg}");
         } else {
             eprintln!("\nError: {msg}");
             eprint!("\nPress Enter to exit...");
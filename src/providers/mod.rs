@@ -0,0 +1,301 @@
+//! Launcher item sources besides the config's commands. Each provider
+//! contributes [`ListItem`]s and resolves the ones it made to an [`Action`]
+//! when they're run, so `run --dry-run` and history work for them too.
+//!
+//! A [`Delivery::Sync`] provider is asked for its items before the launcher
+//! opens. A [`Delivery::Background`] one runs on its own thread and its
+//! items are merged into the open window when they arrive — that's how the
+//! slow shortcut scan stays out of the way. The CLI `list` and `run` wait for
+//! every provider.
+
+mod repos;
+mod script;
+
+use crate::commands::list::Action;
+use crate::config::ConfigManager;
+use crate::launcher::{ListItem, ListItemKind};
+use crate::utils::shortcuts;
+use anyhow::Result;
+use std::sync::mpsc::{self, Receiver};
+
+/// When a provider's items are collected for the GUI launcher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    /// Before the window opens; must be fast.
+    Sync,
+    /// On a background thread, appended to the list when done.
+    Background,
+}
+
+/// A source of launcher items.
+pub trait ItemProvider: Send {
+    /// Unique name; items point back to their provider by it (see
+    /// [`ListItemKind::provider`]).
+    fn name(&self) -> &str;
+
+    fn delivery(&self) -> Delivery;
+
+    /// The items to list for `query`: the input being resolved, or empty
+    /// when listing everything for the launcher. A failure is logged and the
+    /// provider skipped.
+    fn items(&self, query: &str) -> Result<Vec<ListItem>>;
+
+    /// The action for one of this provider's items, run with the user `args`
+    /// typed after its key.
+    fn resolve(&self, item: &ListItem, args: Option<&str>) -> Result<Action>;
+}
+
+/// App shortcuts found on disk (Start Menu, `shortcuts.extraPaths`).
+pub struct ShortcutProvider {
+    extra_paths: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl ItemProvider for ShortcutProvider {
+    fn name(&self) -> &str {
+        "shortcuts"
+    }
+
+    fn delivery(&self) -> Delivery {
+        Delivery::Background
+    }
+
+    fn items(&self, _query: &str) -> Result<Vec<ListItem>> {
+        Ok(
+            shortcuts::collect_shortcuts(&self.extra_paths, &self.exclude)
+                .into_iter()
+                .map(|entry| ListItem {
+                    key: entry.name,
+                    aliases: Vec::new(),
+                    display_detail: entry.path.display().to_string(),
+                    kind: ListItemKind::Shortcut {
+                        path: entry.path.display().to_string(),
+                    },
+                    pinned: false,
+                })
+                .collect(),
+        )
+    }
+
+    fn resolve(&self, item: &ListItem, _args: Option<&str>) -> Result<Action> {
+        match &item.kind {
+            ListItemKind::Shortcut { path } => Ok(Action::Launch { path: path.clone() }),
+            _ => anyhow::bail!("'{}' is not a shortcut", item.key),
+        }
+    }
+}
+
+/// The providers enabled by the config: shortcuts, `repos:`, then
+/// `providers:` in order.
+pub fn registered(config_manager: &ConfigManager) -> Vec<Box<dyn ItemProvider>> {
+    let mut providers: Vec<Box<dyn ItemProvider>> = Vec::new();
+    let shortcuts_config = config_manager.get_shortcuts_config();
+    if shortcuts_config.enabled {
+        providers.push(Box::new(ShortcutProvider {
+            extra_paths: shortcuts_config.extra_paths.unwrap_or_default(),
+            exclude: shortcuts_config.exclude.unwrap_or_default(),
+        }));
+    }
+    if let Some(config) = config_manager.get_repos_config() {
+        providers.push(Box::new(repos::RepoProvider::new(
+            config.clone(),
+            config_manager,
+        )));
+    }
+    for config in config_manager.get_providers() {
+        providers.push(Box::new(script::ScriptProvider::new(
+            config.clone(),
+            config_manager,
+        )));
+    }
+    providers
+}
+
+/// One provider's items from a background collection. Empty when it found
+/// nothing or failed, which clears what it listed before.
+pub struct Batch {
+    pub provider: String,
+    pub items: Vec<ListItem>,
+}
+
+/// A provider's items, or none (logged) when it fails.
+fn items_of(provider: &dyn ItemProvider, query: &str) -> Vec<ListItem> {
+    provider.items(query).unwrap_or_else(|e| {
+        let message = format!("Provider '{}' failed: {:#}", provider.name(), e);
+        eprintln!("{}", message);
+        crate::utils::log::append_error(&message);
+        Vec::new()
+    })
+}
+
+/// Every provider's items for `query`, in registration order.
+pub fn collect(providers: &[Box<dyn ItemProvider>], query: &str) -> Vec<ListItem> {
+    providers
+        .iter()
+        .flat_map(|p| items_of(p.as_ref(), query))
+        .collect()
+}
+
+/// A provider's launcher items as a [`Batch`].
+fn batch_of(provider: &dyn ItemProvider) -> Batch {
+    Batch {
+        provider: provider.name().to_string(),
+        items: items_of(provider, ""),
+    }
+}
+
+/// Collect every provider's launcher items on background threads; each
+/// provider's [`Batch`] is sent as it finishes, and the channel disconnects
+/// once all have. Used to refresh an open launcher.
+pub fn refresh(providers: Vec<Box<dyn ItemProvider>>) -> Receiver<Batch> {
+    let (tx, rx) = mpsc::channel();
+    for provider in providers {
+        let tx = tx.clone();
+        std::thread::spawn(move || {
+            let _ = tx.send(batch_of(provider.as_ref()));
+        });
+    }
+    rx
+}
+
+/// Split `providers` by [`Delivery`]: the sync providers' batches now, and a
+/// channel receiving each background provider's [`Batch`] as it finishes
+/// (none when there are no background providers). The channel disconnects
+/// once all have sent.
+pub fn start(providers: Vec<Box<dyn ItemProvider>>) -> (Vec<Batch>, Option<Receiver<Batch>>) {
+    let (background, sync): (Vec<_>, Vec<_>) = providers
+        .into_iter()
+        .partition(|p| p.delivery() == Delivery::Background);
+    let batches = sync.iter().map(|p| batch_of(p.as_ref())).collect();
+    if background.is_empty() {
+        return (batches, None);
+    }
+    (batches, Some(refresh(background)))
+}
+
+/// Resolve `item` with the provider that made it.
+pub fn resolve(
+    providers: &[Box<dyn ItemProvider>],
+    item: &ListItem,
+    args: Option<&str>,
+) -> Result<Action> {
+    let name = item
+        .kind
+        .provider()
+        .ok_or_else(|| anyhow::anyhow!("'{}' doesn't come from a provider", item.key))?;
+    providers
+        .iter()
+        .find(|p| p.name() == name)
+        .ok_or_else(|| anyhow::anyhow!("Provider '{}' of '{}' is not enabled", name, item.key))?
+        .resolve(item, args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Items `key` → `Open { url: base + key }`, or an error when `fail`.
+    struct Fake {
+        name: &'static str,
+        delivery: Delivery,
+        keys: &'static [&'static str],
+        fail: bool,
+    }
+
+    impl ItemProvider for Fake {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn delivery(&self) -> Delivery {
+            self.delivery
+        }
+
+        fn items(&self, _query: &str) -> Result<Vec<ListItem>> {
+            if self.fail {
+                anyhow::bail!("unreachable");
+            }
+            Ok(self
+                .keys
+                .iter()
+                .map(|key| ListItem {
+                    key: key.to_string(),
+                    aliases: Vec::new(),
+                    display_detail: String::new(),
+                    kind: ListItemKind::Provided {
+                        provider: self.name.to_string(),
+                        data: format!("https://{}/{}", self.name, key),
+                    },
+                    pinned: false,
+                })
+                .collect())
+        }
+
+        fn resolve(&self, item: &ListItem, args: Option<&str>) -> Result<Action> {
+            let ListItemKind::Provided { data, .. } = &item.kind else {
+                anyhow::bail!("not mine");
+            };
+            Ok(Action::Open {
+                url: format!("{}{}", data, args.unwrap_or("")),
+                browser: "default".to_string(),
+            })
+        }
+    }
+
+    fn fake(
+        name: &'static str,
+        delivery: Delivery,
+        keys: &'static [&'static str],
+        fail: bool,
+    ) -> Box<dyn ItemProvider> {
+        Box::new(Fake {
+            name,
+            delivery,
+            keys,
+            fail,
+        })
+    }
+
+    fn keys(items: &[ListItem]) -> Vec<&str> {
+        items.iter().map(|i| i.key.as_str()).collect()
+    }
+
+    #[test]
+    fn start_returns_sync_items_and_streams_background_ones() {
+        let (batches, rx) = start(vec![
+            fake("tickets", Delivery::Background, &["T-1", "T-2"], false),
+            fake("deploys", Delivery::Sync, &["prod"], false),
+            fake("broken", Delivery::Sync, &["x"], true),
+        ]);
+        let names: Vec<&str> = batches.iter().map(|b| b.provider.as_str()).collect();
+        assert_eq!(names, ["deploys", "broken"]);
+        assert_eq!(keys(&batches[0].items), ["prod"]);
+        // A failing provider still sends, empty, to clear what it listed.
+        assert!(batches[1].items.is_empty());
+        let streamed: Vec<ListItem> = rx.unwrap().iter().flat_map(|b| b.items).collect();
+        assert_eq!(keys(&streamed), ["T-1", "T-2"]);
+
+        let (_, rx) = start(vec![fake("deploys", Delivery::Sync, &["prod"], false)]);
+        assert!(rx.is_none());
+    }
+
+    #[test]
+    fn resolve_dispatches_to_the_items_provider() {
+        let providers = vec![
+            fake("deploys", Delivery::Sync, &["prod"], false),
+            fake("tickets", Delivery::Sync, &["T-1"], false),
+        ];
+        let items = collect(&providers, "");
+        let action = resolve(&providers, &items[1], Some("?x")).unwrap();
+        assert_eq!(
+            action,
+            Action::Open {
+                url: "https://tickets/T-1?x".to_string(),
+                browser: "default".to_string(),
+            }
+        );
+
+        let err = resolve(&providers[..1], &items[1], None).unwrap_err();
+        assert!(err.to_string().contains("not enabled"), "{err}");
+    }
+}
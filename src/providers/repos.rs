@@ -0,0 +1,196 @@
+//! `repos:` — git checkouts under the configured roots as launcher items.
+//!
+//! An item is keyed by its path below the root (`web`, `acme/api`). Run on
+//! its own it opens the directory; the first argument picks another action:
+//! `term` opens a terminal there, `edit` the editor, and `web` the page of
+//! its `origin` remote.
+
+use super::{Delivery, ItemProvider};
+use crate::commands::list::Action;
+use crate::config::{self, ConfigManager, EnvMap, ReposConfig};
+use crate::launcher::{ListItem, ListItemKind};
+use crate::utils::git;
+use anyhow::Result;
+use std::path::Path;
+
+const DEFAULT_DEPTH: usize = 3;
+const DEFAULT_EDITOR: &str = "code .";
+
+fn default_terminal() -> &'static str {
+    if cfg!(windows) {
+        "start cmd"
+    } else if cfg!(target_os = "macos") {
+        "open -a Terminal ."
+    } else {
+        "x-terminal-emulator"
+    }
+}
+
+pub struct RepoProvider {
+    config: ReposConfig,
+    browser: String,
+    env: EnvMap,
+}
+
+impl RepoProvider {
+    pub fn new(config: ReposConfig, config_manager: &ConfigManager) -> Self {
+        Self {
+            config,
+            browser: config_manager.get_effective_browser().to_string(),
+            env: config_manager.get_effective_env(),
+        }
+    }
+
+    /// `command` run in the checkout at `path`.
+    fn run_in(&self, command: &str, path: &str) -> Action {
+        Action::Run {
+            command: command.to_string(),
+            args: None,
+            cwd: Some(path.to_string()),
+            env: self.env.clone(),
+        }
+    }
+}
+
+/// The launcher item for the checkout at `repo` below `root`.
+fn repo_item(root: &Path, repo: &Path) -> ListItem {
+    let key = match repo.strip_prefix(root) {
+        Ok(rel) if !rel.as_os_str().is_empty() => rel.to_string_lossy().replace('\\', "/"),
+        _ => repo
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    };
+    let path = repo.display().to_string();
+    ListItem {
+        key,
+        aliases: Vec::new(),
+        display_detail: path.clone(),
+        kind: ListItemKind::Provided {
+            provider: "repo".to_string(),
+            data: path,
+        },
+        pinned: false,
+    }
+}
+
+impl ItemProvider for RepoProvider {
+    fn name(&self) -> &str {
+        "repo"
+    }
+
+    fn delivery(&self) -> Delivery {
+        Delivery::Background
+    }
+
+    fn items(&self, _query: &str) -> Result<Vec<ListItem>> {
+        let depth = self.config.depth.unwrap_or(DEFAULT_DEPTH);
+        Ok(self
+            .config
+            .roots
+            .iter()
+            .map(|root| config::expand_tilde(root))
+            .flat_map(|root| {
+                git::find_repos(&root, depth)
+                    .into_iter()
+                    .map(move |repo| repo_item(&root, &repo))
+            })
+            .collect())
+    }
+
+    fn resolve(&self, item: &ListItem, args: Option<&str>) -> Result<Action> {
+        let ListItemKind::Provided { data: path, .. } = &item.kind else {
+            anyhow::bail!("'{}' is not a repository", item.key);
+        };
+        let action = args.and_then(|a| a.split_whitespace().next()).unwrap_or("");
+        Ok(match action {
+            "" | "open" => Action::Launch { path: path.clone() },
+            "term" | "terminal" => self.run_in(
+                self.config
+                    .terminal
+                    .as_deref()
+                    .unwrap_or(default_terminal()),
+                path,
+            ),
+            "edit" | "editor" => self.run_in(
+                self.config.editor.as_deref().unwrap_or(DEFAULT_EDITOR),
+                path,
+            ),
+            "web" | "remote" => {
+                let remote = git::origin_url(Path::new(path))
+                    .ok_or_else(|| anyhow::anyhow!("'{}' has no origin remote", item.key))?;
+                let url = git::web_url(&remote).ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "No web page known for the remote of '{}': {}",
+                        item.key,
+                        remote
+                    )
+                })?;
+                Action::Open {
+                    url,
+                    browser: self.browser.clone(),
+                }
+            }
+            other => anyhow::bail!(
+                "Unknown repository action '{}' for '{}' (use open, term, edit or web)",
+                other,
+                item.key
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn items_open_the_checkout_or_run_the_chosen_action() {
+        let root = std::env::temp_dir().join(format!("ps-repos-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("acme/web/.git")).unwrap();
+        fs::write(
+            root.join("acme/web/.git/config"),
+            "[remote \"origin\"]\n\turl = git@github.com:acme/web.git\n",
+        )
+        .unwrap();
+        let cm = ConfigManager::from_test_yaml("defaultBrowser: firefox\n");
+        let provider = RepoProvider::new(
+            ReposConfig {
+                roots: vec![root.display().to_string()],
+                depth: None,
+                editor: Some("vim".to_string()),
+                terminal: None,
+            },
+            &cm,
+        );
+
+        let items = provider.items("").unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].key, "acme/web");
+        let path = root.join("acme/web").display().to_string();
+        assert_eq!(
+            provider.resolve(&items[0], None).unwrap(),
+            Action::Launch { path: path.clone() }
+        );
+        assert_eq!(
+            provider.resolve(&items[0], Some("edit")).unwrap(),
+            Action::Run {
+                command: "vim".to_string(),
+                args: None,
+                cwd: Some(path),
+                env: EnvMap::new(),
+            }
+        );
+        assert_eq!(
+            provider.resolve(&items[0], Some("web")).unwrap(),
+            Action::Open {
+                url: "https://github.com/acme/web".to_string(),
+                browser: "firefox".to_string(),
+            }
+        );
+        assert!(provider.resolve(&items[0], Some("deploy")).is_err());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,327 @@
+//! `providers:` — external commands listing launcher items.
+//!
+//! The command runs through the shell in the selection's `path`, with its
+//! `env` plus `PS_QUERY` (the input being resolved; empty when the launcher
+//! lists everything), `PS_CLIENT`, `PS_PROJECT` and `PS_PATH`. It prints one
+//! JSON object per line:
+//!
+//! ```text
+//! {"key": "PROJ-12", "detail": "Fix login", "action": {"url": "https://jira/PROJ-12"}}
+//! ```
+//!
+//! `action` has the fields of an inline step (`url`, `command`, `browser`,
+//! `args`, `cwd`, `webview`) and runs like one. Lines that don't parse are
+//! skipped and logged. Results are cached in `~/.project-switch-cache/` per
+//! query and selection for `cacheTtl` seconds.
+
+use super::{Delivery, ItemProvider};
+use crate::commands::list::Action;
+use crate::config::{self, ConfigManager, InlineStep, ProviderConfig};
+use crate::launcher::{ListItem, ListItemKind};
+use crate::utils::file;
+use crate::utils::interpolate::{self, Vars};
+use crate::utils::shell::{self, Outcome};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const DEFAULT_TIMEOUT: u64 = 5;
+const DEFAULT_CACHE_TTL: u64 = 60;
+/// Cached results kept per provider, most recent first.
+const MAX_CACHED: usize = 16;
+
+/// One line of a provider's output.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScriptItem {
+    key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<String>,
+    action: InlineStep,
+}
+
+/// The items of `stdout`, and a message for each line that isn't one.
+fn parse_lines(stdout: &str) -> (Vec<ScriptItem>, Vec<String>) {
+    let mut items = Vec::new();
+    let mut errors = Vec::new();
+    for (i, line) in stdout.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(item) => items.push(item),
+            Err(e) => errors.push(format!("line {}: {}", i + 1, e)),
+        }
+    }
+    (items, errors)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    context: String,
+    fetched: u64,
+    items: Vec<ScriptItem>,
+}
+
+fn read_cache(path: &Path) -> Vec<CacheEntry> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// Items cached for `context` less than `ttl` seconds before `now`.
+fn cached(path: &Path, context: &str, ttl: u64, now: u64) -> Option<Vec<ScriptItem>> {
+    read_cache(path)
+        .into_iter()
+        .find(|e| e.context == context && now.saturating_sub(e.fetched) < ttl)
+        .map(|e| e.items)
+}
+
+fn store(path: &Path, context: &str, items: &[ScriptItem], now: u64) -> Result<()> {
+    let mut entries = read_cache(path);
+    entries.retain(|e| e.context != context);
+    entries.insert(
+        0,
+        CacheEntry {
+            context: context.to_string(),
+            fetched: now,
+            items: items.to_vec(),
+        },
+    );
+    entries.truncate(MAX_CACHED);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    file::write_atomic(path, &serde_json::to_string(&entries)?)
+}
+
+pub struct ScriptProvider {
+    config: ProviderConfig,
+    config_manager: ConfigManager,
+    /// Where results are cached; `None` disables caching.
+    cache_dir: Option<PathBuf>,
+}
+
+impl ScriptProvider {
+    pub fn new(config: ProviderConfig, config_manager: &ConfigManager) -> Self {
+        Self {
+            config,
+            config_manager: config_manager.clone(),
+            cache_dir: dirs::home_dir().map(|home| home.join(".project-switch-cache")),
+        }
+    }
+
+    fn cache_path(&self) -> Option<PathBuf> {
+        let name: String = self
+            .config
+            .name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        Some(self.cache_dir.as_ref()?.join(format!("{}.json", name)))
+    }
+
+    /// The selection and `query`, as a cache key and the `PS_*` variables.
+    fn context(&self, query: &str) -> [(&'static str, String); 4] {
+        let (client, project) = match self.config_manager.resolve_current() {
+            Some((client, _, project)) => (
+                client.clone(),
+                project.map(|(name, _)| name.clone()).unwrap_or_default(),
+            ),
+            None => Default::default(),
+        };
+        let path = self
+            .config_manager
+            .get_scope_path()
+            .map(|p| config::expand_tilde(p).display().to_string())
+            .unwrap_or_default();
+        [
+            ("PS_QUERY", query.to_string()),
+            ("PS_CLIENT", client),
+            ("PS_PROJECT", project),
+            ("PS_PATH", path),
+        ]
+    }
+
+    /// Run the command and parse its output.
+    fn fetch(&self, context: &[(&'static str, String)]) -> Result<Vec<ScriptItem>> {
+        let vars = Vars::from_selection(&self.config_manager);
+        let mut env = interpolate::expand_env(&self.config_manager.get_effective_env(), &vars)?;
+        env.extend(context.iter().map(|(k, v)| (k.to_string(), v.clone())));
+        let cwd = self
+            .config_manager
+            .get_scope_path()
+            .map(config::expand_tilde);
+        let timeout = Duration::from_secs(self.config.timeout.unwrap_or(DEFAULT_TIMEOUT));
+
+        let (output, outcome) = shell::run(&self.config.command, cwd.as_deref(), &env, timeout);
+        if let Outcome::Failed(reason) = outcome {
+            let stderr = output.stderr.trim();
+            if stderr.is_empty() {
+                anyhow::bail!("{}", reason);
+            }
+            anyhow::bail!("{}: {}", reason, stderr);
+        }
+        let (items, errors) = parse_lines(&output.stdout);
+        if !errors.is_empty() {
+            crate::utils::log::append_error(&format!(
+                "Provider '{}' printed invalid items, skipped:\n{}",
+                self.config.name,
+                errors.join("\n")
+            ));
+        }
+        Ok(items)
+    }
+
+    fn list_item(&self, item: ScriptItem) -> ListItem {
+        let detail = item.detail.unwrap_or_else(|| {
+            item.action
+                .url
+                .clone()
+                .or_else(|| item.action.command.clone())
+                .unwrap_or_default()
+        });
+        ListItem {
+            key: item.key,
+            aliases: item.aliases,
+            display_detail: detail,
+            kind: ListItemKind::Provided {
+                provider: self.config.name.clone(),
+                data: serde_json::to_string(&item.action).unwrap_or_default(),
+            },
+            pinned: false,
+        }
+    }
+}
+
+impl ItemProvider for ScriptProvider {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn delivery(&self) -> Delivery {
+        if self.config.sync {
+            Delivery::Sync
+        } else {
+            Delivery::Background
+        }
+    }
+
+    fn items(&self, query: &str) -> Result<Vec<ListItem>> {
+        let context = self.context(query);
+        let key = context
+            .iter()
+            .map(|(_, v)| v.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let ttl = self.config.cache_ttl.unwrap_or(DEFAULT_CACHE_TTL);
+        let cache = self.cache_path().filter(|_| ttl > 0);
+        let now = crate::history::now();
+
+        let items = match cache.as_deref().and_then(|p| cached(p, &key, ttl, now)) {
+            Some(items) => items,
+            None => {
+                let items = self.fetch(&context)?;
+                if let Some(path) = &cache {
+                    // A cache that can't be written only costs a rerun.
+                    let _ = store(path, &key, &items, now);
+                }
+                items
+            }
+        };
+        Ok(items.into_iter().map(|item| self.list_item(item)).collect())
+    }
+
+    fn resolve(&self, item: &ListItem, args: Option<&str>) -> Result<Action> {
+        let ListItemKind::Provided { data, .. } = &item.kind else {
+            anyhow::bail!("'{}' is not a provider item", item.key);
+        };
+        let step: InlineStep = serde_json::from_str(data)
+            .with_context(|| format!("Invalid action for '{}'", item.key))?;
+        crate::commands::list::inline_action(&self.config_manager, &item.key, &step, args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("ps-script-test-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn parse_lines_skips_invalid_lines() {
+        let (items, errors) = parse_lines(concat!(
+            r#"{"key": "PROJ-1", "detail": "Fix login", "action": {"url": "https://j/PROJ-1"}}"#,
+            "\n\nnot json\n",
+            r#"{"key": "deploy", "action": {"command": "make deploy"}}"#,
+            "\n",
+            r#"{"key": "bad", "action": {"launch": "x"}}"#,
+        ));
+        let keys: Vec<&str> = items.iter().map(|i| i.key.as_str()).collect();
+        assert_eq!(keys, ["PROJ-1", "deploy"]);
+        assert_eq!(items[0].detail.as_deref(), Some("Fix login"));
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("line 3:"), "{:?}", errors);
+    }
+
+    #[test]
+    fn cache_expires_and_is_per_context() {
+        let path = temp_dir("cache").join("jira.json");
+        let items = parse_lines(r#"{"key": "a", "action": {"url": "https://a"}}"#).0;
+        store(&path, "acme\n\nfoo", &items, 1000).unwrap();
+        store(&path, "globex\n\nfoo", &[], 1000).unwrap();
+        assert_eq!(cached(&path, "acme\n\nfoo", 60, 1059), Some(items));
+        assert_eq!(cached(&path, "acme\n\nfoo", 60, 1060), None);
+        assert_eq!(cached(&path, "acme\n\nbar", 60, 1000), None);
+        assert_eq!(cached(&path, "globex\n\nfoo", 60, 1000), Some(Vec::new()));
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn items_come_from_the_command_with_the_selection_context() {
+        let cm = ConfigManager::from_test_yaml(
+            "currentClient: acme\nclients:\n  - name: acme\n    path: /tmp\n",
+        );
+        let script = r#"echo "{\"key\": \"$PS_CLIENT-$PS_QUERY\", \"action\": {\"url\": \"https://x/$PS_PATH\"}}""#;
+        let mut provider = ScriptProvider::new(
+            ProviderConfig {
+                name: "ctx".to_string(),
+                command: script.to_string(),
+                timeout: None,
+                cache_ttl: None,
+                sync: false,
+            },
+            &cm,
+        );
+        provider.cache_dir = Some(temp_dir("items"));
+
+        let items = provider.items("q").unwrap();
+        assert_eq!(items[0].key, "acme-q");
+        assert_eq!(items[0].display_detail, "https://x//tmp");
+        assert_eq!(
+            provider.resolve(&items[0], None).unwrap(),
+            Action::Open {
+                url: "https://x//tmp".to_string(),
+                browser: cm.get_effective_browser().to_string(),
+            }
+        );
+
+        // A second call within the TTL reads the cache, even if the command
+        // would now fail.
+        provider.config.command = "exit 1".to_string();
+        assert_eq!(provider.items("q").unwrap(), items);
+        assert!(provider.items("other").is_err());
+        let _ = std::fs::remove_dir_all(provider.cache_dir.unwrap());
+    }
+}
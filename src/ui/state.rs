@@ -3,9 +3,15 @@
 //! Manages visibility, input, filtering, selection, and transitions
 //! independently of the GUI framework for testability.
 
+use crate::launcher::fuzzy::fuzzy_match;
 use crate::launcher::{
-    eval_calc_input, filter_items, is_file_path, order_recent_keys, CalcResult, ListItem,
+    eval_calc_input, filter_items, is_file_path, order_recent_keys, CalcResult, ListItem, Usage,
 };
+use crate::providers::Batch;
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+/// Input prefix that turns the launcher into a client/project switcher.
+pub const SWITCH_PREFIX: char = '@';
 
 /// The current input mode, derived from the input text.
 #[derive(Debug, Clone, PartialEq)]
@@ -16,6 +22,27 @@ pub enum InputMode {
     Calculator { result: CalcResult },
     /// File path browsing mode.
     FilePath,
+    /// Client/project switching mode (input starts with `@`).
+    Switch,
+}
+
+/// A client, or one of its nested projects, offered in switch mode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwitchTarget {
+    pub client: String,
+    pub project: Option<String>,
+    /// Whether this is the active selection.
+    pub current: bool,
+}
+
+impl SwitchTarget {
+    /// `client` or `client / project`; what switch mode matches and shows.
+    pub fn label(&self) -> String {
+        match &self.project {
+            Some(project) => format!("{} / {}", self.client, project),
+            None => self.client.clone(),
+        }
+    }
 }
 
 /// An entry in the filtered display list, including both regular items
@@ -57,6 +84,15 @@ pub struct WindowState {
     /// Recently executed item keys (most recent first), used to show
     /// recents when input is empty.
     recent_keys: Vec<String>,
+    /// Derived from `recent_keys`; ranks filtered items.
+    usage: Usage,
+    /// Why the config couldn't be (re)loaded; the items are the last good
+    /// ones. Shown above the list until a reload succeeds.
+    pub config_error: Option<String>,
+    /// Clients and projects for switch mode, in config order.
+    targets: Vec<SwitchTarget>,
+    /// Items still being collected by background providers.
+    pending: Option<Receiver<Batch>>,
 }
 
 impl WindowState {
@@ -69,7 +105,11 @@ impl WindowState {
             visible_frames: 0,
             items,
             filtered_count: 0,
+            usage: Usage::from_recent(&recent_keys),
             recent_keys,
+            config_error: None,
+            targets: Vec::new(),
+            pending: None,
         };
         s.update_filtered_count();
         s
@@ -146,7 +186,9 @@ impl WindowState {
 
     /// Determine the current input mode.
     pub fn input_mode(&self) -> InputMode {
-        if let Some(expr) = self.input.strip_prefix('=') {
+        if self.input.starts_with(SWITCH_PREFIX) {
+            InputMode::Switch
+        } else if let Some(expr) = self.input.strip_prefix('=') {
             let expr = expr.trim();
             if expr.is_empty() {
                 InputMode::Calculator {
@@ -180,7 +222,7 @@ impl WindowState {
                 return recent;
             }
         }
-        filter_items(&self.items, &self.input)
+        filter_items(&self.items, &self.input, &self.usage)
     }
 
     /// Get the full filtered entry list including non-item recents
@@ -216,7 +258,7 @@ impl WindowState {
                 return entries;
             }
         }
-        filter_items(&self.items, &self.input)
+        filter_items(&self.items, &self.input, &self.usage)
             .into_iter()
             .map(|item| FilteredEntry::Item(item.clone()))
             .collect()
@@ -239,12 +281,13 @@ impl WindowState {
     #[allow(dead_code)]
     /// Update the recent-history keys (e.g., after the daemon executes an action).
     pub fn set_recent_keys(&mut self, keys: Vec<String>) {
+        self.usage = Usage::from_recent(&keys);
         self.recent_keys = keys;
         self.update_filtered_count();
     }
 
     /// Return the action input string for the currently selected Item entry.
-    /// Preserves user-typed args when the first word matches the item key exactly.
+    /// Preserves user-typed args when the first word is exactly the item's key or an alias.
     /// Returns `None` when the selection is empty, out-of-bounds, or a non-Item entry
     /// (Expression/Path are handled separately by the caller).
     pub fn selected_action_input(&self) -> Option<String> {
@@ -255,7 +298,7 @@ impl WindowState {
         match &entries[self.selected] {
             FilteredEntry::Item(item) => {
                 let keyword = self.input.split_whitespace().next().unwrap_or("");
-                if keyword.eq_ignore_ascii_case(&item.key) {
+                if item.is_named(keyword) {
                     Some(self.input.clone())
                 } else {
                     Some(item.key.clone())
@@ -265,13 +308,94 @@ impl WindowState {
         }
     }
 
-    /// Append additional items (e.g., shortcuts loaded asynchronously).
-    /// Preserves the current input and selection.
-    pub fn append_items(&mut self, new_items: Vec<ListItem>) {
-        self.items.extend(new_items);
+    /// Replace the command items, keeping provider items (shortcuts, which
+    /// don't depend on the selection). Used after switching client/project
+    /// in place.
+    pub fn set_command_items(&mut self, commands: Vec<ListItem>) {
+        self.items.retain(|item| item.kind.provider().is_some());
+        self.items.splice(0..0, commands);
+        self.selected = 0;
         self.update_filtered_count();
     }
 
+    /// The items that came from providers, for resolving the input run.
+    pub fn provider_items(&self) -> Vec<ListItem> {
+        self.items
+            .iter()
+            .filter(|item| item.kind.provider().is_some())
+            .cloned()
+            .collect()
+    }
+
+    pub fn set_targets(&mut self, targets: Vec<SwitchTarget>) {
+        self.targets = targets;
+    }
+
+    /// The text after `@`, trimmed.
+    pub fn switch_query(&self) -> &str {
+        self.input
+            .strip_prefix(SWITCH_PREFIX)
+            .unwrap_or(&self.input)
+            .trim()
+    }
+
+    /// Switch targets matching the input after `@`: all in config order when
+    /// it's empty, otherwise fuzzy matched and ranked by score.
+    pub fn filtered_targets(&self) -> Vec<&SwitchTarget> {
+        let query = self.switch_query();
+        if query.is_empty() {
+            return self.targets.iter().collect();
+        }
+        let mut ranked: Vec<(i32, &SwitchTarget)> = self
+            .targets
+            .iter()
+            .filter_map(|t| Some((fuzzy_match(query, &t.label())?.score, t)))
+            .collect();
+        ranked.sort_by(|(a, _), (b, _)| b.cmp(a));
+        ranked.into_iter().map(|(_, t)| t).collect()
+    }
+
+    /// The switch target under the selection cursor.
+    pub fn selected_target(&self) -> Option<&SwitchTarget> {
+        let targets = self.filtered_targets();
+        let index = self.selected.min(targets.len().checked_sub(1)?);
+        Some(targets[index])
+    }
+
+    /// Add a provider's items, replacing the ones it gave before (a
+    /// refresh); an empty batch just removes them. Preserves the current
+    /// input and selection.
+    pub fn append_items(&mut self, batch: Batch) {
+        self.items
+            .retain(|item| item.kind.provider() != Some(batch.provider.as_str()));
+        self.items.extend(batch.items);
+        self.update_filtered_count();
+    }
+
+    /// Append the items background providers send on `rx` as they arrive
+    /// (see [`poll_items`](Self::poll_items)).
+    pub fn receive_items(&mut self, rx: Receiver<Batch>) {
+        self.pending = Some(rx);
+    }
+
+    /// Append whatever background providers have sent. Call once per frame;
+    /// returns whether more items are expected.
+    pub fn poll_items(&mut self) -> bool {
+        let Some(rx) = self.pending.take() else {
+            return false;
+        };
+        loop {
+            match rx.try_recv() {
+                Ok(batch) => self.append_items(batch),
+                Err(TryRecvError::Disconnected) => return false,
+                Err(TryRecvError::Empty) => {
+                    self.pending = Some(rx);
+                    return true;
+                }
+            }
+        }
+    }
+
     fn update_filtered_count(&mut self) {
         self.filtered_count = self.filtered_entries().len();
     }
@@ -285,6 +409,7 @@ mod tests {
     fn make_item(key: &str) -> ListItem {
         ListItem {
             key: key.to_string(),
+            aliases: Vec::new(),
             display_detail: format!("https://{}.com/", key),
             kind: ListItemKind::Command,
             pinned: false,
@@ -294,6 +419,7 @@ mod tests {
     fn make_pinned_item(key: &str) -> ListItem {
         ListItem {
             key: key.to_string(),
+            aliases: Vec::new(),
             display_detail: format!("https://{}.com/", key),
             kind: ListItemKind::Command,
             pinned: true,
@@ -328,6 +454,7 @@ mod tests {
         let mut items = sample_items();
         items.push(ListItem {
             key: "g".to_string(),
+            aliases: Vec::new(),
             display_detail: "https://google.com/search?q=".to_string(),
             kind: ListItemKind::Command,
             pinned: false,
@@ -598,6 +725,7 @@ mod tests {
         state.navigate_down();
         state.set_items(vec![ListItem {
             key: "new".to_string(),
+            aliases: Vec::new(),
             display_detail: String::new(),
             kind: ListItemKind::Command,
             pinned: false,
@@ -777,6 +905,7 @@ mod tests {
             entries[0],
             FilteredEntry::Item(ListItem {
                 key: "g".to_string(),
+                aliases: Vec::new(),
                 display_detail: "https://google.com/search?q=".to_string(),
                 kind: ListItemKind::Command,
                 pinned: false,
@@ -860,4 +989,125 @@ mod tests {
             }
         );
     }
+
+    fn target(client: &str, project: Option<&str>) -> SwitchTarget {
+        SwitchTarget {
+            client: client.to_string(),
+            project: project.map(str::to_string),
+            current: false,
+        }
+    }
+
+    fn switch_state() -> WindowState {
+        let mut state = WindowState::new(sample_items(), vec![]);
+        state.set_targets(vec![
+            target("acme", None),
+            target("acme", Some("website")),
+            target("globex", None),
+            target("globex", Some("warehouse")),
+        ]);
+        state.show();
+        state
+    }
+
+    fn labels(targets: &[&SwitchTarget]) -> Vec<String> {
+        targets.iter().map(|t| t.label()).collect()
+    }
+
+    #[test]
+    fn at_prefix_enters_switch_mode() {
+        let mut state = switch_state();
+        state.set_input("@".to_string());
+        assert_eq!(state.input_mode(), InputMode::Switch);
+        assert_eq!(state.filtered_targets().len(), 4);
+    }
+
+    #[test]
+    fn switch_mode_filters_and_ranks_targets() {
+        let mut state = switch_state();
+        state.set_input("@ ws".to_string());
+        assert_eq!(
+            labels(&state.filtered_targets()),
+            ["acme / website", "globex / warehouse"]
+        );
+        state.navigate_down_bounded(state.filtered_targets().len());
+        assert_eq!(
+            state.selected_target(),
+            Some(&target("globex", Some("warehouse")))
+        );
+    }
+
+    #[test]
+    fn switch_mode_with_no_match_selects_nothing() {
+        let mut state = switch_state();
+        state.set_input("@zzz".to_string());
+        assert!(state.filtered_targets().is_empty());
+        assert_eq!(state.selected_target(), None);
+    }
+
+    #[test]
+    fn set_command_items_keeps_shortcuts() {
+        let shortcut = ListItem {
+            key: "Notepad".to_string(),
+            aliases: Vec::new(),
+            display_detail: String::new(),
+            kind: ListItemKind::Shortcut {
+                path: "notepad.lnk".to_string(),
+            },
+            pinned: false,
+        };
+        let mut state = WindowState::new(sample_items(), vec![]);
+        state.append_items(Batch {
+            provider: "shortcuts".to_string(),
+            items: vec![shortcut],
+        });
+        state.set_command_items(vec![make_item("wiki")]);
+        let keys: Vec<String> = state
+            .filtered_items()
+            .iter()
+            .map(|i| i.key.clone())
+            .collect();
+        assert_eq!(keys, ["wiki", "Notepad"]);
+    }
+
+    fn provided(provider: &str, key: &str) -> ListItem {
+        ListItem {
+            key: key.to_string(),
+            aliases: Vec::new(),
+            display_detail: String::new(),
+            kind: ListItemKind::Provided {
+                provider: provider.to_string(),
+                data: String::new(),
+            },
+            pinned: false,
+        }
+    }
+
+    #[test]
+    fn received_items_replace_their_providers_earlier_items() {
+        let mut state = WindowState::new(vec![make_item("wiki")], vec![]);
+        let batch = |provider: &str, keys: &[&str]| Batch {
+            provider: provider.to_string(),
+            items: keys.iter().map(|key| provided(provider, key)).collect(),
+        };
+        state.append_items(batch("jira", &["PROJ-1"]));
+        state.append_items(batch("deploys", &["prod"]));
+        state.append_items(batch("confluence", &["Home"]));
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        state.receive_items(rx);
+        assert!(state.poll_items());
+        tx.send(batch("jira", &["PROJ-2"])).unwrap();
+        // A provider that now fails or finds nothing drops its stale items.
+        tx.send(batch("confluence", &[])).unwrap();
+        drop(tx);
+        assert!(!state.poll_items());
+
+        let keys: Vec<String> = state
+            .filtered_items()
+            .iter()
+            .map(|i| i.key.clone())
+            .collect();
+        assert_eq!(keys, ["wiki", "prod", "PROJ-2"]);
+    }
 }
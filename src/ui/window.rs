@@ -1,7 +1,7 @@
 //! egui launcher window — renders the text input and filtered list.
 
-use crate::launcher::{get_path_entries, CalcResult, ListItemKind};
-use crate::ui::state::{FilteredEntry, InputMode, WindowState};
+use crate::launcher::{get_path_entries, truncate, CalcResult, ListItem, ListItemKind};
+use crate::ui::state::{FilteredEntry, InputMode, SwitchTarget, WindowState};
 use eframe::egui;
 
 fn set_path_input(state: &mut WindowState, prev_input: &mut String, path: &str) {
@@ -11,33 +11,107 @@ fn set_path_input(state: &mut WindowState, prev_input: &mut String, path: &str)
     state.set_input(new_input);
 }
 
-fn open_path_and_hide(state: &mut WindowState, path: String) {
-    state.hide();
+/// Run launcher input off the UI thread, resolving provider items against
+/// the `provided` ones already listed. The window is already hidden, so
+/// failures (including which step of a `steps:` command failed) go to the log.
+fn execute_in_background(input: String, provided: Vec<ListItem>) {
     std::thread::spawn(move || {
-        if let Err(e) = crate::commands::list::execute_action(&path) {
+        if let Err(e) = crate::commands::list::execute_action(&input, Some(&provided)) {
             eprintln!("Action error: {e:#}");
+            crate::utils::log::append_error(&format!("{input}: {e:#}"));
         }
     });
 }
 
+fn open_path_and_hide(state: &mut WindowState, path: String) {
+    state.hide();
+    execute_in_background(path, Vec::new());
+}
+
 fn execute_and_hide(state: &mut WindowState, action_input: &str) {
     if action_input.starts_with('=') {
         return;
     }
-    let input = action_input.to_string();
     state.hide();
-    std::thread::spawn(move || {
-        if let Err(e) = crate::commands::list::execute_action(&input) {
-            eprintln!("Action error: {e:#}");
+    execute_in_background(action_input.to_string(), state.provider_items());
+}
+
+/// Switch to `target` and reload the items, switch targets, recents and
+/// header for the new selection without closing the window. Shortcuts don't
+/// depend on the selection and are kept.
+fn switch_and_reload(
+    state: &mut WindowState,
+    title: &mut String,
+    prev_input: &mut String,
+    target: &SwitchTarget,
+) {
+    match crate::commands::switch::switch_detached(&target.client, target.project.as_deref()) {
+        Ok(config_manager) => {
+            let (_, commands) = crate::commands::list::load_command_items(&config_manager);
+            state.set_command_items(commands);
+            // Provider items depend on the selection too
+            state.receive_items(crate::providers::refresh(crate::providers::registered(
+                &config_manager,
+            )));
+            state.set_targets(crate::commands::switch::targets(&config_manager));
+            state.set_recent_keys(crate::history::load());
+            *title = crate::commands::list::selection_display_name(&config_manager);
         }
-    });
+        Err(e) => {
+            eprintln!("Switch error: {e:#}");
+            crate::utils::log::append_error(&format!("switch to {}: {e:#}", target.label()));
+        }
+    }
+    prev_input.clear();
+    state.set_input(String::new());
+}
+
+/// Build a list label whose `key` has the characters `query` matched
+/// underlined and brightened; `prefix` and `suffix` are drawn plainly.
+fn highlighted_label(
+    ui: &egui::Ui,
+    [prefix, key, suffix]: [&str; 3],
+    query: &str,
+    color: Option<egui::Color32>,
+    strong: bool,
+) -> egui::text::LayoutJob {
+    let visuals = ui.visuals();
+    let base = egui::TextFormat {
+        font_id: egui::TextStyle::Button.resolve(ui.style()),
+        color: match (color, strong) {
+            (Some(color), _) => color,
+            (None, true) => visuals.strong_text_color(),
+            (None, false) => visuals.text_color(),
+        },
+        ..Default::default()
+    };
+    let matched = egui::TextFormat {
+        color: visuals.strong_text_color(),
+        underline: egui::Stroke::new(1.0, visuals.strong_text_color()),
+        ..base.clone()
+    };
+    let ranges = crate::launcher::fuzzy::fuzzy_match(query, key)
+        .map(|m| m.ranges)
+        .unwrap_or_default();
+
+    let mut job = egui::text::LayoutJob::default();
+    job.append(prefix, 0.0, base.clone());
+    let mut pos = 0;
+    for range in ranges {
+        job.append(&key[pos..range.start], 0.0, base.clone());
+        job.append(&key[range.clone()], 0.0, matched.clone());
+        pos = range.end;
+    }
+    job.append(&key[pos..], 0.0, base.clone());
+    job.append(suffix, 0.0, base);
+    job
 }
 
 /// Render the launcher UI inside a CentralPanel. Shared by both standalone and daemon modes.
 pub fn render_launcher(
     ctx: &egui::Context,
     state: &mut WindowState,
-    project_name: &str,
+    project_name: &mut String,
     prev_input: &mut String,
 ) {
     egui::CentralPanel::default().show(ctx, |ui| {
@@ -50,11 +124,18 @@ pub fn render_launcher(
         ui.painter().text(
             title_rect.left_center() + egui::vec2(4.0, 0.0),
             egui::Align2::LEFT_CENTER,
-            project_name,
+            project_name.as_str(),
             egui::FontId::proportional(16.0),
             egui::Color32::GRAY,
         );
 
+        if let Some(error) = &state.config_error {
+            ui.label(
+                egui::RichText::new(format!("Config error, using the last good config: {error}"))
+                    .color(egui::Color32::from_rgb(230, 110, 90)),
+            );
+        }
+
         // Text input
         let input_response = ui.add(
             egui::TextEdit::singleline(&mut state.input)
@@ -122,6 +203,51 @@ pub fn render_launcher(
                     }
                 });
             }
+            InputMode::Switch => {
+                let targets: Vec<SwitchTarget> =
+                    state.filtered_targets().into_iter().cloned().collect();
+                if key_down {
+                    state.navigate_down_bounded(targets.len());
+                }
+                if key_up {
+                    state.navigate_up();
+                }
+                if key_enter {
+                    if let Some(target) = state.selected_target().cloned() {
+                        switch_and_reload(state, project_name, prev_input, &target);
+                    }
+                    return;
+                }
+                let selected = state.selected.min(targets.len().saturating_sub(1));
+
+                let query = state.switch_query().to_string();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (i, target) in targets.iter().enumerate() {
+                        let is_selected = i == selected;
+                        let suffix = if target.current { "  (current)" } else { "" };
+                        let label = highlighted_label(
+                            ui,
+                            ["@ ", &target.label(), suffix],
+                            &query,
+                            None,
+                            is_selected,
+                        );
+                        let response = ui.selectable_label(is_selected, label);
+                        if response.clicked() {
+                            switch_and_reload(state, project_name, prev_input, target);
+                        }
+                        if is_selected && (key_down || key_up) {
+                            response.scroll_to_me(Some(egui::Align::Center));
+                        }
+                    }
+                    if targets.is_empty() {
+                        ui.label(
+                            egui::RichText::new("No client or project matches")
+                                .color(egui::Color32::GRAY),
+                        );
+                    }
+                });
+            }
             InputMode::FilePath => {
                 let entries = get_path_entries(&state.input);
                 if key_down {
@@ -214,6 +340,7 @@ pub fn render_launcher(
 
                 let entries = state.filtered_entries();
                 let selected = state.selected;
+                let query = state.input.split_whitespace().next().unwrap_or("");
 
                 if key_enter && !entries.is_empty() && selected < entries.len() {
                     if let Some(action) = state.selected_action_input() {
@@ -230,50 +357,55 @@ pub fn render_launcher(
                     for (i, entry) in entries.iter().enumerate() {
                         let is_selected = i == selected;
 
-                        let label = match entry {
+                        let label: egui::WidgetText = match entry {
                             FilteredEntry::Item(item) => match &item.kind {
                                 ListItemKind::Command => {
-                                    let detail = if item.display_detail.len() > 50 {
-                                        format!("{}...", &item.display_detail[..47])
-                                    } else {
-                                        item.display_detail.clone()
-                                    };
-                                    let text = format!("{}  -  {}", item.key, detail);
-                                    if is_selected {
-                                        egui::RichText::new(text).strong()
-                                    } else {
-                                        egui::RichText::new(text)
-                                    }
+                                    let detail = truncate(&item.display_detail, 50);
+                                    let suffix = format!("  -  {}", detail);
+                                    highlighted_label(
+                                        ui,
+                                        ["", &item.key, &suffix],
+                                        query,
+                                        None,
+                                        is_selected,
+                                    )
+                                    .into()
                                 }
-                                ListItemKind::Shortcut { .. } => {
-                                    let text = format!("[app] {}", item.key);
-                                    let rt = egui::RichText::new(text)
-                                        .color(egui::Color32::from_rgb(100, 200, 200));
-                                    if is_selected {
-                                        rt.strong()
+                                ListItemKind::Shortcut { .. } => highlighted_label(
+                                    ui,
+                                    ["[app] ", &item.key, ""],
+                                    query,
+                                    Some(egui::Color32::from_rgb(100, 200, 200)),
+                                    is_selected,
+                                )
+                                .into(),
+                                ListItemKind::Provided { provider, .. } => {
+                                    let prefix = format!("[{}] ", provider);
+                                    let suffix = if item.display_detail.is_empty() {
+                                        String::new()
                                     } else {
-                                        rt
-                                    }
+                                        format!("  -  {}", item.display_detail)
+                                    };
+                                    highlighted_label(
+                                        ui,
+                                        [&prefix, &item.key, &suffix],
+                                        query,
+                                        Some(egui::Color32::from_rgb(200, 160, 230)),
+                                        is_selected,
+                                    )
+                                    .into()
                                 }
                             },
                             FilteredEntry::Expression { display, .. } => {
                                 let rt = egui::RichText::new(display)
                                     .color(egui::Color32::from_rgb(100, 200, 100));
-                                if is_selected {
-                                    rt.strong()
-                                } else {
-                                    rt
-                                }
+                                if is_selected { rt.strong() } else { rt }.into()
                             }
                             FilteredEntry::Path(path) => {
                                 let text = format!("[path] {}", path);
                                 let rt = egui::RichText::new(text)
                                     .color(egui::Color32::from_rgb(100, 180, 255));
-                                if is_selected {
-                                    rt.strong()
-                                } else {
-                                    rt
-                                }
+                                if is_selected { rt.strong() } else { rt }.into()
                             }
                         };
 
@@ -295,25 +427,17 @@ pub struct LauncherApp {
     prev_input: String,
     /// Counts frames since creation; used to request OS focus during startup.
     startup_frames: u32,
-    /// Receives shortcuts collected on a background thread.
-    shortcut_rx: Option<std::sync::mpsc::Receiver<Vec<crate::launcher::ListItem>>>,
     /// Target monitor (1-based) to reposition the window onto after creation.
     monitor: Option<u32>,
 }
 
 impl LauncherApp {
-    pub fn new(
-        state: WindowState,
-        project_name: String,
-        shortcut_rx: Option<std::sync::mpsc::Receiver<Vec<crate::launcher::ListItem>>>,
-        monitor: Option<u32>,
-    ) -> Self {
+    pub fn new(state: WindowState, project_name: String, monitor: Option<u32>) -> Self {
         Self {
             state,
             project_name,
             prev_input: String::new(),
             startup_frames: 0,
-            shortcut_rx,
             monitor,
         }
     }
@@ -323,16 +447,9 @@ impl eframe::App for LauncherApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         use crate::ui::state::Visibility;
 
-        // Merge async-loaded shortcuts when ready
-        if let Some(rx) = self.shortcut_rx.take() {
-            match rx.try_recv() {
-                Ok(items) => self.state.append_items(items),
-                Err(std::sync::mpsc::TryRecvError::Disconnected) => {}
-                Err(std::sync::mpsc::TryRecvError::Empty) => {
-                    self.shortcut_rx = Some(rx);
-                    ctx.request_repaint();
-                }
-            }
+        // Merge background provider items as they arrive
+        if self.state.poll_items() {
+            ctx.request_repaint();
         }
 
         // Hide on focus loss (focused → unfocused transition only).
@@ -378,7 +495,7 @@ impl eframe::App for LauncherApp {
         render_launcher(
             ctx,
             &mut self.state,
-            &self.project_name,
+            &mut self.project_name,
             &mut self.prev_input,
         );
     }
@@ -1,5 +1,7 @@
+use crate::config::EnvMap;
 use anyhow::Result;
 use colored::*;
+use std::path::Path;
 use std::process::Command;
 
 pub fn open_command_with_args(
@@ -11,11 +13,19 @@ pub fn open_command_with_args(
     if let Some(browser) = browser {
         open_url_in_browser(command, browser, debug)
     } else {
-        run_terminal_command(command, args, debug)
+        run_terminal_command(command, args, None, &EnvMap::new(), debug)
     }
 }
 
-fn run_terminal_command(command: &str, args: Option<&str>, debug: bool) -> Result<()> {
+/// Run `command` plus `args` through the platform shell, in `cwd` when given,
+/// with `env` added to the inherited environment.
+pub fn run_terminal_command(
+    command: &str,
+    args: Option<&str>,
+    cwd: Option<&str>,
+    env: &EnvMap,
+    debug: bool,
+) -> Result<()> {
     let mut full_command = command.to_string();
     if let Some(args_str) = args {
         if !args_str.is_empty() {
@@ -28,21 +38,42 @@ fn run_terminal_command(command: &str, args: Option<&str>, debug: bool) -> Resul
         .filter(|s| !s.is_empty())
         .map(|a| format!(" {}", a))
         .unwrap_or_default();
+    let in_dir = cwd.map(|d| format!(" (in {})", d)).unwrap_or_default();
+    if let Some(dir) = cwd {
+        if !Path::new(dir).is_dir() {
+            anyhow::bail!("Working directory does not exist: {}", dir);
+        }
+    }
 
     if cfg!(target_os = "windows") {
         if debug {
-            println!("{}", format!("[debug] cmd /c {}", full_command).dimmed());
+            println!(
+                "{}",
+                format!("[debug] cmd /c {}{}", full_command, in_dir).dimmed()
+            );
         }
         // Spawn via cmd so PATH and .cmd scripts are resolved; don't wait.
-        Command::new("cmd")
-            .args(["/c", &full_command])
+        let mut cmd = Command::new("cmd");
+        if let Some(dir) = cwd {
+            cmd.current_dir(dir);
+        }
+        cmd.envs(env);
+        cmd.args(["/c", &full_command])
             .spawn()
             .map_err(|e| anyhow::anyhow!("Error running command: {}", e))?;
     } else {
         if debug {
-            println!("{}", format!("[debug] sh -c {}", full_command).dimmed());
+            println!(
+                "{}",
+                format!("[debug] sh -c {}{}", full_command, in_dir).dimmed()
+            );
+        }
+        let mut cmd = Command::new("sh");
+        if let Some(dir) = cwd {
+            cmd.current_dir(dir);
         }
-        let status = Command::new("sh")
+        cmd.envs(env);
+        let status = cmd
             .args(["-c", &full_command])
             .status()
             .map_err(|e| anyhow::anyhow!("Error running command: {}", e))?;
@@ -53,7 +84,7 @@ fn run_terminal_command(command: &str, args: Option<&str>, debug: bool) -> Resul
 
     println!(
         "{}",
-        format!("Running command: {}{}", command, args_str).green()
+        format!("Running command: {}{}{}", command, args_str, in_dir).green()
     );
     Ok(())
 }
@@ -100,6 +131,81 @@ fn parse_browser_with_args(browser: &str) -> (&str, Vec<&str>) {
     }
 }
 
+/// Whether a browser setting (executable plus optional args) can be launched
+/// on this machine: `default`, an existing path, a command on `PATH`, an
+/// installed app bundle (macOS) or a registered App Path (Windows).
+pub fn browser_available(browser: &str) -> bool {
+    let (name, _) = parse_browser_with_args(browser);
+    if name.eq_ignore_ascii_case("default") {
+        return true;
+    }
+    let path = Path::new(name);
+    if path.components().count() > 1 {
+        return path.exists();
+    }
+    if on_path(name) {
+        return true;
+    }
+    if cfg!(target_os = "macos") {
+        // `open -a` matches application bundles case-insensitively.
+        let bundle = format!("{}.app", name.trim_end_matches(".app")).to_lowercase();
+        let mut app_dirs = vec![
+            Path::new("/Applications").to_path_buf(),
+            Path::new("/System/Applications").to_path_buf(),
+        ];
+        app_dirs.extend(dirs::home_dir().map(|h| h.join("Applications")));
+        return app_dirs.iter().any(|dir| {
+            std::fs::read_dir(dir).is_ok_and(|entries| {
+                entries
+                    .flatten()
+                    .any(|e| e.file_name().to_string_lossy().to_lowercase() == bundle)
+            })
+        });
+    }
+    if cfg!(windows) {
+        // Start-Process also resolves executables registered under App Paths.
+        let exe = if name.to_lowercase().ends_with(".exe") {
+            name.to_string()
+        } else {
+            format!("{}.exe", name)
+        };
+        return ["HKLM", "HKCU"].iter().any(|hive| {
+            let key = format!(
+                "{}\\SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\App Paths\\{}",
+                hive, exe
+            );
+            Command::new("reg")
+                .args(["query", &key])
+                .output()
+                .is_ok_and(|o| o.status.success())
+        });
+    }
+    false
+}
+
+/// Whether `name` is an executable file in one of the `PATH` directories
+/// (trying each `PATHEXT` extension on Windows).
+fn on_path(name: &str) -> bool {
+    let Some(path) = std::env::var_os("PATH") else {
+        return false;
+    };
+    let extensions: Vec<String> = if cfg!(windows) {
+        std::env::var("PATHEXT")
+            .unwrap_or_else(|_| ".EXE;.CMD;.BAT".to_string())
+            .split(';')
+            .map(str::to_string)
+            .chain(std::iter::once(String::new()))
+            .collect()
+    } else {
+        vec![String::new()]
+    };
+    std::env::split_paths(&path).any(|dir| {
+        extensions
+            .iter()
+            .any(|ext| dir.join(format!("{}{}", name, ext)).is_file())
+    })
+}
+
 /// The `prefix`-joined extra args as they appear in a debug command line, or an
 /// empty string when there are none (so it can be spliced in unconditionally).
 fn debug_extra_args(prefix: &str, extra_args: &[&str]) -> String {
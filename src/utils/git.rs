@@ -0,0 +1,279 @@
+//! Git checkouts on disk: finding them under a directory, reading their
+//! `origin` remote, and turning a remote into the repository's web pages.
+//! Reads `.git` directly rather than running `git`, so scanning hundreds of
+//! checkouts stays fast.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Whether `dir` is the top of a checkout (`.git` is a directory, or a file
+/// for worktrees and submodules).
+pub fn is_repo(dir: &Path) -> bool {
+    dir.join(".git").exists()
+}
+
+/// Checkouts at most `depth` levels below `root` (its children are level 1;
+/// `root` itself counts as level 0), sorted by path. Checkouts aren't
+/// searched for nested ones, and hidden directories are skipped.
+pub fn find_repos(root: &Path, depth: usize) -> Vec<PathBuf> {
+    let mut repos = Vec::new();
+    if is_repo(root) {
+        repos.push(root.to_path_buf());
+    } else {
+        scan(root, depth, &mut repos);
+    }
+    repos.sort();
+    repos
+}
+
+fn scan(dir: &Path, depth: usize, repos: &mut Vec<PathBuf>) {
+    if depth == 0 {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        // `file_type` doesn't follow symlinks, so linked dirs aren't walked twice.
+        if hidden || !entry.file_type().is_ok_and(|t| t.is_dir()) {
+            continue;
+        }
+        let path = entry.path();
+        if is_repo(&path) {
+            repos.push(path);
+        } else {
+            scan(&path, depth - 1, repos);
+        }
+    }
+}
+
+/// The git directory of the checkout at `repo`, following a `.git` file's
+/// `gitdir:` pointer.
+fn git_dir(repo: &Path) -> Option<PathBuf> {
+    let dot_git = repo.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+    let pointer = fs::read_to_string(&dot_git).ok()?;
+    let dir = pointer.trim().strip_prefix("gitdir:")?.trim();
+    Some(repo.join(dir))
+}
+
+/// The URL of the `origin` remote of the checkout at `repo`.
+pub fn origin_url(repo: &Path) -> Option<String> {
+    let git_dir = git_dir(repo)?;
+    // A worktree's config lives in the main repository's git directory.
+    let common = fs::read_to_string(git_dir.join("commondir"))
+        .map(|dir| git_dir.join(dir.trim()))
+        .unwrap_or(git_dir);
+    let config = fs::read_to_string(common.join("config")).ok()?;
+    remote_url(&config, "origin")
+}
+
+/// The `url` of `[remote "<name>"]` in git config text.
+fn remote_url(config: &str, name: &str) -> Option<String> {
+    let section = format!("[remote \"{}\"]", name);
+    let mut in_section = false;
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_section = line == section;
+        } else if in_section {
+            if let Some((key, value)) = line.split_once('=') {
+                if key.trim() == "url" {
+                    return Some(value.trim().to_string());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// A remote as host and path, e.g. `("github.com", "acme/web")`: user,
+/// port, scheme and `.git` suffix dropped.
+pub fn parse_remote(remote: &str) -> Option<(String, String)> {
+    let (host, path) = match remote.split_once("://") {
+        // https://user@host:port/path, ssh://git@host:port/path
+        Some((_, rest)) => rest.split_once('/')?,
+        // scp-like git@host:path
+        None => remote.split_once(':')?,
+    };
+    let host = host.rsplit('@').next()?;
+    let host = host.split(':').next()?.to_lowercase();
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    if host.is_empty() || path.is_empty() {
+        return None;
+    }
+    Some((host, path.to_string()))
+}
+
+/// The web page of the repository a remote points at. Azure DevOps SSH
+/// remotes (`ssh.dev.azure.com:v3/org/project/repo`) are rewritten to their
+/// `dev.azure.com/org/project/_git/repo` page; other hosts serve the repo
+/// at its path.
+pub fn web_url(remote: &str) -> Option<String> {
+    let (host, path) = parse_remote(remote)?;
+    if host == "ssh.dev.azure.com" {
+        let mut parts = path.strip_prefix("v3/")?.splitn(3, '/');
+        let (org, project, repo) = (parts.next()?, parts.next()?, parts.next()?);
+        return Some(format!(
+            "https://dev.azure.com/{}/{}/_git/{}",
+            org, project, repo
+        ));
+    }
+    Some(format!("https://{}/{}", host, path))
+}
+
+/// The pages of a hosted repository: its web page, and where known its CI
+/// runs and issue tracker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteLinks {
+    pub web: String,
+    pub ci: Option<String>,
+    pub issues: Option<String>,
+}
+
+/// The [`RemoteLinks`] of a remote. CI and issues are known for GitHub
+/// (including `github.<company>` Enterprise hosts), GitLab (any host with
+/// `gitlab` in its name) and Azure DevOps, whose are per project rather than
+/// per repository.
+pub fn links(remote: &str) -> Option<RemoteLinks> {
+    let web = web_url(remote)?;
+    let (host, _) = parse_remote(&web)?;
+    let (ci, issues) = if let Some((project, _)) = web.split_once("/_git/") {
+        (
+            format!("{}/_build", project),
+            format!("{}/_workitems", project),
+        )
+    } else if host == "github.com" || host.starts_with("github.") {
+        (format!("{}/actions", web), format!("{}/issues", web))
+    } else if host.contains("gitlab") {
+        (format!("{}/-/pipelines", web), format!("{}/-/issues", web))
+    } else {
+        return Some(RemoteLinks {
+            web,
+            ci: None,
+            issues: None,
+        });
+    };
+    Some(RemoteLinks {
+        web,
+        ci: Some(ci),
+        issues: Some(issues),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn web_url_from_common_remote_forms() {
+        let cases = [
+            ("git@github.com:acme/web.git", "https://github.com/acme/web"),
+            (
+                "https://github.com/acme/web.git",
+                "https://github.com/acme/web",
+            ),
+            ("https://github.com/acme/web", "https://github.com/acme/web"),
+            (
+                "ssh://git@gitlab.example.com:2222/group/sub/api.git",
+                "https://gitlab.example.com/group/sub/api",
+            ),
+            (
+                "git@ssh.dev.azure.com:v3/acme/Platform/web",
+                "https://dev.azure.com/acme/Platform/_git/web",
+            ),
+            (
+                "https://acme@dev.azure.com/acme/Platform/_git/web",
+                "https://dev.azure.com/acme/Platform/_git/web",
+            ),
+        ];
+        for (remote, url) in cases {
+            assert_eq!(web_url(remote).as_deref(), Some(url), "{}", remote);
+        }
+        assert_eq!(web_url("/srv/git/web.git"), None);
+    }
+
+    #[test]
+    fn links_per_host() {
+        let cases = [
+            (
+                "git@github.com:acme/web.git",
+                "https://github.com/acme/web/actions",
+                "https://github.com/acme/web/issues",
+            ),
+            (
+                "https://gitlab.example.com/group/api.git",
+                "https://gitlab.example.com/group/api/-/pipelines",
+                "https://gitlab.example.com/group/api/-/issues",
+            ),
+            (
+                "git@ssh.dev.azure.com:v3/acme/Platform/web",
+                "https://dev.azure.com/acme/Platform/_build",
+                "https://dev.azure.com/acme/Platform/_workitems",
+            ),
+            (
+                "https://acme.visualstudio.com/Platform/_git/web",
+                "https://acme.visualstudio.com/Platform/_build",
+                "https://acme.visualstudio.com/Platform/_workitems",
+            ),
+        ];
+        for (remote, ci, issues) in cases {
+            let links = links(remote).unwrap();
+            assert_eq!(links.ci.as_deref(), Some(ci), "{}", remote);
+            assert_eq!(links.issues.as_deref(), Some(issues), "{}", remote);
+        }
+        assert_eq!(
+            links("git@bitbucket.org:acme/web.git"),
+            Some(RemoteLinks {
+                web: "https://bitbucket.org/acme/web".to_string(),
+                ci: None,
+                issues: None,
+            })
+        );
+    }
+
+    #[test]
+    fn remote_url_reads_the_named_section() {
+        let config = "[core]\n\tbare = false\n[remote \"upstream\"]\n\turl = git@github.com:up/web.git\n[remote \"origin\"]\n\turl = git@github.com:acme/web.git\n\tfetch = +refs/heads/*:refs/remotes/origin/*\n";
+        assert_eq!(
+            remote_url(config, "origin").as_deref(),
+            Some("git@github.com:acme/web.git")
+        );
+        assert_eq!(remote_url(config, "fork"), None);
+    }
+
+    #[test]
+    fn find_repos_stops_at_checkouts_and_depth() {
+        let root = std::env::temp_dir().join(format!("ps-git-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for dir in [
+            "web/.git",
+            "web/vendor/lib/.git",
+            "acme/api/.git",
+            "deep/a/b/.git",
+            ".hidden/x/.git",
+        ] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(
+            root.join("web/.git/config"),
+            "[remote \"origin\"]\n\turl = git@github.com:acme/web.git\n",
+        )
+        .unwrap();
+
+        let found: Vec<PathBuf> = find_repos(&root, 2)
+            .into_iter()
+            .map(|p| p.strip_prefix(&root).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(found, [PathBuf::from("acme/api"), PathBuf::from("web")]);
+        assert_eq!(find_repos(&root, 3).len(), 3);
+        assert_eq!(
+            origin_url(&root.join("web")).as_deref(),
+            Some("git@github.com:acme/web.git")
+        );
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,186 @@
+//! `${...}` placeholders in command `url`, `command`, `args` and `env` values,
+//! resolved when the command runs so a shared config can leave per-machine values and
+//! secrets out of the file.
+//!
+//! - `${env:NAME}` — environment variable
+//! - `${file:path}` — file contents without the trailing newline (`~` expanded)
+//! - `${client.name}`, `${client.path}`, `${project.name}`, `${project.path}` —
+//!   the active selection
+//!
+//! Any other `${...}`, like a shell's `${HOME}`, is left as it is. `$${` is
+//! a literal `${`.
+
+use crate::config::{self, ConfigManager, EnvMap};
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+
+/// Values for the `client.*` / `project.*` placeholders.
+#[derive(Debug, Default)]
+pub struct Vars {
+    selection: HashMap<&'static str, String>,
+}
+
+impl Vars {
+    /// Placeholders for the active client and project, if any.
+    pub fn from_selection(config_manager: &ConfigManager) -> Self {
+        let mut selection = HashMap::new();
+        let path = |p: &str| config::expand_tilde(p).display().to_string();
+        if let Some((client_name, client, project)) = config_manager.resolve_current() {
+            selection.insert("client.name", client_name.clone());
+            if let Some(p) = &client.path {
+                selection.insert("client.path", path(p));
+            }
+            if let Some((project_name, project)) = project {
+                selection.insert("project.name", project_name.clone());
+                if let Some(p) = &project.path {
+                    selection.insert("project.path", path(p));
+                }
+            }
+        }
+        Self { selection }
+    }
+
+    /// The value of placeholder `name`, or `None` when it isn't one of ours.
+    fn lookup(&self, name: &str) -> Result<Option<String>> {
+        if !is_placeholder(name) {
+            return Ok(None);
+        }
+        if let Some(var) = name.strip_prefix("env:") {
+            return std::env::var(var)
+                .map(Some)
+                .map_err(|_| anyhow::anyhow!("Environment variable '{}' is not set", var));
+        }
+        if let Some(file) = name.strip_prefix("file:") {
+            let path = config::expand_tilde(file);
+            let contents = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read '${{{}}}'", name))?;
+            return Ok(Some(contents.trim_end_matches(['\r', '\n']).to_string()));
+        }
+        match self.selection.get(name) {
+            Some(value) => Ok(Some(value.clone())),
+            None if is_selection_var(name) => {
+                anyhow::bail!("'${{{}}}' is not defined for the current selection", name)
+            }
+            None => anyhow::bail!("Unknown placeholder '${{{}}}'", name),
+        }
+    }
+}
+
+/// Whether `name` is in one of the placeholder namespaces.
+fn is_placeholder(name: &str) -> bool {
+    ["env:", "file:", "client.", "project."]
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
+fn is_selection_var(name: &str) -> bool {
+    matches!(
+        name,
+        "client.name" | "client.path" | "project.name" | "project.path"
+    )
+}
+
+/// Replace every placeholder in `text`, leaving other `${...}` untouched.
+/// Fails on the first placeholder that can't be resolved.
+pub fn expand(text: &str, vars: &Vars) -> Result<String> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            out.push_str(&rest[..start - 1]);
+            out.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        out.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('}') else {
+            if is_placeholder(rest[start + 2..].trim_start()) {
+                anyhow::bail!("Unterminated placeholder in '{}'", text);
+            }
+            rest = &rest[start..];
+            break;
+        };
+        let whole = &rest[start..start + len + 1];
+        match vars.lookup(whole[2..whole.len() - 1].trim())? {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(whole),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// [`expand`] every value of an environment map.
+pub fn expand_env(env: &EnvMap, vars: &Vars) -> Result<EnvMap> {
+    env.iter()
+        .map(|(name, value)| {
+            expand(value, vars)
+                .with_context(|| format!("In environment variable '{}'", name))
+                .map(|value| (name.clone(), value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> Vars {
+        Vars::from_selection(&ConfigManager::from_test_yaml(
+            "currentClient: acme\ncurrentProject: api\nclients:\n- name: acme\n  path: /src/acme\n  projects:\n  - name: api\n",
+        ))
+    }
+
+    #[test]
+    fn expands_selection_and_env() {
+        std::env::set_var("PS_INTERPOLATE_TEST", "s3cr3t");
+        assert_eq!(
+            expand(
+                "cd ${client.path} && deploy ${project.name} --token ${env:PS_INTERPOLATE_TEST}",
+                &vars()
+            )
+            .unwrap(),
+            "cd /src/acme && deploy api --token s3cr3t"
+        );
+    }
+
+    #[test]
+    fn reads_file_without_trailing_newline() {
+        let path = std::env::temp_dir().join(format!("ps-token-{}", std::process::id()));
+        std::fs::write(&path, "abc123\n").unwrap();
+        let text = format!("https://ci.example.com/?token=${{file:{}}}", path.display());
+        let expanded = expand(&text, &vars());
+        std::fs::remove_file(&path).ok();
+        assert_eq!(expanded.unwrap(), "https://ci.example.com/?token=abc123");
+    }
+
+    #[test]
+    fn undefined_values_are_errors() {
+        let vars = vars();
+        let err = expand("${project.path}", &vars).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "'${project.path}' is not defined for the current selection"
+        );
+        let err = expand("${env:PS_INTERPOLATE_UNSET}", &vars).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Environment variable 'PS_INTERPOLATE_UNSET' is not set"
+        );
+        assert!(expand("${client.nmae}", &vars).is_err());
+        assert!(expand("${env:HOME", &vars).is_err());
+    }
+
+    #[test]
+    fn escaped_and_plain_text_pass_through() {
+        assert_eq!(
+            expand("echo $${HOME} $PATH {}", &Vars::default()).unwrap(),
+            "echo ${HOME} $PATH {}"
+        );
+        assert_eq!(
+            expand("echo ${HOME} ${x:-1} ${", &Vars::default()).unwrap(),
+            "echo ${HOME} ${x:-1} ${"
+        );
+    }
+}
@@ -5,8 +5,13 @@ use std::time::SystemTime;
 const MAX_LOG_SIZE: u64 = 64 * 1024;
 
 /// Appends a timestamped error line to ~/.project-switch.log.
-/// Truncates the file first if it exceeds 64 KB.
 pub fn append_error(message: &str) {
+    append(message);
+}
+
+/// Appends a timestamped entry (which may span several lines) to
+/// ~/.project-switch.log. Truncates the file first if it exceeds 64 KB.
+pub fn append(message: &str) {
     let Some(home) = dirs::home_dir() else {
         return;
     };
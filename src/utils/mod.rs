@@ -1,4 +1,9 @@
 pub mod browser;
+pub mod file;
+pub mod git;
+pub mod interpolate;
 pub mod log;
+pub mod output;
+pub mod shell;
 pub mod shortcuts;
 pub mod url;
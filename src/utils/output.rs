@@ -0,0 +1,266 @@
+//! Machine-readable output (`--format json|yaml|plain`) for scripts and
+//! status bars. Field names here are a stable interface: add fields, never
+//! rename or remove them.
+
+use crate::config::{self, ConfigManager, ProjectCommand, Step};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::io::Write;
+
+/// Exit code when there is no current selection (or it points at a client
+/// that no longer exists). 1 is reserved for errors and 2 for usage errors.
+pub const EXIT_NO_SELECTION: i32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    Json,
+    Yaml,
+    /// Uncolored, line-oriented text (tab-separated where there are columns).
+    Plain,
+}
+
+/// The resolved active selection.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SelectionOutput {
+    pub client: Option<String>,
+    pub project: Option<String>,
+    /// Project path, falling back to the client path.
+    pub path: Option<String>,
+    /// Project description, falling back to the client description.
+    pub description: Option<String>,
+    /// Effective browser: project > client > global default.
+    pub browser: String,
+}
+
+impl SelectionOutput {
+    pub fn from_config(config_manager: &ConfigManager) -> Self {
+        let browser = config_manager.get_effective_browser().to_string();
+        match config_manager.resolve_current() {
+            Some((client_name, client, project)) => Self {
+                client: Some(client_name.clone()),
+                project: project.map(|(name, _)| name.clone()),
+                path: project
+                    .and_then(|(_, p)| p.path.clone())
+                    .or_else(|| client.path.clone()),
+                description: project
+                    .and_then(|(_, p)| p.description.clone())
+                    .or_else(|| client.description.clone()),
+                browser,
+            },
+            None => Self {
+                client: None,
+                project: None,
+                path: None,
+                description: None,
+                browser,
+            },
+        }
+    }
+
+    /// `client / project`, `client`, or empty when nothing is selected.
+    pub fn label(&self) -> String {
+        match (&self.client, &self.project) {
+            (Some(c), Some(p)) => format!("{} / {}", c, p),
+            (Some(c), None) => c.clone(),
+            _ => String::new(),
+        }
+    }
+}
+
+/// One entry of the effective command set.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CommandOutput {
+    pub key: String,
+    pub aliases: Vec<String>,
+    pub url: Option<String>,
+    pub command: Option<String>,
+    pub args: Option<String>,
+    pub cwd: Option<String>,
+    /// Browser a URL command opens in (command > project > client > global
+    /// default); `None` for `command:` entries and webview commands.
+    pub browser: Option<String>,
+    pub steps: Option<Vec<Step>>,
+    pub parallel: bool,
+    pub webview: bool,
+    pub pinned: bool,
+}
+
+impl CommandOutput {
+    pub fn from_command(cmd: &ProjectCommand, effective_browser: &str) -> Self {
+        let opens_in_browser =
+            cmd.url.is_some() && cmd.command.is_none() && cmd.steps.is_none() && !cmd.webview;
+        Self {
+            key: cmd.key.clone(),
+            aliases: cmd.aliases.clone().unwrap_or_default(),
+            url: cmd.url.clone(),
+            command: cmd.command.clone(),
+            args: cmd.args.clone(),
+            cwd: cmd.cwd.clone(),
+            browser: opens_in_browser.then(|| {
+                cmd.browser
+                    .as_deref()
+                    .unwrap_or(effective_browser)
+                    .to_string()
+            }),
+            steps: cmd.steps.clone(),
+            parallel: cmd.parallel,
+            webview: cmd.webview,
+            pinned: cmd.pinned,
+        }
+    }
+}
+
+/// `list` output: the active selection plus its effective command set.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ListOutput {
+    pub selection: SelectionOutput,
+    pub commands: Vec<CommandOutput>,
+}
+
+impl ListOutput {
+    /// One `key<TAB>url-or-command` line per command (its steps for a
+    /// `steps:` command).
+    pub fn plain(&self) -> String {
+        self.commands
+            .iter()
+            .map(|c| {
+                let detail = match &c.steps {
+                    Some(steps) => config::steps_detail(steps, c.parallel),
+                    None => c
+                        .url
+                        .clone()
+                        .or_else(|| c.command.clone())
+                        .unwrap_or_default(),
+                };
+                format!("{}\t{}", c.key, detail)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// `switch` output: the new selection and whether it actually changed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SwitchOutput {
+    pub changed: bool,
+    #[serde(flatten)]
+    pub selection: SelectionOutput,
+}
+
+/// Serialize `value` in the requested format to stdout. `plain` renders the
+/// line-oriented text form.
+pub fn print<T: Serialize>(
+    format: OutputFormat,
+    value: &T,
+    plain: impl FnOnce(&T) -> String,
+) -> Result<()> {
+    let text = render(format, value, plain)?;
+    let mut stdout = std::io::stdout();
+    stdout.write_all(text.as_bytes())?;
+    // Callers may `process::exit` with a status code right after printing.
+    stdout.flush()?;
+    Ok(())
+}
+
+fn render<T: Serialize>(
+    format: OutputFormat,
+    value: &T,
+    plain: impl FnOnce(&T) -> String,
+) -> Result<String> {
+    Ok(match format {
+        OutputFormat::Json => {
+            let mut json =
+                serde_json::to_string_pretty(value).context("Failed to serialize JSON")?;
+            json.push('\n');
+            json
+        }
+        OutputFormat::Yaml => serde_yaml::to_string(value).context("Failed to serialize YAML")?,
+        OutputFormat::Plain => {
+            let mut text = plain(value);
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+            text
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url_command(key: &str) -> ProjectCommand {
+        ProjectCommand {
+            key: key.to_string(),
+            aliases: None,
+            url: Some(format!("https://{}/", key)),
+            command: None,
+            browser: None,
+            args: None,
+            cwd: None,
+            steps: None,
+            parallel: false,
+            webview: false,
+            pinned: false,
+        }
+    }
+
+    #[test]
+    fn url_command_inherits_effective_browser() {
+        let out = CommandOutput::from_command(&url_command("docs"), "chrome");
+        assert_eq!(out.browser.as_deref(), Some("chrome"));
+    }
+
+    #[test]
+    fn url_command_browser_overrides_effective() {
+        let mut cmd = url_command("docs");
+        cmd.browser = Some("edge".to_string());
+        let out = CommandOutput::from_command(&cmd, "chrome");
+        assert_eq!(out.browser.as_deref(), Some("edge"));
+    }
+
+    #[test]
+    fn terminal_and_webview_commands_have_no_browser() {
+        let mut terminal = url_command("build");
+        terminal.url = None;
+        terminal.command = Some("cargo build".to_string());
+        assert_eq!(
+            CommandOutput::from_command(&terminal, "chrome").browser,
+            None
+        );
+
+        let mut webview = url_command("app");
+        webview.webview = true;
+        assert_eq!(
+            CommandOutput::from_command(&webview, "chrome").browser,
+            None
+        );
+    }
+
+    #[test]
+    fn json_keeps_null_fields_for_stable_shape() {
+        let selection = SelectionOutput {
+            client: Some("acme".to_string()),
+            project: None,
+            path: None,
+            description: None,
+            browser: "firefox".to_string(),
+        };
+        let json = render(OutputFormat::Json, &selection, |_| String::new()).unwrap();
+        assert!(json.contains("\"project\": null"), "got: {}", json);
+        assert!(json.contains("\"browser\": \"firefox\""), "got: {}", json);
+    }
+
+    #[test]
+    fn plain_uses_label_with_trailing_newline() {
+        let selection = SelectionOutput {
+            client: Some("acme".to_string()),
+            project: Some("web".to_string()),
+            path: None,
+            description: None,
+            browser: "firefox".to_string(),
+        };
+        let text = render(OutputFormat::Plain, &selection, SelectionOutput::label).unwrap();
+        assert_eq!(text, "acme / web\n");
+    }
+}
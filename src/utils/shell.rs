@@ -0,0 +1,123 @@
+//! Config-supplied shell commands (switch hooks, script providers): run
+//! through the platform shell with a timeout, collecting their output.
+
+use crate::config::EnvMap;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+pub enum Outcome {
+    Ok,
+    Failed(String),
+}
+
+#[derive(Debug, Default)]
+pub struct Output {
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Run `command` through `sh -c` (`cmd /c` on Windows) in `cwd` when it is a
+/// directory, with `env` added to the inherited environment. Killed after
+/// `timeout`.
+pub fn run(
+    command: &str,
+    cwd: Option<&Path>,
+    env: &EnvMap,
+    timeout: Duration,
+) -> (Output, Outcome) {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/c", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    };
+    if let Some(dir) = cwd.filter(|dir| dir.is_dir()) {
+        cmd.current_dir(dir);
+    }
+    cmd.envs(env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => return (Output::default(), Outcome::Failed(e.to_string())),
+    };
+
+    // Read both pipes on threads so a chatty command can't fill one and block.
+    let (tx, rx) = mpsc::channel();
+    let pipes: [Option<Box<dyn Read + Send>>; 2] = [
+        child.stdout.take().map(|p| Box::new(p) as _),
+        child.stderr.take().map(|p| Box::new(p) as _),
+    ];
+    for (i, pipe) in pipes.into_iter().enumerate() {
+        let Some(mut pipe) = pipe else {
+            continue;
+        };
+        let tx = tx.clone();
+        std::thread::spawn(move || {
+            let mut text = String::new();
+            let _ = pipe.read_to_string(&mut text);
+            let _ = tx.send((i, text));
+        });
+    }
+    drop(tx);
+
+    let deadline = Instant::now() + timeout;
+    let outcome = loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => break Outcome::Ok,
+            Ok(Some(status)) => break Outcome::Failed(status.to_string()),
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break Outcome::Failed(format!("timed out after {}s", timeout.as_secs()));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(20)),
+            Err(e) => break Outcome::Failed(e.to_string()),
+        }
+    };
+
+    // A background process left holding the pipes mustn't stall the caller.
+    let mut output = Output::default();
+    while let Ok((i, text)) = rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+        match i {
+            0 => output.stdout = text,
+            _ => output.stderr = text,
+        }
+    }
+    (output, outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn commands_capture_output_and_time_out() {
+        let env: EnvMap = [("SHELL_TEST".to_string(), "hi".to_string())].into();
+        let (output, outcome) = run(
+            "echo $SHELL_TEST; echo err >&2",
+            None,
+            &env,
+            Duration::from_secs(5),
+        );
+        assert!(matches!(outcome, Outcome::Ok));
+        assert_eq!(output.stdout, "hi\n");
+        assert_eq!(output.stderr, "err\n");
+
+        let (_, outcome) = run("exit 3", None, &env, Duration::from_secs(5));
+        assert!(matches!(outcome, Outcome::Failed(ref r) if r.contains('3')));
+
+        let started = Instant::now();
+        let (_, outcome) = run("sleep 5", None, &env, Duration::from_millis(100));
+        assert!(matches!(outcome, Outcome::Failed(ref r) if r.starts_with("timed out")));
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+}